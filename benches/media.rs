@@ -0,0 +1,53 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use lazytool::media::Parser;
+use lazytool::{Episode, RegexParser};
+use regex::Regex;
+use std::hint::black_box;
+
+/// 内置模式的示例路径与大量不相关的路径混合，模拟扫描整个磁盘的场景
+fn paths() -> Vec<String> {
+    let samples = [
+        "/Volumes/影片/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4",
+        "/Volumes/Getea/影片/电影/黄渤/疯狂的赛车.2009.01201.mp4",
+        "/Volumes/ZhiTai/影片/电视剧/约会专家.1080P/约会专家第04集.mp4",
+        "/Volumes/ZhiTai/影片/电视剧/怪侠一枝梅.全30集.4K/02.mp4",
+        "/Volumes/ZhiTai/影片/综艺/向往的生活第20210115期.mkv",
+    ];
+    let mut paths = vec![];
+    for i in 0..1000 {
+        match i % 10 {
+            0..=1 => paths.push(samples[i % samples.len()].to_string()),
+            2..=5 => paths.push(format!("/Users/wxnacy/Pictures/2024/旅行/IMG_{i:04}.jpg")),
+            6..=7 => paths.push(format!("/Users/wxnacy/Documents/工作/周报-{i}.docx")),
+            _ => paths.push(format!("/Users/wxnacy/Music/周杰伦/{i:03}.flac")),
+        }
+    }
+    paths
+}
+
+fn bench_from_path(c: &mut Criterion) {
+    let paths = paths();
+    let mut group = c.benchmark_group("from_path");
+
+    // 对每个模式直接运行正则，即没有快速排除时的开销
+    let regexes: Vec<Regex> = Episode::PARSERS.iter().map(|(pattern, _)| Regex::new(pattern).unwrap()).collect();
+    group.bench_function("regex_only", |b| {
+        b.iter(|| paths.iter().filter(|path| regexes.iter().any(|re| re.captures(black_box(path)).is_some())).count())
+    });
+
+    let parsers: Vec<RegexParser> = Episode::PARSERS.iter()
+        .map(|(pattern, indexes)| RegexParser::new(pattern, indexes.to_vec()))
+        .collect();
+    group.bench_function("prefilter", |b| {
+        b.iter(|| paths.iter().filter(|path| parsers.iter().any(|p| p.parse(black_box(path)).is_some())).count())
+    });
+
+    // 内置解析器只构建一次，正则编译后缓存
+    group.bench_function("episode_from_path", |b| {
+        b.iter(|| paths.iter().filter(|path| Episode::from_path(black_box(path)).unwrap().is_some()).count())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_from_path);
+criterion_main!(benches);
@@ -0,0 +1,19 @@
+use lazytool::Episode;
+
+fn main() {
+    let path = match std::env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: cargo run --example media -- <path>");
+            std::process::exit(1);
+        }
+    };
+
+    match Episode::from_path(&path).unwrap() {
+        Some(ep) => println!("{}", ep.to_json().unwrap()),
+        None => {
+            eprintln!("No episode matched: {path}");
+            std::process::exit(1);
+        }
+    }
+}
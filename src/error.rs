@@ -0,0 +1,131 @@
+use std::io;
+use std::path::PathBuf;
+
+/// lazytool 的错误类型
+///
+/// 各模块的函数统一返回该错误，调用方可以按变体区分错误的种类。
+/// 实现了 `std::error::Error`，也可以直接通过 `?` 转为 `anyhow::Error` 等通用错误
+///
+/// Examples
+///
+/// ```
+/// use lazytool::{time, Error};
+///
+/// match time::from_str("2025-13-01", "%Y-%m-%d") {
+///     Err(Error::ParseTime { input, fmt }) => {
+///         assert_eq!(input, "2025-13-01");
+///         assert_eq!(fmt, "%Y-%m-%d");
+///     }
+///     other => panic!("unexpected {other:?}"),
+/// }
+///
+/// // 与 anyhow 兼容
+/// fn run() -> anyhow::Result<i64> {
+///     Ok(time::to_timestamp_utc("2025-01-15 10:16:13", "%Y-%m-%d %H:%M:%S")?)
+/// }
+/// assert_eq!(run().unwrap(), 1736936173);
+/// ```
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// 路径不是合法的 UTF-8 或缺少文件名等必要的部分
+    #[error("Invalid path {0:?}")]
+    InvalidPath(PathBuf),
+
+    /// 路径已存在但不是目录
+    #[error("Path {0:?} exists but is not a directory")]
+    NotADirectory(PathBuf),
+
+    /// 路径不是文件
+    #[error("Path {0:?} is not a file")]
+    NotAFile(PathBuf),
+
+    /// 目标路径已存在
+    #[error("Destination {0:?} already exists")]
+    AlreadyExists(PathBuf),
+
+    /// 复制后文件大小与源文件不一致
+    #[error("Size mismatch after copying to {path:?}: expected {expected}, got {actual}")]
+    SizeMismatch { path: PathBuf, expected: u64, actual: u64 },
+
+    /// 正则表达式无法编译
+    #[error("Invalid pattern {pattern:?}: {source}")]
+    PatternError { pattern: String, source: regex::Error },
+
+    /// glob 或文件名模板等格式不合法
+    #[error("Invalid pattern {pattern:?}: {reason}")]
+    InvalidPattern { pattern: String, reason: String },
+
+    /// 环境变量未定义
+    #[error("Undefined environment variable {name:?} in {input:?}")]
+    UndefinedVariable { name: String, input: String },
+
+    /// 时间字符串与格式不匹配
+    #[error("Invalid datetime {input:?} for format {fmt:?}")]
+    ParseTime { input: String, fmt: String },
+
+    /// 时间合法但不存在或有歧义，如夏令时切换时的本地时间，以及不合法的月份、间隔等
+    #[error("Invalid time: {0}")]
+    InvalidTime(String),
+
+    /// 时间戳超出可表示的范围
+    #[error("Timestamp out of range: {0}")]
+    TimestampOutOfRange(i64),
+
+    /// 未知的时区名称
+    #[error("Invalid timezone: {0}")]
+    UnknownTimezone(String),
+
+    /// JSON 序列化或解析失败
+    #[error("Invalid JSON{}: {source}", .path.as_ref().map(|p| format!(" in {p:?}")).unwrap_or_default())]
+    Json { path: Option<PathBuf>, source: serde_json::Error },
+
+    /// TOML 配置文件解析失败
+    #[cfg(feature = "config")]
+    #[error("Invalid TOML{}: {source}", .path.as_ref().map(|p| format!(" in {p:?}")).unwrap_or_default())]
+    Toml { path: Option<PathBuf>, source: toml::de::Error },
+
+    /// 文件系统操作失败，`context` 描述失败的操作及路径
+    #[error("{context}: {source}")]
+    Io { context: String, source: io::Error },
+}
+
+impl Error {
+    /// 带上下文的 IO 错误
+    pub(crate) fn io(context: impl Into<String>, source: io::Error) -> Self {
+        Self::Io { context: context.into(), source }
+    }
+
+    /// IO 错误的种类，不是 IO 错误时为 `None`
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::path;
+    /// use std::io::ErrorKind;
+    ///
+    /// let err = path::file_size("/no/such/lazytool/file").unwrap_err();
+    /// assert_eq!(err.io_kind(), Some(ErrorKind::NotFound));
+    /// ```
+    pub fn io_kind(&self) -> Option<io::ErrorKind> {
+        match self {
+            Self::Io { source, .. } => Some(source.kind()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Self::io("I/O error", source)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(source: serde_json::Error) -> Self {
+        Self::Json { path: None, source }
+    }
+}
+
+/// 以 `Error` 为错误类型的 `Result`
+pub type Result<T, E = Error> = std::result::Result<T, E>;
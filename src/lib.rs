@@ -1,10 +1,33 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod path_str;
+#[cfg(feature = "std")]
 pub mod path;
+#[cfg(feature = "std")]
 pub mod time;
+#[cfg(feature = "std")]
 pub mod media;
+#[cfg(feature = "std")]
+pub mod error;
 
+#[cfg(feature = "std")]
+pub use error::{Error, Result};
+#[cfg(feature = "std")]
 pub use path::expand_user;
+#[cfg(feature = "std")]
 pub use time::{
     current_timestamp,
+    current_timestamp_millis,
+    current_timestamp_nanos,
     to_timestamp,
+    to_timestamp_millis,
+};
+#[cfg(feature = "timezone")]
+pub use time::{
+    from_str_with_timezone,
+    to_timestamp_with_timezone,
 };
-pub use media::{Episode, RegexParser};
+#[cfg(feature = "std")]
+pub use media::{Episode, Media, MediaKind, Movie, RegexParser, Resolution, TemplateParser};
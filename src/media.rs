@@ -1,61 +1,166 @@
-use anyhow::{Result, anyhow};
-use std::path::Path;
-use serde::Deserialize;
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::num::IntErrorKind;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
 
+use chrono::{Datelike, NaiveDate};
+use log::{debug, trace};
+use once_cell::sync::{Lazy, OnceCell};
 use regex::Regex;
+use regex_syntax::hir::{Hir, HirKind};
 
+pub mod chinese_num;
 
-#[derive(Debug, Clone)]
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Episode {
     // 剧名
     pub title: Option<String>,
 
-    // 季数
-    pub season: Option<u16>,
+    // 季数，特别篇（`S00`、`Specials`）为 0，路径中没有季数时为解析器的默认季数，通常为 1
+    pub season: Option<u32>,
 
     // 集数
-    pub episode: Option<u16>,
+    pub episode: Option<u32>,
+
+    // 结束集数，一个文件包含多集（如 `E01-E02`）时为最后一集，单集时为 None
+    pub episode_end: Option<u32>,
+
+    // 年份
+    pub year: Option<u16>,
+
+    // 播出日期，综艺节目按日期分期
+    pub air_date: Option<NaiveDate>,
+
+    // 容器格式，即视频文件扩展名，没有扩展名时为 None
+    pub container: Option<String>,
+
+    // 分辨率，从文件名或所在目录名中的 `1080p`、`4K` 等标记识别
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<Resolution>,
+
+    // 视频编码，如 `HEVC`、`x265`，由解析器的 `video_codec` 分组捕获
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub video_codec: Option<String>,
+
+    // 音频编码，如 `AAC`、`DDP5.1`，由解析器的 `audio_codec` 分组捕获
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_codec: Option<String>,
+
+    // 片源，如 `WEB-DL`、`BluRay`，由解析器的 `source` 分组捕获
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+
+    // 匹配可信度 0.0 - 1.0，计算方式见 `Episode::score`
+    #[serde(default)]
+    pub confidence: f32,
+
+    // 解析来源的文件路径，由 `from_path`、`scan_dir` 等填充，手动构造或 `from_filename` 解析时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_path: Option<PathBuf>,
 }
 
 impl Episode {
-    /// 静态匹配数据
-    pub const PARSERS: [(&str, [usize; 3]); 7] = [
-        // (pattern, indexs)
+    /// 静态匹配数据，文件扩展名均为可选
+    pub const PARSERS: [(&str, &[usize]); 13] = [
+        // (pattern, indexs) indexs 依次为 title, season, episode, year(可选), air_date(可选), episode_end(可选), resolution(可选), video_codec(可选), audio_codec(可选), source(可选)
         // 匹配模式 1: /影片/电视剧/医馆笑传/医馆笑传S01.37集.1080P/01.mkv
         // title: 医馆笑传
         // season: 1
         // episode: 37
-        (r"^(.*?)/([^/]+)S(\d{2})\.(\d{1,2})集\.(\d{4}P)/(\d{2})\.(\w+)$", [2, 3, 6]),
+        // resolution: 1080P
+        (r"^(.*?)/([^/]+)S(\d{2})\.(\d{1,2})集\.(\d{4}P)/(\d{2})(?:\.(\w+))?$", &[2, 3, 6, 0, 0, 0, 5]),
         // 匹配模式 2: /还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4
         // title: 还珠格格
         // season: 1
         // episode: 2
-        (r"^(.*?)/([^/]+)S(\d{2})E(\d{2})\.(\w+)$", [2, 3, 4]),
+        (r"^(.*?)/([^/]+)S(\d{2})E(\d{2})(?:\.(\w+))?$", &[2, 3, 4]),
         // 匹配模式 3: /Volumes/ZhiTai/影片/电视剧/爱情公寓/S4 (2014) 4K/01.mp4
         // title: 爱情公寓
         // season: 4
         // episode: 1
-        (r"^(.*?)/([^/]+)/S(\d{1,2})\s+\(\d{4}\)\s+\d{1,2}K/(\d{2})\.(\w+)$", [2, 3, 4]),
+        // year: 2014
+        (r"^(.*?)/([^/]+)/S(\d{1,2})\s+\((\d{4})\)\s+\d{1,2}K/(\d{2})(?:\.(\w+))?$", &[2, 3, 5, 4]),
         // 匹配模式 4: /电视剧/龙门镖局/龙门镖局 (2013) 4K/龙门镖局.Longmen.Express.2013.E02.4K.2160p.HEVC.AAC-DHTCLUB.mp4
         // title: 龙门镖局
         // season: 固定=1 indexs 中间用 0 表示
         // episode: 2
-        (r"/([^/]+)/([^/]+) \(.*\) .*E(\d{2})", [2, 0, 3]),
+        // year: 2013
+        (r"/([^/]+)/([^/]+) \((.*)\) .*E(\d{2})", &[2, 0, 4, 3]),
         // 匹配模式 5: /Volumes/Getea/影片/电影/黄渤/疯狂的赛车.2009.01201.mp4
         // title: 疯狂的赛车
         // season: 2009
         // episode: 01201
-        (r"^(.*?)/([^/]+)\.(\d{4})\.(\d{5})\.\w+$", [2, 3, 4]),
+        (Self::MOVIE_PATTERN, &[2, 3, 4]),
         // 匹配模式 6: /Volumes/ZhiTai/影片/电视剧/约会专家.1080P/约会专家第04集.mp4
         // title: 约会专家
         // season: 固定=1 indexs 中间用 0 表示
         // episode: 4
-        (r"^(.*?)/([^/]+)\.1080P/.*?第(\d{1,2})集\.\w+$", [2, 0, 3]),
+        (r"^(.*?)/([^/]+)\.1080P/.*?第(\d{1,2})集(?:\.\w+)?$", &[2, 0, 3]),
         // 匹配模式 7: /Volumes/ZhiTai/影片/电视剧/怪侠一枝梅.全30集.4K/02.mp4
         // title: 怪侠一枝梅
         // season: 固定=1 indexs 中间用 0 表示
         // episode: 2
-        (r"^(.*?)/([^/]+)\.全\d+集\.\d+K/(\d{2})\.(\w+)$", [2, 0, 3]),
+        (r"^(.*?)/([^/]+)\.全\d+集\.\d+K/(\d{2})(?:\.(\w+))?$", &[2, 0, 3]),
+        // 匹配模式 8: /Volumes/ZhiTai/影片/综艺/向往的生活第20210115期.mkv
+        // title: 向往的生活
+        // season: 固定=1 indexs 中间用 0 表示
+        // episode: 无 indexs 中用 0 表示
+        // air_date: 2021-01-15
+        (r"(?:^|/)([^/]+?)第(\d{8})期(?:\.(\w+))?$", &[1, 0, 0, 0, 2]),
+        // 匹配模式 9: /还珠格格S01.国语中字.无台标.1080P/还珠格格S01E01-E02.mp4
+        // title: 还珠格格
+        // season: 1
+        // episode: 1
+        // episode_end: 2
+        (r"^(.*?)/([^/]+)S(\d{2})E(\d{2})-E?(\d{2})(?:\.(\w+))?$", &[2, 3, 4, 0, 0, 5]),
+        // 匹配模式 10: /TV/Show.Name.2023.S02E10.REPACK.1080p.WEB-DL.x265.mkv
+        // title: Show Name
+        // season: 2
+        // episode: 10
+        // year: 2023（可选）
+        // episode_end: 可选，如 S01E05E06、S01E05-E06
+        (r"/(?P<title>[^/]+?)[. _-]+(?:\(?(?P<year>(?:19|20)\d{2})\)?[. _-]+)?[Ss](?P<season>\d{1,2})[Ee](?P<episode>\d{1,3})(?:-?[Ee](?P<episode_end>\d{1,3}))?(?:[. _-][^/]*?)?(?:\.\w+)?$", &[]),
+        // 匹配模式 11: /TV/Show Name - 1x05 - Title.mkv
+        // title: Show Name
+        // season: 1
+        // episode: 5
+        (r"/(?P<title>[^/]+?)[. _-]+(?P<season>\d{1,2})x(?P<episode>\d{2,3})(?:[. _-][^/]*?)?(?:\.\w+)?$", &[]),
+        // 匹配模式 12: /影片/电视剧/约会专家/约会专家第十二集.mp4
+        // title: 约会专家
+        // season: 固定=1
+        // episode: 12，中文数字见 `chinese_num::parse`
+        (r"/(?P<title>[^/]+?)[. _-]*第(?P<episode>[零〇一二两三四五六七八九十百千]{1,6})集(?:[. _-][^/]*?)?(?:\.\w+)?$", &[]),
+        // 匹配模式 13: /影片/电视剧/武林外传/第二季/05.mp4、/影片/电视剧/武林外传第二季/第十二集.mp4
+        // title: 武林外传
+        // season: 2，可以是中文数字或阿拉伯数字
+        // episode: 5
+        (r"/(?P<title>[^/]+?)(?:/|[. _-]*)第(?P<season>[零〇一二两三四五六七八九十\d]{1,3})季/(?:[^/]*?第)?(?P<episode>[零〇一二两三四五六七八九十百千\d]{1,6})集?(?:[. _-][^/]*?)?(?:\.\w+)?$", &[]),
+    ];
+
+    /// `PARSERS` 中模式 5 的正则，描述的实际是电影，`detect` 中不参与剧集的匹配
+    const MOVIE_PATTERN: &'static str = r"^(.*?)/([^/]+)\.(\d{4})\.(\d{5})(?:\.\w+)?$";
+
+    /// 只针对不带目录的文件名的模式，如种子客户端中的文件名，由 `from_filename` 优先尝试，格式与 `PARSERS` 相同
+    pub const FILENAME_PARSERS: [(&str, &[usize]); 3] = [
+        // 匹配模式 1: [字幕组] Show Name - 05 [1080p].mkv
+        // title: Show Name
+        // season: 固定=1
+        // episode: 5
+        (r"^\[[^\]/]+\][ _]*(?P<title>[^/\[\]]+?)[ _]+-[ _]+(?P<episode>\d{1,4})(?:[vV]\d)?(?:[ _]*[\[(][^/]*?)?(?:\.\w+)?$", &[]),
+        // 匹配模式 2: Show.Name.2013.E02.4K.mp4、Show Name EP05.mkv
+        // title: Show Name
+        // season: 固定=1
+        // episode: 2
+        // year: 2013（可选）
+        (r"^(?P<title>[^/]+?)(?:[. _-]+\(?(?P<year>(?:19|20)\d{2})\)?)?(?:[. _]+|[ _]*-[ _]+)[Ee][Pp]?(?P<episode>\d{1,4})(?:[. _-][^/]*?)?(?:\.\w+)?$", &[]),
+        // 匹配模式 3: 约会专家第04集.mp4、约会专家 第04话.mkv
+        // title: 约会专家
+        // season: 固定=1
+        // episode: 4
+        (r"^(?P<title>[^/]+?)[. _-]*第(?P<episode>[\d零〇一二两三四五六七八九十百千]{1,6})[集话話](?:[. _-][^/]*?)?(?:\.\w+)?$", &[]),
     ];
 
     /// 从地址中解析剧集信息
@@ -72,6 +177,7 @@ impl Episode {
     ///     assert_eq!(ep.title, Some("还珠格格".to_string()));
     ///     assert_eq!(ep.season, Some(1));
     ///     assert_eq!(ep.episode, Some(2));
+    ///     assert_eq!(ep.source_path, Some(path.into()));
     /// }
     ///
     /// let path = "/Volumes/医馆笑传S02.37集.1080P/03.mp4";
@@ -84,161 +190,3774 @@ impl Episode {
     /// }
     /// ```
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Option<Self>> {
-        // 尝试匹配每个模式
-        let mut parsers = vec![];
-        for parser in Self::PARSERS {
-            parsers.push(RegexParser::new(parser.0, parser.1.to_vec()))
+        Self::from_path_with_regex(path, Self::builtin_parsers().iter().collect())
+    }
+
+    /// 从不带目录的文件名中解析剧集信息，如 `还珠格格S01E02.mp4`
+    ///
+    /// 先尝试 `FILENAME_PARSERS`，不匹配时在文件名前补上 `/` 后再按内置的 `PARSERS` 解析，
+    /// 依赖上级目录名称的模式不会匹配。传入带目录的路径时只使用最后一级名称
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    ///
+    /// let ep = Episode::from_filename("还珠格格S01E02.mp4").unwrap();
+    /// assert_eq!(ep.title, Some("还珠格格".to_string()));
+    /// assert_eq!(ep.season, Some(1));
+    /// assert_eq!(ep.episode, Some(2));
+    /// assert_eq!(ep.source_path, None);
+    ///
+    /// // 裸文件名不能用 from_path 解析
+    /// assert!(Episode::from_path("还珠格格S01E02.mp4").unwrap().is_none());
+    ///
+    /// // 种子客户端中常见的文件名
+    /// let ep = Episode::from_filename("[字幕组] Show Name - 05 [1080p].mkv").unwrap();
+    /// assert_eq!(ep.title, Some("Show Name".to_string()));
+    /// assert_eq!(ep.episode, Some(5));
+    ///
+    /// assert!(Episode::from_filename("01.mp4").is_none());
+    /// ```
+    pub fn from_filename(name: &str) -> Option<Self> {
+        let name = crate::path_str::file_name(name)?;
+        let ep = Self::from_path_with_regex(name, Self::filename_parsers().iter().collect()).ok()?
+            .or_else(|| Self::from_path_with_regex(format!("/{name}"), Self::builtin_parsers().iter().collect()).ok()?)?;
+        Some(Self { source_path: None, ..ep })
+    }
+
+    /// 先按 `FILENAME_PARSERS` 解析路径中的文件名，不匹配时再按 `PARSERS` 解析完整路径，
+    /// 适合来源不确定、可能只有文件名也可能带有目录的场景
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    ///
+    /// let ep = Episode::from_path_or_filename("/下载/约会专家第04集.mp4").unwrap().unwrap();
+    /// assert_eq!(ep.title, Some("约会专家".to_string()));
+    /// assert_eq!(ep.episode, Some(4));
+    /// assert_eq!(ep.source_path, Some("/下载/约会专家第04集.mp4".into()));
+    ///
+    /// // 文件名不匹配时使用完整路径
+    /// let ep = Episode::from_path_or_filename("/影片/怪侠一枝梅.全30集.4K/02.mp4").unwrap().unwrap();
+    /// assert_eq!(ep.title, Some("怪侠一枝梅".to_string()));
+    /// assert_eq!(ep.episode, Some(2));
+    /// ```
+    pub fn from_path_or_filename<P: AsRef<Path>>(path: P) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        let path_str = normalize_separators(path)?;
+        if let Some(name) = crate::path_str::file_name(&path_str) {
+            if let Some(ep) = Self::from_path_with_regex(name, Self::filename_parsers().iter().collect())? {
+                return Ok(Some(ep.with_source_path(path)));
+            }
         }
+        Self::from_path(path)
+    }
+
+    /// 先尝试自定义的解析器，再尝试内置的 `PARSERS`
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::{Episode, RegexParser};
+    ///
+    /// let extra = vec![RegexParser::new(r"^(.*?)/([^/]+)/第(\d+)季/(\d+)\.(\w+)$", vec![2, 3, 4])];
+    ///
+    /// let ep = Episode::from_path_with_extra("/影片/武林外传/第2季/05.mp4", extra).unwrap().unwrap();
+    /// assert_eq!(ep.title, Some("武林外传".to_string()));
+    /// assert_eq!(ep.season, Some(2));
+    /// assert_eq!(ep.episode, Some(5));
+    ///
+    /// // 内置的模式依然可用
+    /// let ep = Episode::from_path_with_extra("/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4", vec![]).unwrap().unwrap();
+    /// assert_eq!(ep.episode, Some(2));
+    /// ```
+    pub fn from_path_with_extra<P: AsRef<Path>>(path: P, extra: Vec<RegexParser>) -> Result<Option<Self>> {
+        let parsers: Vec<&RegexParser> = extra.iter().chain(Self::builtin_parsers()).collect();
         Self::from_path_with_regex(path, parsers)
     }
 
+    /// 先尝试配置文件中的自定义解析器，再尝试内置的 `PARSERS`，需要开启 `config` feature
+    ///
+    /// 配置文件的格式见 `ParserConfig`，通常位于 `ParserConfig::DEFAULT_PATH`
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    /// use lazytool::path::TempDir;
+    ///
+    /// let tmp = TempDir::new("lazytool-from-path-with-config-").unwrap();
+    /// let config = tmp.child("parsers.toml");
+    /// std::fs::write(&config, r#"
+    /// [[parsers]]
+    /// pattern = '^(.*?)/([^/]+)/第(\d+)季/(\d+)\.(\w+)$'
+    /// indexes = [2, 3, 4]
+    /// "#).unwrap();
+    ///
+    /// let ep = Episode::from_path_with_config("/影片/武林外传/第2季/05.mp4", &config).unwrap().unwrap();
+    /// assert_eq!(ep.title, Some("武林外传".to_string()));
+    /// assert_eq!(ep.season, Some(2));
+    /// assert_eq!(ep.episode, Some(5));
+    /// ```
+    #[cfg(feature = "config")]
+    pub fn from_path_with_config<P, C>(path: P, config_path: C) -> Result<Option<Self>>
+        where P: AsRef<Path>,
+              C: AsRef<Path>,
+    {
+        let config = ParserConfig::load(config_path)?;
+        Self::from_path_with_extra(path, config.parsers)
+    }
+
+    /// 内置 `PARSERS` 对应的解析器，只构建一次，正则在首次匹配时编译后缓存，供所有调用共用
+    ///
+    /// 内置模式中的 `\d` 也能匹配全角数字，默认先转为半角，避免匹配后数字无法解析
+    fn builtin_parsers() -> &'static [RegexParser] {
+        static BUILTIN: Lazy<Vec<RegexParser>> = Lazy::new(|| {
+            Episode::PARSERS.iter()
+                .map(|(pattern, indexes)| RegexParser::new(pattern, indexes.to_vec()).with_normalize_width(true))
+                .collect()
+        });
+        &BUILTIN
+    }
+
+    /// `FILENAME_PARSERS` 对应的解析器，与 `builtin_parsers` 一样只构建一次
+    fn filename_parsers() -> &'static [RegexParser] {
+        static FILENAME: Lazy<Vec<RegexParser>> = Lazy::new(|| {
+            Episode::FILENAME_PARSERS.iter()
+                .map(|(pattern, indexes)| RegexParser::new(pattern, indexes.to_vec()).with_normalize_width(true))
+                .collect()
+        });
+        &FILENAME
+    }
+
+    /// 按给定的解析器解析，按 `Parser::priority` 从高到低依次尝试，返回第一个匹配的结果
     pub fn from_path_with_regex<P, T>(path: P, parsers: Vec<T>) -> Result<Option<Self>>
         where P: AsRef<Path>,
               T: Parser,
     {
 
-        let path_str = path.as_ref().to_str().ok_or_else(|| anyhow!("Invalid path"))?;
+        let path_str = normalize_separators(path.as_ref())?;
 
         // 尝试匹配每个模式
-        for parser in parsers {
-            let item = parser.parse(path_str);
-            if item.is_some() {
-                return Ok(item);
+        for (i, parser) in by_priority(parsers).iter().enumerate() {
+            if let Some(ep) = parser.parse(&path_str) {
+                debug!("parser #{i} matched {path_str:?}");
+                return Ok(Some(ep.with_source_path(path.as_ref())));
             }
         }
 
         // 配合文件夹名称
+        debug!("no parser matched {path_str:?}");
         Ok(None)
     }
 
-}
+    /// 递归扫描目录下的视频文件并解析，跳过隐藏文件和无法解析的文件，结果按路径排序
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    /// use lazytool::path::TempDir;
+    ///
+    /// let tmp = TempDir::new("lazytool-scan-dir-").unwrap();
+    /// let dir = tmp.path();
+    /// std::fs::create_dir_all(dir.join("还珠格格S01")).unwrap();
+    /// std::fs::write(dir.join("还珠格格S01/还珠格格S01E02.mp4"), "").unwrap();
+    /// std::fs::write(dir.join("还珠格格S01/还珠格格S01E01.mp4"), "").unwrap();
+    /// std::fs::write(dir.join("还珠格格S01/poster.jpg"), "").unwrap();
+    ///
+    /// let episodes = Episode::scan_dir(dir).unwrap();
+    /// assert_eq!(episodes.len(), 2);
+    /// assert_eq!(episodes[0].episode, Some(1));
+    /// assert_eq!(episodes[0].source_path, Some(dir.join("还珠格格S01/还珠格格S01E01.mp4")));
+    /// ```
+    pub fn scan_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<Self>> {
+        // 与 `path::walk_files` 一致，根目录无法读取时返回错误，子目录无法读取时跳过
+        let mut items: Vec<(PathBuf, Self)> = Self::scan_dir_paths(dir)?.filter_map(Result::ok).collect();
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(items.into_iter().map(|(_, ep)| ep).collect())
+    }
 
-pub trait Parser {
-    fn parse(&self, path: &str) -> Option<Episode>;
-}
+    /// 惰性扫描目录下的视频文件并解析，边遍历边产出，不会把所有结果放在内存中，
+    /// 顺序与目录遍历顺序一致，无法读取的目录作为错误产出后继续遍历
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    /// use lazytool::path::TempDir;
+    ///
+    /// let tmp = TempDir::new("lazytool-scan-dir-iter-").unwrap();
+    /// let dir = tmp.path();
+    /// for i in 1..=5 {
+    ///     std::fs::write(dir.join(format!("还珠格格S01E0{i}.mp4")), "").unwrap();
+    /// }
+    ///
+    /// let first: Vec<Episode> = Episode::scan_dir_iter(dir)
+    ///     .filter_map(Result::ok)
+    ///     .filter(|ep| ep.episode.is_some_and(|e| e > 2))
+    ///     .take(2)
+    ///     .collect();
+    /// assert_eq!(first.len(), 2);
+    ///
+    /// assert!(Episode::scan_dir_iter(dir.join("missing")).next().unwrap().is_err());
+    /// ```
+    pub fn scan_dir_iter<P: AsRef<Path>>(dir: P) -> impl Iterator<Item = Result<Self>> {
+        let (items, error) = match Self::scan_dir_paths(dir) {
+            Ok(items) => (Some(items), None),
+            Err(e) => (None, Some(Err(e))),
+        };
+        error.into_iter().chain(items.into_iter().flatten().map(|item| item.map(|(_, ep)| ep)))
+    }
 
-#[derive(Debug, Deserialize)]
-pub struct RegexParser {
-    pattern: String,
-    indexes: Vec<usize>,
-}
+    /// 惰性扫描并产出文件路径和解析结果，内置解析器只构建一次，根目录无法读取时返回错误
+    fn scan_dir_paths<P: AsRef<Path>>(dir: P) -> Result<impl Iterator<Item = Result<(PathBuf, Self)>>> {
+        let dir = crate::path::expand_user(dir);
+        let opts = crate::path::WalkOptions {
+            extensions: Some(MediaKind::VIDEO_EXTENSIONS.iter().map(|e| e.to_string()).collect()),
+            ..Default::default()
+        };
+        let walker = crate::path::Walker::new(&dir, opts)
+            .map_err(|e| Error::io(format!("Failed to read directory {dir:?}"), e))?;
+        let parsers = Self::builtin_parsers();
 
-impl RegexParser {
-    pub fn new<P: AsRef<str>>(pattern: P, indexes: Vec<usize>) -> Self {
-        Self { pattern: pattern.as_ref().to_string(), indexes }
+        Ok(walker.filter_map(move |item| {
+            let (path, _) = match item {
+                Ok(item) => item,
+                Err((path, e)) => return Some(Err(Error::io(format!("Failed to read {path:?}"), e))),
+            };
+            let ep = Self::parse_with(&path, parsers)?;
+            Some(Ok((path, ep)))
+        }))
     }
-}
 
-impl Parser for RegexParser {
-    fn parse(&self, path: &str) -> Option<Episode> {
-        let re = Regex::new(&self.pattern).ok()?;
-        let indexs = self.indexes.clone();
-        if let Some(caps) = re.captures(path) {
-            // println!("{caps:#?}");
-            let title = &caps[indexs[0]]; // 剧名
-            let mut season = Some(1);
-            if indexs[1] != 0 {
-                let season_text = &caps[indexs[1]]; // 季数
-                season = season_text.parse().ok();
-            }
-            let episode = &caps[indexs[2]]; // 集数
-            Some(Episode {
-                title: Some(title.to_string()),
-                season,
-                episode: episode.parse().ok(),
-            })
-        } else {
-            None
-        }
+    /// 按顺序尝试已构建好的解析器并记录来源路径，用于批量解析时避免重复构建解析器
+    fn parse_with(path: &Path, parsers: &[RegexParser]) -> Option<Self> {
+        let path_str = normalize_separators(path).ok()?;
+        Some(parsers.iter().find_map(|p| p.parse(&path_str))?.with_source_path(path))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::Episode;
+    /// 按所有内置模式解析，返回每个匹配的结果，按可信度从高到低排列
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    ///
+    /// let path = "/Volumes/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4";
+    /// let matches = Episode::all_matches(path).unwrap();
+    /// assert!(!matches.is_empty());
+    /// assert_eq!(matches[0].confidence, 1.0);
+    /// ```
+    pub fn all_matches<P: AsRef<Path>>(path: P) -> Result<Vec<Self>> {
+        Self::all_matches_with_regex(path, Self::builtin_parsers().iter().collect())
+    }
 
-    #[test]
-    fn test_match_pattern1() {
-        let path = "/Volumes/Getea/影片/电视剧/医馆笑传/医馆笑传S01.37集.1080P/01.mp4";
-        let item = Episode::from_path(path).unwrap();
-        assert!(item.is_some());
-        if let Some(ep) = item {
-            assert_eq!(ep.title, Some("医馆笑传".to_string()));
-            assert_eq!(ep.season, Some(1));
-            assert_eq!(ep.episode, Some(1));
-        }
+    /// 按给定的解析器解析，返回每个匹配的结果，按可信度从高到低排列，可信度相同时按解析器的优先级及顺序排列
+    pub fn all_matches_with_regex<P, T>(path: P, parsers: Vec<T>) -> Result<Vec<Self>>
+        where P: AsRef<Path>,
+              T: Parser,
+    {
+        let path_str = normalize_separators(path.as_ref())?;
+        let mut matches: Vec<Self> = by_priority(parsers).iter()
+            .filter_map(|p| p.parse(&path_str))
+            .map(|ep| ep.with_source_path(path.as_ref()))
+            .collect();
+        matches.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        Ok(matches)
     }
 
-    #[test]
-    fn test_match_pattern2() {
-        let path = "/Volumes/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4";
-        let item = Episode::from_path(path).unwrap();
-        println!("{item:?}");
-        assert!(item.is_some());
-        if let Some(ep) = item {
-            assert_eq!(ep.title, Some("还珠格格".to_string()));
-            assert_eq!(ep.season, Some(1));
-            assert_eq!(ep.episode, Some(2));
+    /// 匹配可信度的计算方式
+    ///
+    /// 按捕获到的字段累加：剧名 0.3，季数 0.3（使用默认季数时只计 0.15），集数或播出日期 0.4
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    ///
+    /// assert_eq!(Episode::score(true, true, true), 1.0);
+    /// assert_eq!(Episode::score(true, false, true), 0.85);
+    /// assert_eq!(Episode::score(false, false, false), 0.15);
+    /// ```
+    pub fn score(title: bool, season: bool, episode: bool) -> f32 {
+        // 以 0.05 为单位累加，避免浮点误差
+        let mut points = 0;
+        if title {
+            points += 6;
         }
+        points += if season { 6 } else { 3 };
+        if episode {
+            points += 8;
+        }
+        points as f32 / 20.0
     }
 
-    #[test]
-    fn test_match_pattern3() {
-        let path = "/Volumes/ZhiTai/影片/电视剧/爱情公寓/S2 (2011) 4K/02.mp4";
-        let item = Episode::from_path(path).unwrap();
-        assert!(item.is_some());
-        if let Some(ep) = item {
-            assert_eq!(ep.title, Some("爱情公寓".to_string()));
-            assert_eq!(ep.season, Some(2));
-            assert_eq!(ep.episode, Some(2));
-        }
+    /// 索引文件名称，放在剧集文件同级目录下
+    pub const INDEX_FILENAME: &str = "season.json";
+
+    /// 优先从同级目录的 `season.json` 中读取剧集信息，未命中时使用正则解析，
+    /// 命中但缺少字段时再用正则解析的结果补全
+    ///
+    /// `season.json` 以文件名为键，值中的 `title`、`season`、`episode` 均可省略：
+    ///
+    /// ```json
+    /// {
+    ///     "01.mp4": { "title": "医馆笑传", "season": 2, "episode": 5 }
+    /// }
+    /// ```
+    pub fn from_path_with_index<P: AsRef<Path>>(path: P) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        let entry = match Self::read_index_entry(path)? {
+            Some(entry) => entry,
+            None => return Self::from_path(path),
+        };
 
+        // 索引中缺失的字段使用正则解析结果补全
+        let parsed = if entry.title.is_some() && entry.season.is_some() && entry.episode.is_some() {
+            Episode::default()
+        } else {
+            Self::from_path(path)?.unwrap_or_default()
+        };
+        // 索引由用户明确指定，可信度最高
+        Ok(Some(Episode {
+            title: entry.title.or(parsed.title),
+            season: entry.season.or(parsed.season),
+            episode: entry.episode.or(parsed.episode),
+            confidence: 1.0,
+            source_path: Some(path.to_path_buf()),
+            ..parsed
+        }))
     }
-    #[test]
-    fn test_match_pattern4() {
-        let path = "/Volumes/ZhiTai/影片/电视剧/龙门镖局/龙门镖局 (2013) 4K/龙门镖局.Longmen.Express.2013.E02.4K.2160p.HEVC.AAC-DHTCLUB.mp4";
-        let item = Episode::from_path(path).unwrap();
-        assert!(item.is_some());
-        if let Some(ep) = item {
-            assert_eq!(ep.title, Some("龙门镖局".to_string()));
-            assert_eq!(ep.season, Some(1)); // 默认季数为1
-            assert_eq!(ep.episode, Some(2));
+
+    /// 合并两个部分解析的结果，`self` 中为 `None` 的字段使用 `other` 补全，可信度取两者中较高的
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    ///
+    /// let from_folder = Episode { title: Some("神探狄仁杰".to_string()), ..Default::default() };
+    /// let from_file = Episode { season: Some(2), episode: Some(5), ..Default::default() };
+    ///
+    /// let ep = from_folder.merge(from_file);
+    /// assert_eq!(ep.title, Some("神探狄仁杰".to_string()));
+    /// assert_eq!(ep.season, Some(2));
+    /// assert_eq!(ep.episode, Some(5));
+    /// ```
+    pub fn merge(self, other: Episode) -> Episode {
+        Episode {
+            title: self.title.or(other.title),
+            season: self.season.or(other.season),
+            episode: self.episode.or(other.episode),
+            episode_end: self.episode_end.or(other.episode_end),
+            year: self.year.or(other.year),
+            air_date: self.air_date.or(other.air_date),
+            container: self.container.or(other.container),
+            resolution: self.resolution.or(other.resolution),
+            video_codec: self.video_codec.or(other.video_codec),
+            audio_codec: self.audio_codec.or(other.audio_codec),
+            source: self.source.or(other.source),
+            confidence: self.confidence.max(other.confidence),
+            source_path: self.source_path.or(other.source_path),
         }
     }
 
-    #[test]
-    fn test_match_pattern5() {
-        let path = "/Volumes/Getea/影/影/渤/疯狂的赛车.2009.01201.mp4";
-        let item = Episode::from_path(path).unwrap();
-        assert!(item.is_some());
-        if let Some(ep) = item {
-            assert_eq!(ep.title, Some("疯狂的赛车".to_string()));
-            assert_eq!(ep.season, Some(2009));
-            assert_eq!(ep.episode, Some(1201));
+    /// 剧名的拼音，不带声调，音节之间以空格分隔，需要开启 `pinyin` feature，见 `media::to_pinyin`
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    ///
+    /// let ep = Episode::from_path("/电视剧/神探狄仁杰S01/神探狄仁杰S01E02.mp4").unwrap().unwrap();
+    /// assert_eq!(ep.title_pinyin(), Some("shen tan di ren jie".to_string()));
+    /// assert_eq!(Episode::default().title_pinyin(), None);
+    /// ```
+    #[cfg(feature = "pinyin")]
+    pub fn title_pinyin(&self) -> Option<String> {
+        self.title.as_deref().map(to_pinyin)
+    }
+
+    /// 生成规范的文件名，如 `还珠格格.S01E02.mp4`
+    ///
+    /// 多集时为 `还珠格格.S01E01-E02.mp4`，只有播出日期时为 `向往的生活.2021-01-15.mkv`，
+    /// 没有季数时按第 1 季处理，没有容器格式时不带扩展名，剧名中的非法字符按 `path::sanitize_filename` 替换。
+    /// 缺少剧名，或集数和播出日期都没有时返回 None
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    ///
+    /// let ep = Episode::from_path("/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4").unwrap().unwrap();
+    /// assert_eq!(ep.to_filename(), Some("还珠格格.S01E02.mp4".to_string()));
+    ///
+    /// let ep = Episode::from_path("/综艺/向往的生活第20210115期.mkv").unwrap().unwrap();
+    /// assert_eq!(ep.to_filename(), Some("向往的生活.2021-01-15.mkv".to_string()));
+    ///
+    /// let ep = Episode { title: Some("还珠格格".to_string()), ..Default::default() };
+    /// assert_eq!(ep.to_filename(), None);
+    /// ```
+    pub fn to_filename(&self) -> Option<String> {
+        let title = self.title.as_deref()?;
+        let marker = match (self.episode, self.air_date) {
+            (Some(episode), _) => {
+                let mut marker = format!("S{:02}E{episode:02}", self.season.unwrap_or(1));
+                if let Some(end) = self.episode_end {
+                    marker.push_str(&format!("-E{end:02}"));
+                }
+                marker
+            }
+            (None, Some(date)) => date.format("%Y-%m-%d").to_string(),
+            (None, None) => return None,
+        };
+        let name = match &self.container {
+            Some(ext) => format!("{title}.{marker}.{ext}"),
+            None => format!("{title}.{marker}"),
+        };
+        Some(crate::path::sanitize_filename(&name))
+    }
+
+    /// 按媒体库的目录结构生成路径，是 `from_path` 的逆操作，各布局的格式见 `Layout`
+    ///
+    /// 有年份时剧集目录带上年份如 `医馆笑传 (2021)`，只有播出日期时按播出年份分季如 `Season 2021`，
+    /// 各级名称中的非法字符按 `path::sanitize_filename` 替换。缺少剧名，或集数和播出日期都没有时返回 None
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    /// use lazytool::media::Layout;
+    /// use std::path::Path;
+    ///
+    /// let ep = Episode::from_path("/下载/医馆笑传S01E03.mkv").unwrap().unwrap();
+    /// let path = ep.to_path("/影片", Layout::Plex).unwrap();
+    /// assert_eq!(path, Path::new("/影片/医馆笑传/Season 01/医馆笑传 - S01E03.mkv"));
+    /// assert_eq!(ep.to_path("/影片", Layout::Kodi).unwrap(), Path::new("/影片/医馆笑传/Season 1/医馆笑传 S01E03.mkv"));
+    /// assert_eq!(ep.to_path("/影片", Layout::Flat).unwrap(), Path::new("/影片/医馆笑传.S01E03.mkv"));
+    ///
+    /// // 生成的路径可以解析回相同的剧集
+    /// let parsed = Episode::from_path(&path).unwrap().unwrap();
+    /// assert_eq!((parsed.title, parsed.season, parsed.episode), (ep.title, ep.season, ep.episode));
+    /// ```
+    pub fn to_path<P: AsRef<Path>>(&self, root: P, layout: Layout) -> Option<PathBuf> {
+        let root = root.as_ref();
+        if layout == Layout::Flat {
+            return Some(root.join(self.to_filename()?));
+        }
+
+        let title = self.title.as_deref()?;
+        let (season, marker) = match (self.episode, self.air_date) {
+            (Some(episode), _) => {
+                let season = self.season.unwrap_or(1);
+                let mut marker = format!("S{season:02}E{episode:02}");
+                if let Some(end) = self.episode_end {
+                    marker.push_str(&format!("-E{end:02}"));
+                }
+                (season, marker)
+            }
+            (None, Some(date)) => (date.year().try_into().ok()?, date.format("%Y-%m-%d").to_string()),
+            (None, None) => return None,
+        };
+        let show = match self.year {
+            Some(year) => format!("{title} ({year})"),
+            None => title.to_string(),
+        };
+        let season_dir = match layout {
+            Layout::Kodi => format!("Season {season}"),
+            _ => format!("Season {season:02}"),
+        };
+        let mut name = match layout {
+            Layout::Plex => format!("{title} - {marker}"),
+            _ => format!("{title} {marker}"),
+        };
+        if let Some(ext) = &self.container {
+            name = format!("{name}.{ext}");
         }
+
+        let sanitize = crate::path::sanitize_filename;
+        Some(root.join(sanitize(&show)).join(season_dir).join(sanitize(&name)))
     }
 
-    #[test]
-    fn test_match_pattern6() {
-        let path = "/Volumes/ZhiTai/影片/电视剧/约会专家.1080P/约会专家第04集.mp4";
-        let item = Episode::from_path(path).unwrap();
-        assert!(item.is_some());
-        if let Some(ep) = item {
-            assert_eq!(ep.title, Some("约会专家".to_string()));
-            assert_eq!(ep.season, Some(1));
-            assert_eq!(ep.episode, Some(4));
+    /// 按模板生成文件名，模板的占位符与 `TemplateParser` 一致，可以包含目录，如
+    /// `{title}/Season {season:2}/{title} - S{season:2}E{episode:2}.{ext}`
+    ///
+    /// `{air_date}` 生成 `20210115` 形式的日期，没有季数时按第 1 季处理，
+    /// 各级名称中的非法字符按 `path::sanitize_filename` 替换。
+    /// 模板用到的字段缺失时返回 None，占位符未知或括号未闭合时返回 `Error::InvalidPattern`，
+    /// 模板先整体检查，字段缺失也不会掩盖后面不合法的占位符
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    ///
+    /// let ep = Episode::from_path("/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4").unwrap().unwrap();
+    /// let template = "{title}/Season {season:2}/{title} - S{season:2}E{episode:2}.{ext}";
+    /// assert_eq!(ep.to_filename_with(template).unwrap(), Some("还珠格格/Season 01/还珠格格 - S01E02.mp4".to_string()));
+    ///
+    /// // 没有年份
+    /// assert_eq!(ep.to_filename_with("{title} ({year})/{episode}.{ext}").unwrap(), None);
+    ///
+    /// assert!(ep.to_filename_with("{title}/{name}").is_err());
+    /// assert!(ep.to_filename_with("{year}/{name}").is_err());
+    /// ```
+    pub fn to_filename_with(&self, template: &str) -> Result<Option<String>> {
+        validate_filename_template(template)?;
+        let mut name = String::new();
+        for part in template_parts(template)? {
+            let placeholder = match part {
+                TemplatePart::Literal(literal) => {
+                    name.push_str(literal);
+                    continue;
+                }
+                TemplatePart::Placeholder(placeholder) => placeholder,
+            };
+            let (field, width) = filename_field(template, placeholder)?;
+            let number = match field {
+                "season" => Some(self.season.or(Some(1))),
+                "episode" => Some(self.episode),
+                "episode_end" => Some(self.episode_end),
+                "year" => Some(self.year.map(u32::from)),
+                _ => None,
+            };
+            let value = match (field, number) {
+                (_, Some(None)) => return Ok(None),
+                (_, Some(Some(n))) => format!("{n:0width$}"),
+                ("title", None) => match &self.title {
+                    Some(title) => crate::path::sanitize_filename(title),
+                    None => return Ok(None),
+                },
+                ("air_date", None) => match self.air_date {
+                    Some(date) => date.format("%Y%m%d").to_string(),
+                    None => return Ok(None),
+                },
+                ("ext", None) => match &self.container {
+                    Some(ext) => ext.clone(),
+                    None => return Ok(None),
+                },
+                _ => unreachable!("placeholder {placeholder:?} is checked by filename_field"),
+            };
+            name.push_str(&value);
         }
+        let components: Vec<String> = name.split('/').map(crate::path::sanitize_filename).collect();
+        Ok(Some(components.join("/")))
     }
 
-    #[test]
-    fn test_match_pattern7() {
-        let path = "/Volumes/ZhiTai/影片/电视剧/怪侠一枝梅.全30集.4K/02.mp4";
-        let item = Episode::from_path(path).unwrap();
+    /// 文件名是否已经是 `to_filename` 生成的规范形式，用于重命名时跳过无需修改的文件
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    /// use std::path::Path;
+    ///
+    /// let ep = Episode::from_path("/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4").unwrap().unwrap();
+    /// assert!(ep.matches_filename(Path::new("/电视剧/还珠格格/还珠格格.S01E02.mp4")));
+    /// assert!(!ep.matches_filename(Path::new("/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4")));
+    /// ```
+    pub fn matches_filename(&self, path: &Path) -> bool {
+        let Some(expected) = self.to_filename() else {
+            return false;
+        };
+        path.file_name().is_some_and(|name| name == expected.as_str())
+    }
+
+    /// 是否为同一集：剧名（不区分大小写）和季数相同，且集数相同或播出日期相同
+    fn is_same_episode(&self, other: &Episode) -> bool {
+        let same_title = match (&self.title, &other.title) {
+            (Some(a), Some(b)) => a.to_lowercase() == b.to_lowercase(),
+            _ => false,
+        };
+        let same_episode = (self.episode.is_some() && self.episode == other.episode && self.episode_end == other.episode_end)
+            || (self.air_date.is_some() && self.air_date == other.air_date);
+        same_title && self.season == other.season && same_episode
+    }
+
+    /// 设置解析来源的文件路径
+    fn with_source_path(self, path: &Path) -> Self {
+        Self { source_path: Some(path.to_path_buf()), ..self }
+    }
+
+    /// 序列化为 JSON 字符串
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    ///
+    /// let ep = Episode::from_path("/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4").unwrap().unwrap();
+    /// let json = ep.to_json().unwrap();
+    /// assert!(json.contains(r#""title":"还珠格格""#));
+    /// assert!(json.contains(r#""episode":2"#));
+    /// ```
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// 从 `to_json` 生成的 JSON 字符串还原，缺少的字段按默认值处理，可用于缓存扫描结果
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Episode;
+    ///
+    /// let ep = Episode::from_path("/向往的生活第20210115期.mkv").unwrap().unwrap();
+    /// let cached = Episode::from_json(&ep.to_json().unwrap()).unwrap();
+    /// assert_eq!(cached.title, ep.title);
+    /// assert_eq!(cached.air_date, ep.air_date);
+    /// assert_eq!(cached.source_path, ep.source_path);
+    ///
+    /// let ep = Episode::from_json(r#"{"title":"还珠格格","episode":2}"#).unwrap();
+    /// assert_eq!(ep.episode, Some(2));
+    /// assert_eq!(ep.confidence, 0.0);
+    ///
+    /// assert!(Episode::from_json("[]").is_err());
+    /// ```
+    pub fn from_json(s: &str) -> Result<Self> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    /// 读取索引文件中当前文件对应的条目
+    fn read_index_entry(path: &Path) -> Result<Option<IndexEntry>> {
+        let (dir, filename) = match (path.parent(), path.file_name()) {
+            (Some(dir), Some(filename)) => (dir, filename),
+            _ => return Ok(None),
+        };
+        let index_path = dir.join(Self::INDEX_FILENAME);
+        if !index_path.is_file() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&index_path)
+            .map_err(|e| Error::io(format!("Failed to read {index_path:?}"), e))?;
+        let mut index: HashMap<String, IndexEntry> = serde_json::from_str(&content)
+            .map_err(|source| Error::Json { path: Some(index_path), source })?;
+        Ok(index.remove(filename.to_string_lossy().as_ref()))
+    }
+
+}
+
+/// `season.json` 中单个文件的剧集信息
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    title: Option<String>,
+    season: Option<u32>,
+    episode: Option<u32>,
+}
+
+/// 媒体库的目录结构，用于 `Episode::to_path`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// `剧名/Season 01/剧名 - S01E03.mkv`
+    Plex,
+    /// `剧名/Season 01/剧名 S01E03.mkv`
+    Jellyfin,
+    /// `剧名/Season 1/剧名 S01E03.mkv`
+    Kodi,
+    /// 不分目录，直接使用 `Episode::to_filename`，如 `剧名.S01E03.mkv`
+    Flat,
+}
+
+/// 电影信息
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Movie {
+    // 片名
+    pub title: Option<String>,
+
+    // 上映年份
+    pub year: Option<u16>,
+
+    // 分辨率，从文件名或所在目录名中识别
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<Resolution>,
+
+    // 版本，如 `Director's Cut`、`Extended`、`导演剪辑版`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edition: Option<String>,
+
+    // 容器格式，即视频文件扩展名，没有扩展名时为 None
+    pub container: Option<String>,
+
+    // 解析来源的文件路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_path: Option<PathBuf>,
+}
+
+impl Movie {
+    /// 从地址中解析电影信息，文件名需要包含年份，如 `疯狂的赛车.2009.mp4`、`Heat (1995) 1080p.mkv`，
+    /// 文件名中带有 `S01E02` 季集标记时视为剧集，返回 None
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::Movie;
+    /// use lazytool::media::Resolution;
+    ///
+    /// let movie = Movie::from_path("/影片/电影/Heat.1995.Directors.Cut.1080p.BluRay.mkv").unwrap().unwrap();
+    /// assert_eq!(movie.title, Some("Heat".to_string()));
+    /// assert_eq!(movie.year, Some(1995));
+    /// assert_eq!(movie.edition, Some("Directors Cut".to_string()));
+    /// assert_eq!(movie.resolution, Some(Resolution::HD1080));
+    /// assert_eq!(movie.container, Some("mkv".to_string()));
+    ///
+    /// let movie = Movie::from_path("/影片/电影/黄渤/疯狂的赛车.2009.01201.mp4").unwrap().unwrap();
+    /// assert_eq!(movie.title, Some("疯狂的赛车".to_string()));
+    /// assert_eq!(movie.year, Some(2009));
+    ///
+    /// assert!(Movie::from_path("/影片/电视剧/Show.Name.2023.S02E10.mkv").unwrap().is_none());
+    /// assert!(Movie::from_path("/影片/电视剧/怪侠一枝梅.全30集.4K/02.mp4").unwrap().is_none());
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Option<Self>> {
+        static TITLE_YEAR: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^(?P<title>.*[^. _(\[（-])[. _-]*[(\[（]?(?P<year>(?:19|20)\d{2})[)\]）]?(?:[. _-]|$)").unwrap()
+        });
+        static EDITION: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(concat!(
+                r"(?i)(?:^|[. _-])(director'?s[. _]cut|extended(?:[. _](?:edition|cut))?|unrated|uncut",
+                r"|theatrical(?:[. _]cut)?|remastered|imax|criterion)(?:[. _-]|$)|(导演剪辑版|加长版|未删减版|重制版)",
+            )).unwrap()
+        });
+
+        let path_str = normalize_separators(path.as_ref())?;
+        let Some(name) = crate::path_str::file_name(&path_str) else {
+            return Ok(None);
+        };
+        let container = container_of(&path_str);
+        let stem = match &container {
+            Some(ext) => &name[..name.len() - ext.len() - 1],
+            None => name,
+        };
+        if season_episode_marker(stem).is_some() {
+            return Ok(None);
+        }
+        let Some(caps) = TITLE_YEAR.captures(stem) else {
+            trace!("no movie title and year in {path_str:?}");
+            return Ok(None);
+        };
+
+        let movie = Self {
+            title: Some(clean_title(&caps["title"])),
+            year: caps["year"].parse().ok(),
+            resolution: resolution_of(&path_str),
+            edition: EDITION.captures(stem)
+                .and_then(|c| c.get(1).or_else(|| c.get(2)))
+                .map(|m| m.as_str().replace(['.', '_'], " ")),
+            container,
+            source_path: Some(path.as_ref().to_path_buf()),
+        };
+        debug!("movie matched {path_str:?}: {movie:?}");
+        Ok(Some(movie))
+    }
+}
+
+/// `detect` 识别的媒体信息，序列化时通过 `kind` 字段区分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Media {
+    Episode(Episode),
+    Movie(Movie),
+}
+
+/// 识别路径对应的是剧集还是电影
+///
+/// 优先按 `Episode::PARSERS` 解析为剧集，其中模式 5（如 `疯狂的赛车.2009.01201.mp4`）实际描述的是电影，
+/// 这里不参与剧集的匹配；剧集不匹配时再按 `Movie::from_path` 解析
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media::{self, Media};
+///
+/// let media = media::detect("/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4").unwrap();
+/// assert!(matches!(media, Some(Media::Episode(ep)) if ep.episode == Some(2)));
+///
+/// let media = media::detect("/影片/电影/黄渤/疯狂的赛车.2009.01201.mp4").unwrap();
+/// assert!(matches!(media, Some(Media::Movie(movie)) if movie.year == Some(2009)));
+///
+/// assert!(media::detect("/影片/README.md").unwrap().is_none());
+/// ```
+pub fn detect<P: AsRef<Path>>(path: P) -> Result<Option<Media>> {
+    let parsers = Episode::builtin_parsers().iter()
+        .filter(|parser| parser.pattern != Episode::MOVIE_PATTERN)
+        .collect();
+    if let Some(ep) = Episode::from_path_with_regex(path.as_ref(), parsers)? {
+        return Ok(Some(Media::Episode(ep)));
+    }
+    Ok(Movie::from_path(path)?.map(Media::Movie))
+}
+
+/// `Parser::try_parse` 返回的错误，用于调试自定义的解析器
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// 正则表达式无法编译
+    #[error("Invalid pattern {pattern:?}: {source}")]
+    InvalidRegex { pattern: String, source: regex::Error },
+
+    /// `indexes` 中的索引超出正则的分组数
+    #[error("Capture index {index} for {field} is out of range, the pattern has {groups} groups")]
+    IndexOutOfRange { field: &'static str, index: usize, groups: usize },
+
+    /// 季数、集数、年份等捕获到的内容不是合法的数字或超出范围
+    #[error("Invalid number {value:?} for {field}")]
+    InvalidNumber { field: &'static str, value: String },
+
+    /// 播出日期不是合法的 `%Y%m%d` 日期
+    #[error("Invalid air date {0:?}")]
+    InvalidDate(String),
+}
+
+pub trait Parser {
+    fn parse(&self, path: &str) -> Option<Episode>;
+
+    /// 解析路径，不匹配时返回 `Ok(None)`，解析器本身配置有误或捕获的内容无法解析时返回错误，
+    /// 默认与 `parse` 相同，不会返回错误
+    fn try_parse(&self, path: &str) -> std::result::Result<Option<Episode>, ParseError> {
+        Ok(self.parse(path))
+    }
+
+    /// 优先级，`from_path_with_regex` 等按优先级从高到低尝试解析器，相同时保持原有顺序
+    fn priority(&self) -> i32 {
+        0
+    }
+}
+
+impl<T: Parser + ?Sized> Parser for &T {
+    fn parse(&self, path: &str) -> Option<Episode> {
+        (**self).parse(path)
+    }
+
+    fn try_parse(&self, path: &str) -> std::result::Result<Option<Episode>, ParseError> {
+        (**self).try_parse(path)
+    }
+
+    fn priority(&self) -> i32 {
+        (**self).priority()
+    }
+}
+
+/// 组合多个解析器，按顺序尝试并返回第一个解析成功的结果
+///
+/// Examples
+///
+/// ```
+/// use lazytool::{Episode, RegexParser};
+/// use lazytool::media::ChainParser;
+///
+/// let chain = ChainParser::new()
+///     .with(RegexParser::named(r"/(?P<title>[^/]+)\.S(?P<season>\d{2})E(?P<episode>\d{2})\.\w+$"))
+///     .with(RegexParser::named(r"/(?P<title>[^/]+) (?P<season>\d+)x(?P<episode>\d+)\.\w+$"));
+///
+/// let ep = Episode::from_path_with_regex("/Friends/Friends 2x05.mkv", vec![chain]).unwrap().unwrap();
+/// assert_eq!(ep.season, Some(2));
+/// assert_eq!(ep.episode, Some(5));
+/// ```
+#[derive(Default)]
+pub struct ChainParser {
+    parsers: Vec<Box<dyn Parser>>,
+}
+
+impl ChainParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加一个解析器
+    pub fn with<T: Parser + 'static>(mut self, parser: T) -> Self {
+        self.push(parser);
+        self
+    }
+
+    /// 追加一个解析器
+    pub fn push<T: Parser + 'static>(&mut self, parser: T) {
+        self.parsers.push(Box::new(parser));
+    }
+}
+
+impl From<Vec<Box<dyn Parser>>> for ChainParser {
+    fn from(parsers: Vec<Box<dyn Parser>>) -> Self {
+        Self { parsers }
+    }
+}
+
+impl Parser for ChainParser {
+    fn parse(&self, path: &str) -> Option<Episode> {
+        self.parsers.iter().find_map(|parser| parser.parse(path))
+    }
+
+    /// 依次尝试，返回第一个匹配的结果或第一个错误
+    fn try_parse(&self, path: &str) -> std::result::Result<Option<Episode>, ParseError> {
+        for parser in &self.parsers {
+            if let Some(ep) = parser.try_parse(path)? {
+                return Ok(Some(ep));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// 可插拔的解析器注册表，可以混合注册任意类型的解析器并指定优先级，以及启用或禁用内置的 `Episode::PARSERS`
+///
+/// 按优先级从高到低尝试，优先级相同时先尝试注册的解析器，再按顺序尝试内置的解析器（优先级为 0）
+///
+/// Examples
+///
+/// ```
+/// use lazytool::{Episode, RegexParser};
+/// use lazytool::media::ParserRegistry;
+///
+/// let mut registry = ParserRegistry::new();
+/// registry.register(RegexParser::new(r"^(.*?)/([^/]+)/第(\d+)季/(\d+)\.(\w+)$", vec![2, 3, 4]));
+/// registry.register_fn(-1, |path| {
+///     path.ends_with("/special.mp4").then(|| Episode { title: Some("特别篇".to_string()), season: Some(0), ..Default::default() })
+/// });
+///
+/// let ep = registry.parse("/影片/武林外传/第2季/05.mp4").unwrap().unwrap();
+/// assert_eq!(ep.title, Some("武林外传".to_string()));
+/// assert_eq!(ep.season, Some(2));
+///
+/// let ep = registry.parse("/影片/武林外传/special.mp4").unwrap().unwrap();
+/// assert_eq!(ep.season, Some(0));
+///
+/// // 内置的解析器默认启用，也可以按 `Episode::PARSERS` 中的索引禁用
+/// assert!(registry.parse("/还珠格格S01.1080P/还珠格格S01E02.mp4").unwrap().is_some());
+/// registry.disable_builtin(1);
+/// assert!(registry.parse("/还珠格格S01.1080P/还珠格格S01E02.mp4").unwrap().is_none());
+/// ```
+pub struct ParserRegistry {
+    parsers: Vec<Box<dyn Parser>>,
+    builtin_enabled: [bool; Episode::PARSERS.len()],
+}
+
+impl Default for ParserRegistry {
+    fn default() -> Self {
+        Self { parsers: vec![], builtin_enabled: [true; Episode::PARSERS.len()] }
+    }
+}
+
+impl ParserRegistry {
+    /// 创建注册表，内置的解析器全部启用
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册一个解析器，使用解析器自身的优先级
+    pub fn register<T: Parser + 'static>(&mut self, parser: T) -> &mut Self {
+        self.parsers.push(Box::new(parser));
+        self
+    }
+
+    /// 注册一个解析器并覆盖其优先级
+    pub fn register_with_priority<T: Parser + 'static>(&mut self, parser: T, priority: i32) -> &mut Self {
+        self.register(Prioritized { parser, priority })
+    }
+
+    /// 注册一个闭包作为解析器，闭包接收统一为 `/` 分隔符的路径
+    pub fn register_fn<F>(&mut self, priority: i32, f: F) -> &mut Self
+        where F: Fn(&str) -> Option<Episode> + 'static,
+    {
+        self.register(FnParser::new(f).with_priority(priority))
+    }
+
+    /// 注册一个解析器，用于链式调用
+    pub fn with<T: Parser + 'static>(mut self, parser: T) -> Self {
+        self.register(parser);
+        self
+    }
+
+    /// 启用 `Episode::PARSERS` 中指定索引的内置解析器，索引超出范围时忽略
+    pub fn enable_builtin(&mut self, index: usize) -> &mut Self {
+        if let Some(enabled) = self.builtin_enabled.get_mut(index) {
+            *enabled = true;
+        }
+        self
+    }
+
+    /// 禁用 `Episode::PARSERS` 中指定索引的内置解析器，索引超出范围时忽略
+    pub fn disable_builtin(&mut self, index: usize) -> &mut Self {
+        if let Some(enabled) = self.builtin_enabled.get_mut(index) {
+            *enabled = false;
+        }
+        self
+    }
+
+    /// 禁用全部内置解析器，只使用注册的解析器
+    pub fn disable_builtins(&mut self) -> &mut Self {
+        self.builtin_enabled = [false; Episode::PARSERS.len()];
+        self
+    }
+
+    /// 按注册的解析器和启用的内置解析器解析路径，与 `Episode::from_path_with_regex` 一致
+    pub fn parse<P: AsRef<Path>>(&self, path: P) -> Result<Option<Episode>> {
+        let builtins = Episode::builtin_parsers().iter()
+            .zip(self.builtin_enabled)
+            .filter(|&(_, enabled)| enabled)
+            .map(|(parser, _)| parser as &dyn Parser);
+        let parsers: Vec<&dyn Parser> = self.parsers.iter().map(|p| p.as_ref()).chain(builtins).collect();
+        Episode::from_path_with_regex(path, parsers)
+    }
+}
+
+/// 覆盖优先级的解析器
+struct Prioritized<T> {
+    parser: T,
+    priority: i32,
+}
+
+impl<T: Parser> Parser for Prioritized<T> {
+    fn parse(&self, path: &str) -> Option<Episode> {
+        self.parser.parse(path)
+    }
+
+    fn try_parse(&self, path: &str) -> std::result::Result<Option<Episode>, ParseError> {
+        self.parser.try_parse(path)
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+}
+
+/// 基于闭包的解析器，闭包接收统一为 `/` 分隔符的路径，适合无法用正则表达的解析逻辑
+///
+/// Examples
+///
+/// ```
+/// use lazytool::Episode;
+/// use lazytool::media::FnParser;
+///
+/// // 解析 `第十二集` 形式的中文数字集数
+/// let parser = FnParser::new(|path: &str| {
+///     let name = path.rsplit('/').next()?;
+///     let (title, rest) = name.split_once('第')?;
+///     let digits = rest.split_once('集')?.0;
+///     let value = |c| "一二三四五六七八九".chars().position(|d| d == c).map(|i| i as u32 + 1);
+///     let episode = match digits.chars().collect::<Vec<_>>()[..] {
+///         ['十'] => 10,
+///         ['十', b] => 10 + value(b)?,
+///         [a, '十'] => value(a)? * 10,
+///         [a, '十', b] => value(a)? * 10 + value(b)?,
+///         [a] => value(a)?,
+///         _ => return None,
+///     };
+///     Some(Episode { title: Some(title.to_string()), season: Some(1), episode: Some(episode), ..Default::default() })
+/// });
+///
+/// let ep = Episode::from_path_with_regex("/影片/约会专家第十二集.mp4", vec![parser]).unwrap().unwrap();
+/// assert_eq!(ep.title, Some("约会专家".to_string()));
+/// assert_eq!(ep.episode, Some(12));
+/// ```
+pub struct FnParser<F> {
+    f: F,
+    priority: i32,
+}
+
+impl<F: Fn(&str) -> Option<Episode>> FnParser<F> {
+    pub fn new(f: F) -> Self {
+        Self { f, priority: 0 }
+    }
+
+    /// 设置优先级，默认为 0
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+impl<F: Fn(&str) -> Option<Episode>> Parser for FnParser<F> {
+    fn parse(&self, path: &str) -> Option<Episode> {
+        (self.f)(path)
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+}
+
+/// 正则解析器
+///
+/// 字段既可以通过 `indexes` 按位置指定分组，依次为 title, season, episode, year, air_date, episode_end,
+/// resolution, video_codec, audio_codec, source，
+/// 也可以在正则中使用同名的命名分组，如 `(?P<title>...)`、`(?P<season>...)`、`(?P<video_codec>...)`，存在命名分组时优先使用命名分组，
+/// 按索引指定的分组不存在或没有参与匹配时视为不匹配，命名分组则可以是可选的。
+/// 索引 0 表示没有对应的分组，而不是第 0 组；季数和集数可以是中文数字如 `十二`，见 `chinese_num::parse`；
+/// 没有捕获到季数时使用 `default_season`（默认为 1），
+/// 捕获到的 `00` 则是第 0 季即特别篇；没有捕获到分辨率时从文件名或所在目录名中识别
+///
+/// Examples
+///
+/// ```
+/// use lazytool::{Episode, RegexParser};
+/// use lazytool::media::Resolution;
+///
+/// let pattern = r"/(?P<title>[^/]+)\.S(?P<season>\d{2})E(?P<episode>\d{2})\.(?P<resolution>\d+p)\.(?P<source>[\w-]+)\.(?P<video_codec>\w+)\.(?P<audio_codec>\w+)\.\w+$";
+/// let ep = Episode::from_path_with_regex("/Show.S01E02.1080p.WEB-DL.HEVC.AAC.mkv", vec![RegexParser::named(pattern)]).unwrap().unwrap();
+/// assert_eq!(ep.resolution, Some(Resolution::HD1080));
+/// assert_eq!(ep.source, Some("WEB-DL".to_string()));
+/// assert_eq!(ep.video_codec, Some("HEVC".to_string()));
+/// assert_eq!(ep.audio_codec, Some("AAC".to_string()));
+/// ```
+///
+/// 解析过程通过 `log` 输出记录，匹配成功及正则无效时为 debug 级别，未匹配时为 trace 级别，
+/// 调试解析结果时可在使用方的日志实现中开启
+#[derive(Debug, Deserialize)]
+pub struct RegexParser {
+    pattern: String,
+    #[serde(default)]
+    indexes: Vec<usize>,
+    /// 匹配前是否将全角数字和字母转为半角
+    #[serde(default)]
+    normalize_width: bool,
+    /// 优先级，优先级高的解析器先尝试
+    #[serde(default)]
+    priority: i32,
+    /// 没有捕获到季数时使用的季数，为 None 时季数也为 None
+    #[serde(default = "RegexParser::default_season")]
+    default_season: Option<u32>,
+    /// 季数的转换函数，为 None 时直接解析数字
+    #[serde(skip)]
+    season_transform: Option<NumberTransform>,
+    /// 集数和结束集数的转换函数，为 None 时直接解析数字
+    #[serde(skip)]
+    episode_transform: Option<NumberTransform>,
+    /// 路径中必须包含的字面量，与从正则中提取的必需字面量一起用于匹配前的快速排除
+    #[serde(default)]
+    hints: Vec<String>,
+    /// 首次匹配时编译的正则，编译失败时为 None
+    #[serde(skip)]
+    regex: OnceCell<Option<CompiledPattern>>,
+}
+
+impl RegexParser {
+    pub fn new<P: AsRef<str>>(pattern: P, indexes: Vec<usize>) -> Self {
+        Self {
+            pattern: pattern.as_ref().to_string(),
+            indexes,
+            normalize_width: false,
+            priority: 0,
+            default_season: Self::default_season(),
+            season_transform: None,
+            episode_transform: None,
+            hints: vec![],
+            regex: OnceCell::new(),
+        }
+    }
+
+    /// 创建解析器并立即编译正则，正则无效时返回 `Error::PatternError`，
+    /// 而 `new` 创建的解析器在正则无效时只是不匹配任何路径
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::{Error, RegexParser};
+    ///
+    /// assert!(RegexParser::try_new(r"^(.*?)/([^/]+)S(\d{2})E(\d{2})\.(\w+)$", vec![2, 3, 4]).is_ok());
+    ///
+    /// match RegexParser::try_new(r"^(.*?/([^/]+)$", vec![2]) {
+    ///     Err(Error::PatternError { pattern, .. }) => assert_eq!(pattern, r"^(.*?/([^/]+)$"),
+    ///     other => panic!("unexpected {other:?}"),
+    /// }
+    /// ```
+    pub fn try_new<P: AsRef<str>>(pattern: P, indexes: Vec<usize>) -> Result<Self> {
+        let pattern = pattern.as_ref();
+        let parser = Self::new(pattern, indexes);
+        parser.compile()?;
+        Ok(parser)
+    }
+
+    /// 立即编译正则，正则无效时返回 `Error::PatternError`
+    fn compile(&self) -> Result<()> {
+        if self.regex.get().is_some_and(Option::is_some) {
+            return Ok(());
+        }
+        let compiled = CompiledPattern::new(&self.pattern)
+            .map_err(|source| Error::PatternError { pattern: self.pattern.clone(), source })?;
+        let _ = self.regex.set(Some(compiled));
+        Ok(())
+    }
+
+    /// 添加路径中必须包含的字面量，路径中没有该字面量时直接跳过，不再运行正则
+    ///
+    /// 正则中必需的字面量（如 `第(\d+)集` 中的 `第` 和 `集`）会自动提取，
+    /// 这里用于进一步限定解析器的适用范围，如只解析 `综艺` 目录下的文件
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::{Episode, RegexParser};
+    ///
+    /// let pattern = r"/(?P<title>[^/]+)/(?P<air_date>\d{8})\.\w+$";
+    /// let parser = RegexParser::named(pattern).with_hint("/综艺/");
+    /// let ep = Episode::from_path_with_regex("/综艺/向往的生活/20210115.mp4", vec![parser]).unwrap().unwrap();
+    /// assert_eq!(ep.title, Some("向往的生活".to_string()));
+    ///
+    /// let parser = RegexParser::named(pattern).with_hint("/综艺/");
+    /// assert!(Episode::from_path_with_regex("/照片/旅行/20210115.jpg", vec![parser]).unwrap().is_none());
+    /// ```
+    pub fn with_hint<S: Into<String>>(mut self, literal: S) -> Self {
+        self.hints.push(literal.into());
+        self
+    }
+
+    /// 设置匹配前是否将全角数字和字母转为半角，如 `第０４集` 转为 `第04集`
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::{Episode, RegexParser};
+    ///
+    /// let path = "/还珠格格/还珠格格Ｓ０１Ｅ０２.mp4";
+    /// let parser = RegexParser::new(r"^(.*?)/([^/]+)S(\d{2})E(\d{2})\.(\w+)$", vec![2, 3, 4]);
+    /// assert!(Episode::from_path_with_regex(path, vec![parser]).unwrap().is_none());
+    ///
+    /// let parser = RegexParser::new(r"^(.*?)/([^/]+)S(\d{2})E(\d{2})\.(\w+)$", vec![2, 3, 4])
+    ///     .with_normalize_width(true);
+    /// let ep = Episode::from_path_with_regex(path, vec![parser]).unwrap().unwrap();
+    /// assert_eq!(ep.season, Some(1));
+    /// assert_eq!(ep.episode, Some(2));
+    /// ```
+    pub fn with_normalize_width(mut self, normalize_width: bool) -> Self {
+        self.normalize_width = normalize_width;
+        self
+    }
+
+    /// 设置没有捕获到季数时使用的季数，默认为 `Some(1)`，为 `None` 时不填充季数，
+    /// 便于与其他解析结果合并时区分“没有季数”和“第 1 季”
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::{Episode, RegexParser};
+    ///
+    /// let pattern = r"/(?P<title>[^/]+)第(?P<episode>\d+)集\.\w+$";
+    /// let ep = Episode::from_path_with_regex("/约会专家第04集.mp4", vec![RegexParser::named(pattern)]).unwrap().unwrap();
+    /// assert_eq!(ep.season, Some(1));
+    ///
+    /// let parser = RegexParser::named(pattern).with_default_season(None);
+    /// let ep = Episode::from_path_with_regex("/约会专家第04集.mp4", vec![parser]).unwrap().unwrap();
+    /// assert_eq!(ep.season, None);
+    /// ```
+    pub fn with_default_season(mut self, season: Option<u32>) -> Self {
+        self.default_season = season;
+        self
+    }
+
+    /// `default_season` 的默认值
+    fn default_season() -> Option<u32> {
+        Some(1)
+    }
+
+    /// 设置优先级，默认为 0，优先级高的解析器先尝试
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::{Episode, RegexParser};
+    ///
+    /// let path = "/剧集/还珠格格/HZGG.S01E02.mp4";
+    /// let builtin = RegexParser::new(r"^(.*?)/([^/]+)S(\d{2})E(\d{2})\.(\w+)$", vec![2, 3, 4]);
+    /// let custom = RegexParser::named(r"/(?P<title>[^/]+)/[^/]+S(?P<season>\d{2})E(?P<episode>\d{2})\.\w+$");
+    ///
+    /// let ep = Episode::from_path_with_regex(path, vec![builtin, custom.with_priority(1)]).unwrap().unwrap();
+    /// assert_eq!(ep.title, Some("还珠格格".to_string()));
+    /// ```
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// 设置季数的转换函数，将捕获的文本转为季数，返回 None 时视为没有季数。
+    /// 用于去掉 `Season ` 等前缀或转换罗马数字等，未设置时直接解析数字
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::{Episode, RegexParser};
+    ///
+    /// let pattern = r"/(?P<title>[^/]+)/(?P<season>Season \d+)/(?P<episode>\d+)\.\w+$";
+    /// let parser = RegexParser::named(pattern)
+    ///     .with_season_transform(|s| s.trim_start_matches("Season ").parse().ok());
+    /// let ep = Episode::from_path_with_regex("/Friends/Season 2/05.mkv", vec![parser]).unwrap().unwrap();
+    /// assert_eq!(ep.season, Some(2));
+    ///
+    /// // 未设置时无法解析，季数为 None
+    /// let ep = Episode::from_path_with_regex("/Friends/Season 2/05.mkv", vec![RegexParser::named(pattern)]).unwrap().unwrap();
+    /// assert_eq!(ep.season, None);
+    /// ```
+    pub fn with_season_transform<F>(mut self, f: F) -> Self
+        where F: Fn(&str) -> Option<u32> + Send + Sync + 'static,
+    {
+        self.season_transform = Some(NumberTransform(Box::new(f)));
+        self
+    }
+
+    /// 设置集数的转换函数，同时用于结束集数，见 `with_season_transform`
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::{Episode, RegexParser};
+    ///
+    /// let parser = RegexParser::named(r"/(?P<title>[^/]+)/S01/(?P<episode>[IVX]+)\.\w+$")
+    ///     .with_episode_transform(|s| match s {
+    ///         "I" => Some(1),
+    ///         "II" => Some(2),
+    ///         "III" => Some(3),
+    ///         _ => None,
+    ///     });
+    /// let ep = Episode::from_path_with_regex("/Rocky/S01/III.mkv", vec![parser]).unwrap().unwrap();
+    /// assert_eq!(ep.episode, Some(3));
+    /// ```
+    pub fn with_episode_transform<F>(mut self, f: F) -> Self
+        where F: Fn(&str) -> Option<u32> + Send + Sync + 'static,
+    {
+        self.episode_transform = Some(NumberTransform(Box::new(f)));
+        self
+    }
+
+    /// 按字段将捕获的文本转为数字
+    fn parse_number(&self, name: &str, text: &str) -> Option<u32> {
+        let transform = match name {
+            "season" => &self.season_transform,
+            _ => &self.episode_transform,
+        };
+        match transform {
+            Some(transform) => (transform.0)(text),
+            None => text.parse().ok().or_else(|| chinese_num::parse(text)),
+        }
+    }
+
+    /// 仅使用命名分组的解析器
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::{Episode, RegexParser};
+    ///
+    /// let parser = RegexParser::named(r"/(?P<title>[^/]+)\.S(?P<season>\d{2})E(?P<episode>\d{2})\.\w+$");
+    /// let ep = Episode::from_path_with_regex("/Friends/Friends.S02E05.mkv", vec![parser]).unwrap().unwrap();
+    /// assert_eq!(ep.title, Some("Friends".to_string()));
+    /// assert_eq!(ep.season, Some(2));
+    /// assert_eq!(ep.episode, Some(5));
+    /// ```
+    pub fn named<P: AsRef<str>>(pattern: P) -> Self {
+        Self::new(pattern, vec![])
+    }
+}
+
+/// 编译后的正则及匹配前用于快速排除的必需字面量
+#[derive(Debug)]
+struct CompiledPattern {
+    regex: Regex,
+    literals: Vec<String>,
+}
+
+impl CompiledPattern {
+    fn new(pattern: &str) -> std::result::Result<Self, regex::Error> {
+        let regex = Regex::new(pattern)?;
+        let mut literals = vec![];
+        if let Ok(hir) = regex_syntax::parse(pattern) {
+            required_literals(&hir, &mut literals);
+        }
+        Ok(Self { regex, literals })
+    }
+}
+
+/// 收集匹配时一定会出现的字面量，分支、可选的分组和字符类中的字面量不是必需的
+fn required_literals(hir: &Hir, literals: &mut Vec<String>) {
+    match hir.kind() {
+        HirKind::Literal(literal) => {
+            literals.extend(std::str::from_utf8(&literal.0).ok().map(str::to_string));
+        }
+        HirKind::Capture(capture) => required_literals(&capture.sub, literals),
+        HirKind::Repetition(repetition) if repetition.min > 0 => required_literals(&repetition.sub, literals),
+        HirKind::Concat(subs) => subs.iter().for_each(|sub| required_literals(sub, literals)),
+        _ => {}
+    }
+}
+
+/// 将捕获的文本转为季数、集数的函数
+type TransformFn = dyn Fn(&str) -> Option<u32> + Send + Sync;
+
+/// `RegexParser` 中季数、集数的转换函数
+struct NumberTransform(Box<TransformFn>);
+
+impl std::fmt::Debug for NumberTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("NumberTransform")
+    }
+}
+
+impl Parser for RegexParser {
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    fn parse(&self, path: &str) -> Option<Episode> {
+        self.parse_inner(path, false).ok().flatten()
+    }
+
+    /// 与 `parse` 相比，正则无效、索引超出分组数、数字或日期无法解析时返回错误，而不是不匹配或丢弃该字段。
+    /// 设置了转换函数的字段由转换函数处理，不会返回错误
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::RegexParser;
+    /// use lazytool::media::{ParseError, Parser};
+    ///
+    /// let parser = RegexParser::new(r"/([^/]+)\.E(\d+)\.mp4$", vec![1, 0, 3]);
+    /// assert!(parser.parse("/Show.E02.mp4").is_none());
+    /// assert!(matches!(
+    ///     parser.try_parse("/Show.E02.mp4"),
+    ///     Err(ParseError::IndexOutOfRange { field: "episode", index: 3, groups: 2 }),
+    /// ));
+    ///
+    /// let parser = RegexParser::named(r"/(?P<title>[^/]+)\.S(?P<season>\w+)E(?P<episode>\d+)\.mp4$");
+    /// assert_eq!(parser.parse("/Show.SxxE02.mp4").unwrap().season, None);
+    /// assert!(matches!(parser.try_parse("/Show.SxxE02.mp4"), Err(ParseError::InvalidNumber { field: "season", .. })));
+    /// assert_eq!(parser.try_parse("/Show.S01E02.mp4").unwrap().unwrap().season, Some(1));
+    /// assert!(parser.try_parse("/Show.mp4").unwrap().is_none());
+    /// ```
+    fn try_parse(&self, path: &str) -> std::result::Result<Option<Episode>, ParseError> {
+        self.parse_inner(path, true)
+    }
+}
+
+impl RegexParser {
+    /// 解析路径，`strict` 为 false 时不会返回错误，见 `Parser::try_parse`
+    fn parse_inner(&self, path: &str, strict: bool) -> std::result::Result<Option<Episode>, ParseError> {
+        const FIELDS: [&str; 10] = [
+            "title", "season", "episode", "year", "air_date", "episode_end",
+            "resolution", "video_codec", "audio_codec", "source",
+        ];
+
+        let compiled = self.regex.get_or_init(|| {
+            CompiledPattern::new(&self.pattern)
+                .inspect_err(|e| debug!("invalid pattern {:?}: {e}", self.pattern))
+                .ok()
+        });
+        let Some(compiled) = compiled else {
+            return match Regex::new(&self.pattern) {
+                Err(source) if strict => Err(ParseError::InvalidRegex { pattern: self.pattern.clone(), source }),
+                _ => Ok(None),
+            };
+        };
+        let re = &compiled.regex;
+        if strict {
+            let groups = re.captures_len() - 1;
+            if let Some((&index, field)) = self.indexes.iter().zip(FIELDS).find(|(&i, _)| i > groups) {
+                return Err(ParseError::IndexOutOfRange { field, index, groups });
+            }
+        }
+        let normalized;
+        let path = if self.normalize_width && path.contains(|c| matches!(c, '０'..='９' | 'Ａ'..='Ｚ' | 'ａ'..='ｚ')) {
+            normalized = normalize_width(path);
+            normalized.as_str()
+        } else {
+            path
+        };
+        // 缺少必需的字面量时一定不匹配，跳过开销较大的正则匹配
+        if let Some(literal) = compiled.literals.iter().chain(&self.hints).find(|l| !path.contains(l.as_str())) {
+            trace!("pattern {:?} skipped {path:?} without {literal:?}", self.pattern);
+            return Ok(None);
+        }
+        let Some(caps) = re.captures(path) else {
+            trace!("pattern {:?} did not match {path:?}", self.pattern);
+            return Ok(None);
+        };
+        let names: Vec<&str> = re.capture_names().flatten().collect();
+
+        // 按索引指定的分组超出正则的分组数或没有参与匹配时，视为不匹配
+        let complete = self.indexes.iter()
+            .zip(FIELDS)
+            .filter(|(&i, name)| i != 0 && !names.contains(name))
+            .all(|(&i, _)| caps.get(i).is_some());
+        if !complete {
+            trace!("pattern {:?} matched {path:?} with missing groups", self.pattern);
+            return Ok(None);
+        }
+
+        // 优先按命名分组取值，否则按索引取值，索引不存在或为 0 时表示没有该字段
+        let field = |name: &str, n: usize| {
+            if names.contains(&name) {
+                return caps.name(name).map(|m| m.as_str());
+            }
+            match self.indexes.get(n) {
+                Some(&i) if i != 0 => caps.get(i).map(|m| m.as_str()),
+                _ => None,
+            }
+        };
+
+        // 捕获的数字超出 u32 范围时视为不匹配，而不是静默地丢弃该字段，设置了转换函数的字段由转换函数处理
+        for (name, n) in [("season", 1), ("episode", 2), ("episode_end", 5), ("year", 3)] {
+            let transformed = match name {
+                "season" => self.season_transform.is_some(),
+                "year" => false,
+                _ => self.episode_transform.is_some(),
+            };
+            let Some(text) = field(name, n).filter(|_| !transformed) else {
+                continue;
+            };
+            let number = match name {
+                "year" => text.parse::<u16>().map(u32::from),
+                _ => text.parse::<u32>(),
+            };
+            match number {
+                Err(_) if name != "year" && chinese_num::parse(text).is_some() => {}
+                Err(_) if strict => return Err(ParseError::InvalidNumber { field: name, value: text.to_string() }),
+                Err(e) if name != "year" && *e.kind() == IntErrorKind::PosOverflow => {
+                    debug!("pattern {:?} matched {path:?} with a number out of range", self.pattern);
+                    return Ok(None);
+                }
+                _ => {}
+            }
+        }
+
+        // 剧名，去掉画质、编码、发布组等标记
+        let title = field("title", 0).map(clean_title);
+        // 季数，没有捕获到时使用默认季数，捕获到 `00` 时为特别篇第 0 季
+        let season_text = field("season", 1);
+        let season = match season_text {
+            Some(s) => self.parse_number("season", s),
+            None => self.default_season,
+        };
+        let episode = field("episode", 2).and_then(|s| self.parse_number("episode", s)); // 集数
+        // 结束集数，必须大于开始集数才视为多集
+        let episode_end = field("episode_end", 5)
+            .and_then(|s| self.parse_number("episode_end", s))
+            .filter(|end| episode.is_some_and(|start| *end > start));
+        let year = field("year", 3); // 年份
+        let air_date_text = field("air_date", 4); // 播出日期
+        let air_date = air_date_text.and_then(|s| NaiveDate::parse_from_str(s, "%Y%m%d").ok());
+        if let (Some(text), None, true) = (air_date_text, air_date, strict) {
+            return Err(ParseError::InvalidDate(text.to_string()));
+        }
+        // 分辨率，没有捕获到时从文件名或所在目录名中识别
+        let resolution = match field("resolution", 6) {
+            Some(s) => guess_resolution(s),
+            None => resolution_of(path),
+        };
+        let season_captured = season_text.is_some() && season.is_some();
+        let confidence = Episode::score(title.is_some(), season_captured, episode.is_some() || air_date.is_some());
+        let episode = Episode {
+            title,
+            season,
+            episode,
+            episode_end,
+            year: year.and_then(|s| s.parse().ok()),
+            air_date,
+            container: container_of(path),
+            resolution,
+            video_codec: field("video_codec", 7).map(str::to_string),
+            audio_codec: field("audio_codec", 8).map(str::to_string),
+            source: field("source", 9).map(str::to_string),
+            confidence,
+            source_path: None,
+        };
+        debug!("pattern {:?} matched {path:?}: {episode:?}", self.pattern);
+        Ok(Some(episode))
+    }
+}
+
+/// 从 TOML 配置文件加载的自定义解析器，需要开启 `config` feature
+///
+/// 每个 `[[parsers]]` 对应一个 `RegexParser`，字段与 `RegexParser` 的选项一致，
+/// 只有 `pattern` 是必需的
+///
+/// ```toml
+/// [[parsers]]
+/// pattern = '^(.*?)/([^/]+)/第(\d+)季/(\d+)\.(\w+)$'
+/// indexes = [2, 3, 4]
+///
+/// [[parsers]]
+/// pattern = '/(?P<title>[^/]+)/(?P<air_date>\d{8})\.\w+$'
+/// priority = 1
+/// hints = ["/综艺/"]
+/// ```
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media::ParserConfig;
+///
+/// let config = ParserConfig::from_toml(r#"
+/// [[parsers]]
+/// pattern = '^(.*?)/([^/]+)/第(\d+)季/(\d+)\.(\w+)$'
+/// indexes = [2, 3, 4]
+/// "#).unwrap();
+/// assert_eq!(config.parsers.len(), 1);
+///
+/// assert!(ParserConfig::from_toml("[[parsers]]\nindexes = [2]").is_err());
+/// ```
+#[cfg(feature = "config")]
+#[derive(Debug, Default, Deserialize)]
+pub struct ParserConfig {
+    /// 自定义解析器，按配置中的顺序排列
+    #[serde(default)]
+    pub parsers: Vec<RegexParser>,
+}
+
+#[cfg(feature = "config")]
+impl ParserConfig {
+    /// 默认的配置文件路径
+    pub const DEFAULT_PATH: &'static str = "~/.config/lazytool/parsers.toml";
+
+    /// 从 TOML 字符串解析，配置格式错误时返回 `Error::Toml`，正则无效时返回 `Error::PatternError`
+    pub fn from_toml(s: &str) -> Result<Self> {
+        let config: Self = toml::from_str(s).map_err(|source| Error::Toml { path: None, source })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从配置文件加载，路径支持 `~` 开头
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::media::ParserConfig;
+    /// use lazytool::path::TempDir;
+    ///
+    /// let tmp = TempDir::new("lazytool-parser-config-").unwrap();
+    /// let path = tmp.child("parsers.toml");
+    /// std::fs::write(&path, "[[parsers]]\npattern = '/(?P<title>[^/]+)/(?P<episode>\\d+)\\.mp4$'\n").unwrap();
+    /// assert_eq!(ParserConfig::load(&path).unwrap().parsers.len(), 1);
+    ///
+    /// assert!(ParserConfig::load(tmp.child("missing.toml")).is_err());
+    /// ```
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = crate::path::expand_user(path);
+        let content = fs::read_to_string(&path)
+            .map_err(|e| Error::io(format!("Failed to read {path:?}"), e))?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|source| Error::Toml { path: Some(path), source })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从默认路径 `~/.config/lazytool/parsers.toml` 加载，文件不存在时返回空的配置
+    pub fn load_default() -> Result<Self> {
+        match crate::path::home_dir() {
+            Some(home) => Self::load_from_home(&home),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// 从 `home` 目录下的默认位置加载，文件不存在时返回空的配置
+    fn load_from_home(home: &Path) -> Result<Self> {
+        let path = home.join(Self::DEFAULT_PATH.trim_start_matches("~/"));
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::load(path)
+    }
+
+    /// 编译所有正则，提前发现配置中无效的正则
+    fn validate(&self) -> Result<()> {
+        self.parsers.iter().try_for_each(RegexParser::compile)
+    }
+}
+
+/// 基于文件名模板的解析器
+///
+/// 模板中的占位符会被编译为正则的命名分组，其余部分按字面匹配，模板从路径的某一层开始匹配到结尾：
+///
+/// - `{title}` 剧名
+/// - `{season}`、`{episode}`、`{episode_end}` 数字，可通过 `{season:2}` 指定位数
+/// - `{year}` 四位年份，`{air_date}` 八位日期如 `20210115`
+/// - `{ext}` 扩展名，`{*}` 任意内容
+///
+/// Examples
+///
+/// ```
+/// use lazytool::{Episode, TemplateParser};
+///
+/// let parser = TemplateParser::new("{title}.S{season:2}E{episode:2}.{ext}").unwrap();
+/// let ep = Episode::from_path_with_regex("/Friends/Friends.S02E05.mkv", vec![parser]).unwrap().unwrap();
+/// assert_eq!(ep.title, Some("Friends".to_string()));
+/// assert_eq!(ep.season, Some(2));
+/// assert_eq!(ep.episode, Some(5));
+///
+/// // 模板可以包含目录
+/// let parser = TemplateParser::new("{title}/第{season}季/{episode}.{ext}").unwrap();
+/// let ep = Episode::from_path_with_regex("/影片/武林外传/第2季/05.mp4", vec![parser]).unwrap().unwrap();
+/// assert_eq!(ep.title, Some("武林外传".to_string()));
+/// assert_eq!(ep.season, Some(2));
+/// ```
+#[derive(Debug)]
+pub struct TemplateParser {
+    template: String,
+    parser: RegexParser,
+}
+
+impl TemplateParser {
+    /// 编译模板，占位符未知或括号未闭合时返回错误
+    pub fn new<T: AsRef<str>>(template: T) -> Result<Self> {
+        let template = template.as_ref();
+        let mut pattern = String::from("(?:^|/)");
+        for part in template_parts(template)? {
+            match part {
+                TemplatePart::Literal(literal) => pattern.push_str(&regex::escape(literal)),
+                TemplatePart::Placeholder(placeholder) => {
+                    pattern.push_str(&Self::placeholder_pattern(placeholder)
+                        .ok_or_else(|| unknown_placeholder(template, placeholder))?);
+                }
+            }
+        }
+        pattern.push('$');
+
+        Ok(Self { template: template.to_string(), parser: RegexParser::named(pattern) })
+    }
+
+    /// 原始模板
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// 模板编译得到的正则
+    ///
+    /// Examples
+    ///
+    /// ```
+    /// use lazytool::TemplateParser;
+    ///
+    /// let parser = TemplateParser::new("{title}.E{episode:2}").unwrap();
+    /// assert_eq!(parser.pattern(), r"(?:^|/)(?P<title>[^/]+?)\.E(?P<episode>\d{2})$");
+    /// ```
+    pub fn pattern(&self) -> &str {
+        &self.parser.pattern
+    }
+
+    fn placeholder_pattern(placeholder: &str) -> Option<String> {
+        let (name, width) = match placeholder.split_once(':') {
+            Some((name, width)) => (name, Some(width.parse::<usize>().ok().filter(|w| *w > 0)?)),
+            None => (placeholder, None),
+        };
+        let digits = |default: &str| match width {
+            Some(w) => format!(r"\d{{{w}}}"),
+            None => default.to_string(),
+        };
+        let pattern = match name {
+            "title" if width.is_none() => r"(?P<title>[^/]+?)".to_string(),
+            "season" | "episode" | "episode_end" => format!("(?P<{name}>{})", digits(r"\d+")),
+            "year" => format!("(?P<year>{})", digits(r"\d{4}")),
+            "air_date" if width.is_none() => r"(?P<air_date>\d{8})".to_string(),
+            "ext" if width.is_none() => r"(?P<ext>\w+)".to_string(),
+            "*" if width.is_none() => r"[^/]*?".to_string(),
+            _ => return None,
+        };
+        Some(pattern)
+    }
+}
+
+impl Parser for TemplateParser {
+    fn parse(&self, path: &str) -> Option<Episode> {
+        self.parser.parse(path)
+    }
+
+    fn try_parse(&self, path: &str) -> std::result::Result<Option<Episode>, ParseError> {
+        self.parser.try_parse(path)
+    }
+
+    fn priority(&self) -> i32 {
+        self.parser.priority
+    }
+}
+
+/// 文件名模板中的片段
+enum TemplatePart<'a> {
+    Literal(&'a str),
+    /// 去掉括号的占位符，如 `season:2`
+    Placeholder(&'a str),
+}
+
+/// 将模板拆分为字面量和占位符，括号未闭合时返回 `Error::InvalidPattern`
+fn template_parts(template: &str) -> Result<Vec<TemplatePart<'_>>> {
+    let mut parts = vec![];
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        parts.push(TemplatePart::Literal(&rest[..start]));
+        let end = rest[start..].find('}')
+            .ok_or_else(|| Error::InvalidPattern {
+                pattern: template.to_string(),
+                reason: "unclosed placeholder".to_string(),
+            })?;
+        parts.push(TemplatePart::Placeholder(&rest[start + 1..start + end]));
+        rest = &rest[start + end + 1..];
+    }
+    parts.push(TemplatePart::Literal(rest));
+    Ok(parts)
+}
+
+/// 检查 `Episode::to_filename_with` 的模板，占位符未知、宽度不合法或括号未闭合时返回 `Error::InvalidPattern`
+fn validate_filename_template(template: &str) -> Result<()> {
+    for part in template_parts(template)? {
+        if let TemplatePart::Placeholder(placeholder) = part {
+            filename_field(template, placeholder)?;
+        }
+    }
+    Ok(())
+}
+
+/// 拆出 `Episode::to_filename_with` 占位符中的字段名和宽度，没有宽度时为 0，只有数字字段可以指定宽度
+fn filename_field<'a>(template: &str, placeholder: &'a str) -> Result<(&'a str, usize)> {
+    let (field, width) = match placeholder.split_once(':') {
+        Some((field, width)) => match width.parse::<usize>() {
+            Ok(width) if width > 0 => (field, width),
+            _ => return Err(unknown_placeholder(template, placeholder)),
+        },
+        None => (placeholder, 0),
+    };
+    match field {
+        "season" | "episode" | "episode_end" | "year" => Ok((field, width)),
+        "title" | "air_date" | "ext" if width == 0 => Ok((field, width)),
+        _ => Err(unknown_placeholder(template, placeholder)),
+    }
+}
+
+fn unknown_placeholder(template: &str, placeholder: &str) -> Error {
+    Error::InvalidPattern {
+        pattern: template.to_string(),
+        reason: format!("unknown placeholder {{{placeholder}}}"),
+    }
+}
+
+/// 从目录结构中解析剧集信息的解析器
+///
+/// 适用于 `.../神探狄仁杰/Season 2/Episode 05/video.mp4` 这类信息分散在多级目录中的路径：
+/// 从后向前查找 `Episode 05`、`EP05`、`E05`、`第5集` 形式的集数和 `Season 2`、`S02`、`第2季` 形式的季数，
+/// `Specials`、`SP`、`特别篇` 目录视为第 0 季，文件名（去掉扩展名）也可以是集数，剧名取季数或集数所在目录的上一级
+///
+/// Examples
+///
+/// ```
+/// use lazytool::{Episode, media::DirectoryParser};
+///
+/// let path = "/影片/神探狄仁杰/Season 2/Episode 05/video.mp4";
+/// let ep = Episode::from_path_with_regex(path, vec![DirectoryParser]).unwrap().unwrap();
+/// assert_eq!(ep.title, Some("神探狄仁杰".to_string()));
+/// assert_eq!(ep.season, Some(2));
+/// assert_eq!(ep.episode, Some(5));
+///
+/// let path = "/影片/神探狄仁杰/Specials/E01.mp4";
+/// let ep = Episode::from_path_with_regex(path, vec![DirectoryParser]).unwrap().unwrap();
+/// assert_eq!(ep.season, Some(0));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DirectoryParser;
+
+impl Parser for DirectoryParser {
+    fn parse(&self, path: &str) -> Option<Episode> {
+        static SEASON: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"(?i)^(?:season[\s._-]*|s)(\d{1,3})$|^第(\d{1,3})季$").unwrap()
+        });
+        static EPISODE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"(?i)^(?:episode[\s._-]*|ep?[\s._-]*)(\d{1,4})$|^第(\d{1,4})集$").unwrap()
+        });
+        static SPECIALS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^(?:specials?|sp|特别篇)$").unwrap());
+        let number = |re: &Regex, text: &str| -> Option<u32> {
+            let caps = re.captures(text.trim())?;
+            caps.get(1).or_else(|| caps.get(2))?.as_str().parse().ok()
+        };
+
+        let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+        let (filename, dirs) = components.split_last()?;
+        let stem = Path::new(filename).file_stem()?.to_str()?;
+
+        // 集数优先取文件名，其次取最近的目录
+        let mut episode = number(&EPISODE, stem).map(|n| (n, dirs.len()));
+        let mut season = None;
+        for (i, dir) in dirs.iter().enumerate().rev() {
+            if episode.is_none() {
+                if let Some(n) = number(&EPISODE, dir) {
+                    episode = Some((n, i));
+                    continue;
+                }
+            }
+            if season.is_none() {
+                let n = number(&SEASON, dir).or_else(|| SPECIALS.is_match(dir.trim()).then_some(0));
+                if let Some(n) = n {
+                    season = Some((n, i));
+                }
+            }
+        }
+        let (episode, episode_at) = episode?;
+
+        // 剧名位于季数或集数所在目录的上一级
+        let top = season.map_or(episode_at, |(_, i)| i.min(episode_at));
+        let title = top.checked_sub(1)
+            .and_then(|i| dirs.get(i))
+            .map(|dir| clean_title(dir))
+            .filter(|title| !title.is_empty());
+
+        let confidence = Episode::score(title.is_some(), season.is_some(), true);
+        Some(Episode {
+            title,
+            season: Some(season.map_or(1, |(n, _)| n)),
+            episode: Some(episode),
+            container: container_of(path),
+            resolution: resolution_of(path),
+            confidence,
+            ..Default::default()
+        })
+    }
+}
+
+/// 清理剧名中的发布信息
+///
+/// 去掉括号中的内容（如年份 `(2013)`），将 `.`、`_` 转为空格，并从第一个年份、季集标记、
+/// 画质、编码或来源标记（如 `2013`、`E02`、`1080P`、`HEVC`、`WEB-DL`）处截断，
+/// 再去掉结尾的 `-GROUP` 发布组；中文剧名后跟随的英文译名也会被去掉
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media;
+///
+/// assert_eq!(media::clean_title("龙门镖局.Longmen.Express.2013.E02.4K.2160p.HEVC.AAC-DHTCLUB"), "龙门镖局");
+/// assert_eq!(media::clean_title("Show.Name.S01E05.1080p.WEB-DL.x265"), "Show Name");
+/// assert_eq!(media::clean_title("Longmen_Express (2013) 1080P"), "Longmen Express");
+/// assert_eq!(media::clean_title("Title-DHTCLUB"), "Title");
+/// assert_eq!(media::clean_title("X-Men"), "X-Men");
+/// assert_eq!(media::clean_title("2046"), "2046");
+/// assert_eq!(media::clean_title("还珠格格."), "还珠格格");
+/// ```
+pub fn clean_title(raw: &str) -> String {
+    static BRACKETS: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"\([^)]*\)|\[[^\]]*\]|【[^】]*】|（[^）]*）").unwrap()
+    });
+    static STOP_TOKEN: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(concat!(
+            r"(?i)^(?:(?:19|20)\d{2}|s\d{1,2}(?:e\d{1,3})?|e\d{1,3}|ep\d{1,3}|\d{1,2}x\d{1,3}",
+            r"|\d{3,4}[pi]|[48]k|uhd|hdr\d*|hevc|avc|[hx]26[45]|aac\d*|e?ac3|dts|flac|ddp?\d*|atmos",
+            r"|web|webdl|webrip|bluray|bdrip|brrip|hdtv|dvdrip|remux|repack|proper|\d+bit)$",
+        )).unwrap()
+    });
+    static GROUP_SUFFIX: Lazy<Regex> = Lazy::new(|| Regex::new(r"-[A-Z0-9]{2,}$").unwrap());
+
+    let text = BRACKETS.replace_all(raw, " ").replace(['.', '_'], " ");
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+
+    let is_cjk = |t: &String| t.chars().any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c));
+    let mut kept: Vec<String> = vec![];
+    for (i, token) in tokens.iter().enumerate() {
+        // `AAC-DHTCLUB` 按 `-` 前的部分判断
+        let head = token.split('-').next().unwrap_or(token);
+        if i > 0 && (STOP_TOKEN.is_match(token) || STOP_TOKEN.is_match(head)) {
+            break;
+        }
+        // 中文剧名之后的纯英文单词视为译名
+        if kept.first().is_some_and(is_cjk) && token.chars().all(|c| c.is_ascii_alphabetic()) {
+            break;
+        }
+        kept.push(token.to_string());
+    }
+    if let Some(last) = kept.last_mut() {
+        *last = GROUP_SUFFIX.replace(last, "").into_owned();
+    }
+
+    let title = kept.join(" ");
+    let title = title.trim_matches(['-', ' ']);
+    if title.is_empty() {
+        raw.trim_matches(['.', '_', '-', ' ']).to_string()
+    } else {
+        title.to_string()
+    }
+}
+
+/// 将全角数字和字母转为对应的半角字符，其他字符保持不变
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media;
+///
+/// assert_eq!(media::normalize_width("约会专家第０４集"), "约会专家第04集");
+/// assert_eq!(media::normalize_width("Ｓ０１Ｅ０２ｖ２"), "S01E02v2");
+/// ```
+pub fn normalize_width(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '０'..='９' | 'Ａ'..='Ｚ' | 'ａ'..='ｚ' => {
+                char::from_u32(c as u32 - 0xFEE0).unwrap_or(c)
+            }
+            _ => c,
+        })
+        .collect()
+}
+
+/// 拼音的声调样式，见 `to_pinyin_with`
+#[cfg(feature = "pinyin")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PinyinStyle {
+    /// 不带声调，如 `shen`
+    #[default]
+    Plain,
+    /// 带声调符号，如 `shén`
+    Tone,
+    /// 声调数字放在末尾，如 `shen2`
+    ToneNum,
+}
+
+/// 将剧名转为不带声调的拼音，音节之间以空格分隔，需要开启 `pinyin` feature
+///
+/// 非汉字的连续字符作为一个整体保留，空白字符只作为分隔。
+/// 按单字转换，多音字取最常用的读音，如 `还珠格格` 转为 `hai zhu ge ge`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media;
+///
+/// assert_eq!(media::to_pinyin("神探狄仁杰"), "shen tan di ren jie");
+/// assert_eq!(media::to_pinyin("龙门镖局2013"), "long men biao ju 2013");
+/// assert_eq!(media::to_pinyin("Friends"), "Friends");
+/// ```
+#[cfg(feature = "pinyin")]
+pub fn to_pinyin(title: &str) -> String {
+    to_pinyin_with(title, PinyinStyle::default())
+}
+
+/// 按指定的声调样式将剧名转为拼音，见 `to_pinyin`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media::{self, PinyinStyle};
+///
+/// assert_eq!(media::to_pinyin_with("神探狄仁杰", PinyinStyle::Tone), "shén tàn dí rén jié");
+/// assert_eq!(media::to_pinyin_with("神探狄仁杰", PinyinStyle::ToneNum), "shen2 tan4 di2 ren2 jie2");
+/// ```
+#[cfg(feature = "pinyin")]
+pub fn to_pinyin_with(title: &str, style: PinyinStyle) -> String {
+    use pinyin::ToPinyin;
+
+    let mut words = vec![];
+    let mut word = String::new();
+    for c in title.chars() {
+        let syllable = c.to_pinyin().map(|p| match style {
+            PinyinStyle::Plain => p.plain(),
+            PinyinStyle::Tone => p.with_tone(),
+            PinyinStyle::ToneNum => p.with_tone_num_end(),
+        });
+        if syllable.is_some() || c.is_whitespace() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            words.extend(syllable.map(str::to_string));
+        } else {
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words.join(" ")
+}
+
+/// 媒体文件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Video,
+    Subtitle,
+    Audio,
+    Metadata,
+    Unknown,
+}
+
+impl MediaKind {
+    pub const VIDEO_EXTENSIONS: &[&str] = &[
+        "mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v", "ts", "m2ts", "rmvb", "rm", "mpg", "mpeg", "iso",
+    ];
+    pub const SUBTITLE_EXTENSIONS: &[&str] = &["srt", "ass", "ssa", "vtt", "sub", "idx", "sup"];
+    pub const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "aac", "m4a", "wav", "ogg", "opus", "ape", "wma"];
+    pub const METADATA_EXTENSIONS: &[&str] = &["nfo", "json", "xml", "jpg", "jpeg", "png", "txt"];
+}
+
+/// 视频分辨率，按清晰度从低到高排列，可直接比较大小
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Resolution {
+    /// 标清，如 `480p`、`576p`
+    SD,
+    /// `720p`
+    HD720,
+    /// `1080p`、`1080i`
+    HD1080,
+    /// `2160p`、`4K`、`UHD`
+    UHD4K,
+    /// `4320p`、`8K`
+    UHD8K,
+}
+
+impl Resolution {
+    /// 对应的画面高度（像素），标清按 480 计
+    pub fn height(&self) -> u32 {
+        match self {
+            Self::SD => 480,
+            Self::HD720 => 720,
+            Self::HD1080 => 1080,
+            Self::UHD4K => 2160,
+            Self::UHD8K => 4320,
+        }
+    }
+
+    /// 按画面高度归类
+    fn from_height(height: u32) -> Option<Self> {
+        match height {
+            0..=239 => None,
+            240..=599 => Some(Self::SD),
+            600..=899 => Some(Self::HD720),
+            900..=1799 => Some(Self::HD1080),
+            1800..=3599 => Some(Self::UHD4K),
+            _ => Some(Self::UHD8K),
+        }
+    }
+}
+
+/// 从文件名中猜测分辨率，不区分大小写，返回第一个识别到的标记
+///
+/// 支持 `480p`、`720p`、`1080p`、`1080i`、`2160p`、`4320p` 等，`4K`、`UHD` 视为 `2160p`，
+/// `8K` 视为 `4320p`，`SD` 视为标清，以及 `1920x1080` 形式的宽高。
+/// 标记前后需要是非字母数字的字符，以免误识别 `S04K` 之类的内容
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media::{self, Resolution};
+///
+/// assert_eq!(media::guess_resolution("怪侠一枝梅.全30集.4K"), Some(Resolution::UHD4K));
+/// assert_eq!(media::guess_resolution("Show.S01E02.2160p.WEB-DL.mkv"), Some(Resolution::UHD4K));
+/// assert_eq!(media::guess_resolution("医馆笑传S01.37集.1080P"), Some(Resolution::HD1080));
+/// assert_eq!(media::guess_resolution("Show [1280x720].mp4"), Some(Resolution::HD720));
+/// assert_eq!(media::guess_resolution("Show.S01E02.x264.mkv"), None);
+///
+/// assert!(Resolution::UHD4K > Resolution::HD1080);
+/// ```
+pub fn guess_resolution(name: &str) -> Option<Resolution> {
+    static RESOLUTION: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?i)(?:^|[^a-z0-9])(?:(\d{3,4})[pi]|(4|8)k|(uhd)|(sd)|\d{3,4}x(\d{3,4}))(?:$|[^a-z0-9])").unwrap()
+    });
+
+    let caps = RESOLUTION.captures(name)?;
+    if let Some(height) = caps.get(1).or_else(|| caps.get(5)) {
+        return Resolution::from_height(height.as_str().parse().ok()?);
+    }
+    match caps.get(2).map(|m| m.as_str()) {
+        Some("4") => Some(Resolution::UHD4K),
+        Some(_) => Some(Resolution::UHD8K),
+        None if caps.get(3).is_some() => Some(Resolution::UHD4K),
+        None => Some(Resolution::SD),
+    }
+}
+
+/// 根据扩展名判断媒体文件类型，不区分大小写，扩展名可带 `.` 前缀
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media::{self, MediaKind};
+///
+/// assert_eq!(media::classify_extension("MKV"), MediaKind::Video);
+/// assert_eq!(media::classify_extension(".srt"), MediaKind::Subtitle);
+/// assert_eq!(media::classify_extension("nfo"), MediaKind::Metadata);
+/// assert_eq!(media::classify_extension("exe"), MediaKind::Unknown);
+/// ```
+pub fn classify_extension(ext: &str) -> MediaKind {
+    let ext = ext.trim_start_matches('.').to_ascii_lowercase();
+    let ext = ext.as_str();
+    if MediaKind::VIDEO_EXTENSIONS.contains(&ext) {
+        MediaKind::Video
+    } else if MediaKind::SUBTITLE_EXTENSIONS.contains(&ext) {
+        MediaKind::Subtitle
+    } else if MediaKind::AUDIO_EXTENSIONS.contains(&ext) {
+        MediaKind::Audio
+    } else if MediaKind::METADATA_EXTENSIONS.contains(&ext) {
+        MediaKind::Metadata
+    } else {
+        MediaKind::Unknown
+    }
+}
+
+/// 查找视频文件同目录下对应的字幕文件
+///
+/// 字幕文件名与视频文件名（不含扩展名）相同，或带有语言后缀如 `还珠格格S01E02.zh.srt`，
+/// 或包含相同的 `S01E02` 标记，或按 `Episode::from_path` 解析出相同的剧名、季数和集数（或播出日期）时
+/// 视为对应的字幕，结果按路径排序
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media;
+/// use lazytool::path::TempDir;
+///
+/// let tmp = TempDir::new("lazytool-find-subtitles-").unwrap();
+/// let dir = tmp.path();
+/// for name in ["向往的生活第20210115期.mkv", "向往的生活 第20210115期.chs.ass", "向往的生活第20210122期.srt"] {
+///     std::fs::write(dir.join(name), "").unwrap();
+/// }
+///
+/// let subtitles = media::find_subtitles(dir.join("向往的生活第20210115期.mkv")).unwrap();
+/// assert_eq!(subtitles, vec![dir.join("向往的生活 第20210115期.chs.ass")]);
+/// ```
+pub fn find_subtitles<P: AsRef<Path>>(episode_path: P) -> Result<Vec<PathBuf>> {
+    let episode_path = episode_path.as_ref();
+    let dir = match episode_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let stem = episode_path.file_stem()
+        .ok_or_else(|| Error::InvalidPath(episode_path.to_path_buf()))?
+        .to_string_lossy()
+        .into_owned();
+    let marker = season_episode_marker(&stem);
+    let episode = Episode::from_path(episode_path).ok().flatten();
+
+    let mut subtitles = vec![];
+    let entries = fs::read_dir(dir).map_err(|e| Error::io(format!("Failed to read directory {dir:?}"), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| Error::io(format!("Failed to read directory {dir:?}"), e))?.path();
+        let is_subtitle = path.extension()
+            .map(|ext| classify_extension(&ext.to_string_lossy()) == MediaKind::Subtitle)
+            .unwrap_or(false);
+        if !is_subtitle || !path.is_file() {
+            continue;
+        }
+
+        let sub_stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let same_stem = sub_stem == stem || sub_stem.starts_with(&format!("{stem}."));
+        let same_marker = marker.is_some() && season_episode_marker(&sub_stem) == marker;
+        let same_episode = || episode.as_ref()
+            .zip(subtitle_episode(&path, &sub_stem))
+            .is_some_and(|(a, b)| a.is_same_episode(&b));
+        if same_stem || same_marker || same_episode() {
+            subtitles.push(path);
+        }
+    }
+    subtitles.sort();
+    Ok(subtitles)
+}
+
+/// 解析字幕文件对应的剧集，去掉 `.zh`、`.chs`、`.en-US` 等语言后缀后再按内置的模式解析
+fn subtitle_episode(path: &Path, stem: &str) -> Option<Episode> {
+    static LANGUAGE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"\.[A-Za-z]{2,3}(?:[-_][A-Za-z]{2,4})?$").unwrap()
+    });
+    let ext = path.extension()?.to_str()?;
+    let stem = LANGUAGE.replace(stem, "");
+    Episode::from_path(path.with_file_name(format!("{stem}.{ext}"))).ok().flatten()
+}
+
+/// 批量重命名的计划，由 `rename_plan` 生成
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenamePlan {
+    /// 原路径和新路径，按原路径排序
+    pub operations: Vec<(PathBuf, PathBuf)>,
+}
+
+impl RenamePlan {
+    /// 执行重命名，自动创建新路径的上级目录
+    ///
+    /// 执行前检查所有新路径，存在重复或已有文件时返回 `Error::AlreadyExists`，不会移动任何文件
+    pub fn apply(&self) -> Result<()> {
+        let mut targets = std::collections::HashSet::new();
+        for (_, new) in &self.operations {
+            if !targets.insert(new) || new.exists() {
+                return Err(Error::AlreadyExists(new.clone()));
+            }
+        }
+        for (old, new) in &self.operations {
+            crate::path::ensure_parent(new)?;
+            fs::rename(old, new)
+                .map_err(|e| Error::io(format!("Failed to rename {old:?} to {new:?}"), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// 扫描目录下的视频文件，按模板生成相对于该目录的新路径，返回需要重命名的文件
+///
+/// 模板见 `Episode::to_filename_with`，无法解析、模板字段缺失或已经是目标路径的文件会被跳过
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media;
+/// use lazytool::path::TempDir;
+///
+/// let tmp = TempDir::new("lazytool-rename-plan-").unwrap();
+/// let dir = tmp.path();
+/// std::fs::create_dir_all(dir.join("还珠格格S01.国语中字.1080P")).unwrap();
+/// std::fs::write(dir.join("还珠格格S01.国语中字.1080P/还珠格格S01E02.mp4"), "").unwrap();
+///
+/// let template = "{title}/Season {season:2}/{title} - S{season:2}E{episode:2}.{ext}";
+/// let plan = media::rename_plan(dir, template).unwrap();
+/// assert_eq!(plan.operations, vec![(
+///     dir.join("还珠格格S01.国语中字.1080P/还珠格格S01E02.mp4"),
+///     dir.join("还珠格格/Season 01/还珠格格 - S01E02.mp4"),
+/// )]);
+///
+/// plan.apply().unwrap();
+/// assert!(dir.join("还珠格格/Season 01/还珠格格 - S01E02.mp4").is_file());
+/// assert!(media::rename_plan(dir, template).unwrap().operations.is_empty());
+/// ```
+pub fn rename_plan<P: AsRef<Path>>(dir: P, template: &str) -> Result<RenamePlan> {
+    let dir = crate::path::expand_user(dir);
+    // 先检查模板，目录为空或没有可解析的文件时也能发现模板错误
+    validate_filename_template(template)?;
+    let mut operations = vec![];
+    for ep in Episode::scan_dir(&dir)? {
+        let (Some(old), Some(name)) = (ep.source_path.as_ref(), ep.to_filename_with(template)?) else {
+            continue;
+        };
+        let new = dir.join(name);
+        if *old != new {
+            operations.push((old.clone(), new));
+        }
+    }
+    Ok(RenamePlan { operations })
+}
+
+/// `scan_library_with` 的选项
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// 最大深度，根目录下的直接子项深度为 1
+    pub max_depth: Option<usize>,
+    /// 是否多线程解析，线程数为可用的 CPU 核数
+    pub parallel: bool,
+}
+
+/// 递归扫描媒体库下的视频文件并用内置的解析器解析，无法解析的文件对应 None，结果按路径排序
+///
+/// 与 `Episode::scan_dir` 不同，无法解析的文件也会返回，便于找出需要手动处理的文件。
+/// 跳过隐藏文件和目录，根目录无法读取时返回错误
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media;
+/// use lazytool::path::TempDir;
+///
+/// let tmp = TempDir::new("lazytool-scan-library-").unwrap();
+/// let dir = tmp.path();
+/// std::fs::create_dir_all(dir.join("还珠格格S01")).unwrap();
+/// std::fs::write(dir.join("还珠格格S01/还珠格格S01E01.mp4"), "").unwrap();
+/// std::fs::write(dir.join("还珠格格S01/预告.mp4"), "").unwrap();
+/// std::fs::write(dir.join("还珠格格S01/poster.jpg"), "").unwrap();
+///
+/// let items = media::scan_library(dir).unwrap();
+/// assert_eq!(items.len(), 2);
+/// assert_eq!(items[0].0, dir.join("还珠格格S01/还珠格格S01E01.mp4"));
+/// assert_eq!(items[0].1.as_ref().unwrap().episode, Some(1));
+/// assert!(items[1].1.is_none());
+/// ```
+pub fn scan_library<P: AsRef<Path>>(root: P) -> Result<Vec<(PathBuf, Option<Episode>)>> {
+    scan_library_with(root, ScanOptions::default())
+}
+
+/// 按选项扫描媒体库，见 `scan_library`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media::{self, ScanOptions};
+/// use lazytool::path::TempDir;
+///
+/// let tmp = TempDir::new("lazytool-scan-library-with-").unwrap();
+/// let dir = tmp.path();
+/// for i in 1..=9 {
+///     std::fs::write(dir.join(format!("还珠格格S01E0{i}.mp4")), "").unwrap();
+/// }
+///
+/// let items = media::scan_library_with(dir, ScanOptions { parallel: true, ..Default::default() }).unwrap();
+/// let episodes: Vec<u32> = items.iter().filter_map(|(_, ep)| ep.as_ref()?.episode).collect();
+/// assert_eq!(episodes, (1..=9).collect::<Vec<_>>());
+/// ```
+pub fn scan_library_with<P: AsRef<Path>>(root: P, opts: ScanOptions) -> Result<Vec<(PathBuf, Option<Episode>)>> {
+    let root = crate::path::expand_user(root);
+    let walk = crate::path::WalkOptions {
+        extensions: Some(MediaKind::VIDEO_EXTENSIONS.iter().map(|e| e.to_string()).collect()),
+        max_depth: opts.max_depth,
+        ..Default::default()
+    };
+    let files = crate::path::walk_files(&root, walk)?;
+    let parsers = Episode::builtin_parsers();
+    let parse = |path: &PathBuf| (path.clone(), Episode::parse_with(path, parsers));
+    if !opts.parallel {
+        return Ok(files.iter().map(parse).collect());
+    }
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = files.len().div_ceil(threads).max(1);
+    let items = std::thread::scope(|scope| {
+        let handles: Vec<_> = files.chunks(chunk_size)
+            .map(|chunk| scope.spawn(|| chunk.iter().map(parse).collect::<Vec<_>>()))
+            .collect();
+        handles.into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    });
+    Ok(items)
+}
+
+/// 按优先级从高到低排列解析器，优先级相同时保持原有顺序
+fn by_priority<T: Parser>(mut parsers: Vec<T>) -> Vec<T> {
+    parsers.sort_by_key(|p| std::cmp::Reverse(p.priority()));
+    parsers
+}
+
+/// 按文件名识别分辨率，文件名中没有时使用所在目录名
+fn resolution_of(path: &str) -> Option<Resolution> {
+    let mut names = path.rsplit('/');
+    names.next().and_then(guess_resolution).or_else(|| names.next().and_then(guess_resolution))
+}
+
+/// 获取路径中视频文件的扩展名作为容器格式
+fn container_of(path: &str) -> Option<String> {
+    let ext = Path::new(path).extension()?.to_str()?;
+    match classify_extension(ext) {
+        MediaKind::Video => Some(ext.to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+/// 将路径转为字符串并把 Windows 的 `\` 分隔符统一为 `/`，使内置的模式在各平台上都能匹配
+fn normalize_separators(path: &Path) -> Result<String> {
+    let path = path.to_str().ok_or_else(|| Error::InvalidPath(path.to_path_buf()))?;
+    Ok(path.replace('\\', "/"))
+}
+
+/// 提取文件名中的 `S01E02` 标记
+fn season_episode_marker(name: &str) -> Option<(u32, u32)> {
+    static MARKER: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?i)S(\d{1,2})E(\d{1,3})").unwrap()
+    });
+    let caps = MARKER.captures(name)?;
+    Some((caps.get(1)?.as_str().parse().ok()?, caps.get(2)?.as_str().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChainParser, DirectoryParser, Episode, FnParser, Layout, Media, MediaKind, Movie, Parser, ParserRegistry, RegexParser, Resolution, TemplateParser, classify_extension, detect, find_subtitles, guess_resolution, rename_plan, scan_library, scan_library_with, ScanOptions};
+    use crate::path::TempDir;
+    use crate::Error;
+    use chrono::NaiveDate;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_match_pattern1() {
+        let path = "/Volumes/Getea/影片/电视剧/医馆笑传/医馆笑传S01.37集.1080P/01.mp4";
+        let item = Episode::from_path(path).unwrap();
+        assert!(item.is_some());
+        if let Some(ep) = item {
+            assert_eq!(ep.title, Some("医馆笑传".to_string()));
+            assert_eq!(ep.season, Some(1));
+            assert_eq!(ep.episode, Some(1));
+        }
+    }
+
+    #[test]
+    fn test_match_pattern2() {
+        let path = "/Volumes/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4";
+        let item = Episode::from_path(path).unwrap();
+        println!("{item:?}");
+        assert!(item.is_some());
+        if let Some(ep) = item {
+            assert_eq!(ep.title, Some("还珠格格".to_string()));
+            assert_eq!(ep.season, Some(1));
+            assert_eq!(ep.episode, Some(2));
+            assert_eq!(ep.year, None);
+        }
+    }
+
+    #[test]
+    fn test_match_pattern9() {
+        let path = "/Volumes/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E01-E02.mp4";
+        let ep = Episode::from_path(path).unwrap().unwrap();
+        assert_eq!(ep.title, Some("还珠格格".to_string()));
+        assert_eq!(ep.season, Some(1));
+        assert_eq!(ep.episode, Some(1));
+        assert_eq!(ep.episode_end, Some(2));
+
+        let ep = Episode::from_path("/Volumes/还珠格格S01/还珠格格S01E03-04.mkv").unwrap().unwrap();
+        assert_eq!(ep.episode, Some(3));
+        assert_eq!(ep.episode_end, Some(4));
+
+        // 单集不设置结束集数
+        let ep = Episode::from_path("/Volumes/还珠格格S01/还珠格格S01E02.mp4").unwrap().unwrap();
+        assert_eq!(ep.episode, Some(2));
+        assert_eq!(ep.episode_end, None);
+    }
+
+    #[test]
+    fn test_large_episode_number() {
+        // 超出 u16 范围的集数
+        let parser = RegexParser::named(r"/(?P<title>[^/]+) - (?P<episode>\d+)\.\w+$");
+        let ep = parser.parse("/动画/海贼王 - 70000.mkv").unwrap();
+        assert_eq!(ep.episode, Some(70000));
+    }
+
+    #[test]
+    fn test_merge() {
+        let title_only = Episode {
+            title: Some("还珠格格".to_string()),
+            year: Some(1998),
+            confidence: 0.45,
+            ..Default::default()
+        };
+        let numbers_only = Episode {
+            title: Some("S01".to_string()),
+            season: Some(1),
+            episode: Some(2),
+            episode_end: Some(3),
+            container: Some("mkv".to_string()),
+            confidence: 0.7,
+            ..Default::default()
+        };
+
+        let ep = title_only.clone().merge(numbers_only.clone());
+        assert_eq!(ep.title, Some("还珠格格".to_string()));
+        assert_eq!(ep.season, Some(1));
+        assert_eq!(ep.episode, Some(2));
+        assert_eq!(ep.episode_end, Some(3));
+        assert_eq!(ep.year, Some(1998));
+        assert_eq!(ep.container, Some("mkv".to_string()));
+        assert_eq!(ep.air_date, None);
+        assert_eq!(ep.confidence, 0.7);
+
+        // self 中已有的值优先
+        let ep = numbers_only.merge(title_only);
+        assert_eq!(ep.title, Some("S01".to_string()));
+        assert_eq!(ep.year, Some(1998));
+    }
+
+    #[test]
+    fn test_match_windows_path() {
+        let ep = Episode::from_path(r"D:\影片\还珠格格S01E02.mp4").unwrap().unwrap();
+        assert_eq!(ep.title, Some("还珠格格".to_string()));
+        assert_eq!(ep.season, Some(1));
+        assert_eq!(ep.episode, Some(2));
+
+        let ep = Episode::from_path(r"D:\影片\电视剧\医馆笑传\医馆笑传S01.37集.1080P\01.mp4").unwrap().unwrap();
+        assert_eq!(ep.title, Some("医馆笑传".to_string()));
+        assert_eq!(ep.episode, Some(1));
+
+        let matches = Episode::all_matches(r"D:\综艺\向往的生活第20210115期.mkv").unwrap();
+        assert_eq!(matches[0].air_date, NaiveDate::from_ymd_opt(2021, 1, 15));
+    }
+
+    #[test]
+    fn test_match_without_extension() {
+        let ep = Episode::from_path("/Show/Show.S01E05").unwrap().unwrap();
+        assert_eq!(ep.title, Some("Show".to_string()));
+        assert_eq!(ep.season, Some(1));
+        assert_eq!(ep.episode, Some(5));
+        assert_eq!(ep.container, None);
+
+        let ep = Episode::from_path("/Show/Show.S01E05.MKV").unwrap().unwrap();
+        assert_eq!(ep.episode, Some(5));
+        assert_eq!(ep.container, Some("mkv".to_string()));
+
+        let ep = Episode::from_path("/Volumes/ZhiTai/影片/电视剧/怪侠一枝梅.全30集.4K/02").unwrap().unwrap();
+        assert_eq!(ep.title, Some("怪侠一枝梅".to_string()));
+        assert_eq!(ep.episode, Some(2));
+        assert_eq!(ep.container, None);
+    }
+
+    #[test]
+    fn test_match_pattern3() {
+        let path = "/Volumes/ZhiTai/影片/电视剧/爱情公寓/S2 (2011) 4K/02.mp4";
+        let item = Episode::from_path(path).unwrap();
+        assert!(item.is_some());
+        if let Some(ep) = item {
+            assert_eq!(ep.title, Some("爱情公寓".to_string()));
+            assert_eq!(ep.season, Some(2));
+            assert_eq!(ep.episode, Some(2));
+            assert_eq!(ep.year, Some(2011));
+        }
+
+    }
+    #[test]
+    fn test_match_pattern4() {
+        let path = "/Volumes/ZhiTai/影片/电视剧/龙门镖局/龙门镖局 (2013) 4K/龙门镖局.Longmen.Express.2013.E02.4K.2160p.HEVC.AAC-DHTCLUB.mp4";
+        let item = Episode::from_path(path).unwrap();
+        assert!(item.is_some());
+        if let Some(ep) = item {
+            assert_eq!(ep.title, Some("龙门镖局".to_string()));
+            assert_eq!(ep.season, Some(1)); // 默认季数为1
+            assert_eq!(ep.episode, Some(2));
+            assert_eq!(ep.year, Some(2013));
+        }
+    }
+
+    #[test]
+    fn test_match_pattern5() {
+        let path = "/Volumes/Getea/影/影/渤/疯狂的赛车.2009.01201.mp4";
+        let item = Episode::from_path(path).unwrap();
+        assert!(item.is_some());
+        if let Some(ep) = item {
+            assert_eq!(ep.title, Some("疯狂的赛车".to_string()));
+            assert_eq!(ep.season, Some(2009));
+            assert_eq!(ep.episode, Some(1201));
+        }
+    }
+
+    #[test]
+    fn test_match_pattern6() {
+        let path = "/Volumes/ZhiTai/影片/电视剧/约会专家.1080P/约会专家第04集.mp4";
+        let item = Episode::from_path(path).unwrap();
+        assert!(item.is_some());
+        if let Some(ep) = item {
+            assert_eq!(ep.title, Some("约会专家".to_string()));
+            assert_eq!(ep.season, Some(1));
+            assert_eq!(ep.episode, Some(4));
+        }
+    }
+
+    #[test]
+    fn test_match_pattern7() {
+        let path = "/Volumes/ZhiTai/影片/电视剧/怪侠一枝梅.全30集.4K/02.mp4";
+        let item = Episode::from_path(path).unwrap();
+        assert!(item.is_some());
+        if let Some(ep) = item {
+            assert_eq!(ep.title, Some("怪侠一枝梅".to_string()));
+            assert_eq!(ep.season, Some(1)); // 默认季数为1
+            assert_eq!(ep.episode, Some(2));
+        }
+    }
+
+    #[test]
+    fn test_named_captures() {
+        // 命名分组与位置无关
+        let parser = RegexParser::new(
+            r"^(.*?)/(?P<title>[^/]+)/第(?P<season>\d+)季/(?P<episode>\d+)\.(\w+)$",
+            vec![9, 9, 9],
+        );
+        let ep = parser.parse("/影片/武林外传/第2季/05.mp4").unwrap();
+        assert_eq!(ep.title, Some("武林外传".to_string()));
+        assert_eq!(ep.season, Some(2));
+        assert_eq!(ep.episode, Some(5));
+
+        // 没有 season 命名分组时按索引取值，索引为 0 时默认第 1 季
+        let parser = RegexParser::new(r"^(.*?)/(?P<title>[^/]+)/(\d+)\.(\w+)$", vec![0, 0, 3]);
+        let ep = parser.parse("/影片/武林外传/05.mp4").unwrap();
+        assert_eq!(ep.title, Some("武林外传".to_string()));
+        assert_eq!(ep.season, Some(1));
+        assert_eq!(ep.episode, Some(5));
+    }
+
+    #[test]
+    fn test_parser_registry() {
+        let mut registry = ParserRegistry::new();
+        let path = "/Volumes/影片/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4";
+        assert_eq!(registry.parse(path).unwrap().unwrap().episode, Some(2));
+
+        // 优先级高于内置解析器的闭包先被尝试
+        registry.register_fn(10, |path| {
+            path.contains("还珠格格").then(|| Episode { title: Some("覆盖".to_string()), ..Default::default() })
+        });
+        let ep = registry.parse(path).unwrap().unwrap();
+        assert_eq!(ep.title, Some("覆盖".to_string()));
+        assert_eq!(ep.source_path, Some(PathBuf::from(path)));
+
+        // 覆盖正则解析器自身的优先级
+        let mut registry = ParserRegistry::new();
+        registry
+            .register(RegexParser::new(r"/(还珠格格)S(\d{2})E(\d{2})\.mp4$", vec![1, 0, 3]).with_priority(-1))
+            .register_with_priority(RegexParser::new(r"/(还珠)格格S(\d{2})E(\d{2})\.mp4$", vec![1, 2, 3]), 5);
+        assert_eq!(registry.parse(path).unwrap().unwrap().title, Some("还珠".to_string()));
+
+        // 禁用内置解析器后只剩下优先级为 -1 的解析器
+        let fallback = RegexParser::new(r"/(还珠格格)S(\d{2})E(\d{2})\.mp4$", vec![1, 0, 3])
+            .with_default_season(Some(9))
+            .with_priority(-1);
+        let mut registry = ParserRegistry::new().with(fallback);
+        assert_eq!(registry.parse(path).unwrap().unwrap().season, Some(1));
+        registry.disable_builtins();
+        assert_eq!(registry.parse(path).unwrap().unwrap().season, Some(9));
+        registry.enable_builtin(1).enable_builtin(100);
+        assert_eq!(registry.parse(path).unwrap().unwrap().season, Some(1));
+
+        assert!(ParserRegistry::new().disable_builtins().parse(path).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_to_path() {
+        let ep = Episode {
+            title: Some("AC/DC".to_string()),
+            season: Some(0),
+            episode: Some(1),
+            episode_end: Some(2),
+            year: Some(2021),
+            container: Some("mp4".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ep.to_path("/tv", Layout::Plex), Some(PathBuf::from("/tv/AC_DC (2021)/Season 00/AC_DC - S00E01-E02.mp4")));
+        assert_eq!(ep.to_path("/tv", Layout::Jellyfin), Some(PathBuf::from("/tv/AC_DC (2021)/Season 00/AC_DC S00E01-E02.mp4")));
+        assert_eq!(ep.to_path("/tv", Layout::Kodi), Some(PathBuf::from("/tv/AC_DC (2021)/Season 0/AC_DC S00E01-E02.mp4")));
+        assert_eq!(ep.to_path("/tv", Layout::Flat), Some(PathBuf::from("/tv/AC_DC.S00E01-E02.mp4")));
+
+        // 按播出日期分期的综艺
+        let ep = Episode::from_path("/综艺/向往的生活第20210115期.mkv").unwrap().unwrap();
+        assert_eq!(ep.to_path("/tv", Layout::Plex), Some(PathBuf::from("/tv/向往的生活/Season 2021/向往的生活 - 2021-01-15.mkv")));
+
+        // 各布局生成的路径都能解析回相同的剧集
+        for path in [
+            "/Volumes/影片/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E01-E02.mp4",
+            "/Volumes/ZhiTai/影片/电视剧/爱情公寓/S4 (2014) 4K/01.mp4",
+        ] {
+            let ep = Episode::from_path(path).unwrap().unwrap();
+            for layout in [Layout::Plex, Layout::Jellyfin, Layout::Kodi, Layout::Flat] {
+                let target = ep.to_path("/tv", layout).unwrap();
+                let parsed = Episode::from_path(&target).unwrap().unwrap();
+                assert_eq!(parsed.title, ep.title, "{target:?}");
+                assert_eq!((parsed.season, parsed.episode, parsed.episode_end), (ep.season, ep.episode, ep.episode_end), "{target:?}");
+            }
+        }
+
+        assert_eq!(Episode { title: Some("还珠格格".to_string()), ..Default::default() }.to_path("/tv", Layout::Plex), None);
+        assert_eq!(Episode { episode: Some(1), ..Default::default() }.to_path("/tv", Layout::Kodi), None);
+    }
+
+    #[test]
+    fn test_chinese_numerals() {
+        let ep = Episode::from_path("/影片/电视剧/约会专家/约会专家第十二集.mp4").unwrap().unwrap();
+        assert_eq!(ep.title, Some("约会专家".to_string()));
+        assert_eq!(ep.season, Some(1));
+        assert_eq!(ep.episode, Some(12));
+
+        for (path, season, episode) in [
+            ("/影片/电视剧/武林外传/第二季/05.mp4", 2, 5),
+            ("/影片/电视剧/武林外传第二季/第十二集.mp4", 2, 12),
+            ("/影片/电视剧/武林外传/第3季/第一百零五集.mkv", 3, 105),
+        ] {
+            let ep = Episode::from_path(path).unwrap().unwrap();
+            assert_eq!(ep.title, Some("武林外传".to_string()), "{path}");
+            assert_eq!((ep.season, ep.episode), (Some(season), Some(episode)), "{path}");
+        }
+
+        let ep = Episode::from_filename("约会专家 第二十话.mkv").unwrap();
+        assert_eq!(ep.episode, Some(20));
+
+        // 自定义的正则解析器同样支持，严格模式下中文数字不视为错误
+        let parser = RegexParser::named(r"/(?P<title>[^/]+)第(?P<episode>\w+)集\.\w+$");
+        assert_eq!(parser.try_parse("/约会专家第三十集.mp4").unwrap().unwrap().episode, Some(30));
+        assert!(parser.try_parse("/约会专家第三十三三集.mp4").is_err());
+        assert_eq!(parser.parse("/约会专家第三十三三集.mp4").unwrap().episode, None);
+    }
+
+    #[test]
+    fn test_fn_parser() {
+        let special = FnParser::new(|path: &str| {
+            path.ends_with("/special.mp4").then(|| Episode { title: Some("特别篇".to_string()), season: Some(0), ..Default::default() })
+        });
+        assert_eq!(special.priority(), 0);
+        assert!(special.parse("/影片/还珠格格S01E02.mp4").is_none());
+
+        // 与正则解析器一起放入 ChainParser，或按优先级与其他闭包一起使用
+        let chain = ChainParser::new()
+            .with(special)
+            .with(RegexParser::new(r"^(.*?)/([^/]+)S(\d{2})E(\d{2})\.(\w+)$", vec![2, 3, 4]));
+        assert_eq!(chain.parse("/影片/special.mp4").unwrap().season, Some(0));
+        assert_eq!(chain.parse("/影片/还珠格格S01E02.mp4").unwrap().episode, Some(2));
+
+        let low = FnParser::new(|_: &str| Some(Episode { title: Some("low".to_string()), ..Default::default() }));
+        let high = FnParser::new(|_: &str| Some(Episode { title: Some("high".to_string()), ..Default::default() })).with_priority(1);
+        let ep = Episode::from_path_with_regex("/影片/01.mp4", vec![&low as &dyn Parser, &high]).unwrap().unwrap();
+        assert_eq!(ep.title, Some("high".to_string()));
+    }
+
+    #[test]
+    fn test_chain_parser() {
+        // 自定义解析器与正则解析器混用
+        struct FixedParser;
+        impl Parser for FixedParser {
+            fn parse(&self, path: &str) -> Option<Episode> {
+                path.ends_with("/special.mp4").then(|| Episode {
+                    title: Some("特别篇".to_string()),
+                    season: Some(0),
+                    episode: Some(1),
+                    ..Default::default()
+                })
+            }
+        }
+
+        let parsers: Vec<Box<dyn Parser>> = vec![
+            Box::new(FixedParser),
+            Box::new(RegexParser::new(r"^(.*?)/([^/]+)S(\d{2})E(\d{2})\.(\w+)$", vec![2, 3, 4])),
+        ];
+        let chain = ChainParser::from(parsers);
+
+        let ep = chain.parse("/影片/还珠格格/special.mp4").unwrap();
+        assert_eq!(ep.title, Some("特别篇".to_string()));
+        assert_eq!(ep.season, Some(0));
+
+        let ep = chain.parse("/影片/还珠格格/还珠格格S01E02.mp4").unwrap();
+        assert_eq!(ep.title, Some("还珠格格".to_string()));
+        assert_eq!(ep.episode, Some(2));
+
+        assert!(chain.parse("/影片/还珠格格/poster.jpg").is_none());
+    }
+
+    #[test]
+    fn test_template_parser() {
+        let parser = TemplateParser::new("{title}S{season:2}E{episode:2}-E{episode_end:2}.{ext}").unwrap();
+        let ep = parser.parse("/还珠格格S01/还珠格格S01E01-E02.mp4").unwrap();
+        assert_eq!(ep.title, Some("还珠格格".to_string()));
+        assert_eq!(ep.episode, Some(1));
+        assert_eq!(ep.episode_end, Some(2));
+        assert_eq!(ep.container, Some("mp4".to_string()));
+
+        // 宽度限制
+        assert!(parser.parse("/还珠格格S01/还珠格格S1E01-E02.mp4").is_none());
+
+        let parser = TemplateParser::new("{title} ({year}) {*}/{episode}.{ext}").unwrap();
+        let ep = parser.parse("/电视剧/龙门镖局 (2013) 4K/02.mp4").unwrap();
+        assert_eq!(ep.title, Some("龙门镖局".to_string()));
+        assert_eq!(ep.year, Some(2013));
+        assert_eq!(ep.episode, Some(2));
+        assert_eq!(ep.season, Some(1));
+
+        let parser = TemplateParser::new("{title}第{air_date}期.{ext}").unwrap();
+        let ep = parser.parse("/综艺/向往的生活第20210115期.mkv").unwrap();
+        assert_eq!(ep.air_date, NaiveDate::from_ymd_opt(2021, 1, 15));
+
+        // 正则特殊字符按字面匹配
+        let parser = TemplateParser::new("[{title}] {episode}.{ext}").unwrap();
+        assert_eq!(parser.parse("/动画/[进击的巨人] 05.mkv").unwrap().episode, Some(5));
+
+        assert!(TemplateParser::new("{title").is_err());
+        assert!(TemplateParser::new("{name}.{ext}").is_err());
+        assert!(TemplateParser::new("{season:x}").is_err());
+        assert!(TemplateParser::new("{title:2}").is_err());
+    }
+
+    #[test]
+    fn test_scan_dir() {
+        let tmp = TempDir::new("lazytool-scan-dir-").unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("还珠格格S01")).unwrap();
+        fs::create_dir_all(dir.join(".trash")).unwrap();
+        fs::write(dir.join("还珠格格S01/还珠格格S01E02.mp4"), "").unwrap();
+        fs::write(dir.join("还珠格格S01/还珠格格S01E01.MKV"), "").unwrap();
+        fs::write(dir.join("还珠格格S01/还珠格格S01E01.srt"), "").unwrap();
+        fs::write(dir.join("还珠格格S01/trailer.mp4"), "").unwrap();
+        fs::write(dir.join(".trash/还珠格格S01E03.mp4"), "").unwrap();
+
+        let episodes = Episode::scan_dir(dir).unwrap();
+        let numbers: Vec<_> = episodes.iter().map(|ep| ep.episode).collect();
+        assert_eq!(numbers, vec![Some(1), Some(2)]);
+
+        let mut numbers: Vec<_> = Episode::scan_dir_iter(dir).map(|ep| ep.unwrap().episode).collect();
+        numbers.sort();
+        assert_eq!(numbers, vec![Some(1), Some(2)]);
+
+        assert!(Episode::scan_dir(dir.join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_directory_parser() {
+        let ep = DirectoryParser.parse("/影片/神探狄仁杰/Season 2/Episode 05/video.mp4").unwrap();
+        assert_eq!(ep.title, Some("神探狄仁杰".to_string()));
+        assert_eq!(ep.season, Some(2));
+        assert_eq!(ep.episode, Some(5));
+        assert_eq!(ep.container, Some("mp4".to_string()));
+        assert_eq!(ep.confidence, 1.0);
+
+        // 集数在文件名中
+        let ep = DirectoryParser.parse("/Shows/The Office/Season 03/Episode 12.mkv").unwrap();
+        assert_eq!(ep.title, Some("The Office".to_string()));
+        assert_eq!(ep.season, Some(3));
+        assert_eq!(ep.episode, Some(12));
+
+        let ep = DirectoryParser.parse("/影片/武林外传/第2季/第05集/video.mp4").unwrap();
+        assert_eq!(ep.title, Some("武林外传".to_string()));
+        assert_eq!(ep.season, Some(2));
+        assert_eq!(ep.episode, Some(5));
+
+        // 没有季数时默认为第一季
+        let ep = DirectoryParser.parse("/影片/大明王朝1566/EP 07/video.mp4").unwrap();
+        assert_eq!(ep.title, Some("大明王朝1566".to_string()));
+        assert_eq!(ep.season, Some(1));
+        assert_eq!(ep.episode, Some(7));
+
+        // 没有剧名
+        let ep = DirectoryParser.parse("Season 1/Episode 2/video.mp4").unwrap();
+        assert_eq!(ep.title, None);
+
+        assert!(DirectoryParser.parse("/影片/神探狄仁杰/Season 2/video.mp4").is_none());
+        assert!(DirectoryParser.parse("").is_none());
+    }
+
+    #[test]
+    fn test_regex_parser_missing_groups() {
+        // 索引超出分组数
+        let parser = RegexParser::new(r"/([^/]+)S(\d{2})E(\d{2})\.\w+$", vec![1, 2, 9]);
+        assert!(parser.parse("/还珠格格/还珠格格S01E02.mp4").is_none());
+
+        // 可选分组没有参与匹配
+        let parser = RegexParser::new(r"/([^/]+?)(?:S(\d{2}))?E(\d{2})\.\w+$", vec![1, 2, 3]);
+        assert!(parser.parse("/还珠格格/还珠格格E02.mp4").is_none());
+        assert_eq!(parser.parse("/还珠格格/还珠格格S01E02.mp4").unwrap().episode, Some(2));
+    }
+
+    #[test]
+    fn test_parse_never_panics() {
+        // 简单的伪随机数生成器，避免引入依赖
+        let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        let alphabet: Vec<char> = "/.-_ ()[]【】（）SEsex0123456789０９Ｓ第季集期全K P年月日\\:*?\u{0}\u{7f}é还珠格格\u{1F600}"
+            .chars()
+            .collect();
+
+        // 解析器只构建一次，复用编译好的正则
+        let mut chain = ChainParser::new()
+            .with(TemplateParser::new("{title}.S{season:2}E{episode:2}.{ext}").unwrap())
+            .with(TemplateParser::new("{title}/{*}/{episode}-{episode_end}").unwrap())
+            .with(RegexParser::new(Episode::PARSERS[3].0, vec![9, 8, 7]).with_normalize_width(true))
+            .with(DirectoryParser);
+        for (pattern, indexes) in Episode::PARSERS {
+            chain.push(RegexParser::new(pattern, indexes.to_vec()).with_normalize_width(true));
+        }
+
+        for i in 0..5000 {
+            let len = (next() % 64) as usize;
+            let path: String = (0..len)
+                .map(|_| alphabet[(next() % alphabet.len() as u64) as usize])
+                .collect();
+            chain.parse(&path);
+            super::clean_title(&path);
+            if i % 50 == 0 {
+                let _ = Episode::from_path(&path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalize_width() {
+        let parsers = || {
+            Episode::PARSERS.iter()
+                .map(|(pattern, indexes)| RegexParser::new(pattern, indexes.to_vec()).with_normalize_width(true))
+                .collect::<Vec<_>>()
+        };
+
+        let path = "/Volumes/ZhiTai/影片/电视剧/约会专家.1080P/约会专家第０４集.mp4";
+        let ep = Episode::from_path_with_regex(path, parsers()).unwrap().unwrap();
+        assert_eq!(ep.title, Some("约会专家".to_string()));
+        assert_eq!(ep.episode, Some(4));
+        // 内置解析器默认转为半角，不会匹配后丢弃集数
+        let ep = Episode::from_path(path).unwrap().unwrap();
+        assert_eq!(ep.episode, Some(4));
+        let ep = Episode::from_filename("约会专家第０４集.mp4").unwrap();
+        assert_eq!(ep.episode, Some(4));
+
+        let path = "/Volumes/还珠格格S01.国语中字.无台标.1080P/还珠格格Ｓ０１Ｅ０２.mp4";
+        let ep = Episode::from_path_with_regex(path, parsers()).unwrap().unwrap();
+        assert_eq!(ep.title, Some("还珠格格".to_string()));
+        assert_eq!(ep.season, Some(1));
+        assert_eq!(ep.episode, Some(2));
+        let ep = Episode::from_path(path).unwrap().unwrap();
+        assert_eq!((ep.season, ep.episode), (Some(1), Some(2)));
+    }
+
+    #[test]
+    fn test_confidence() {
+        let path = "/Volumes/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4";
+        let ep = Episode::from_path(path).unwrap().unwrap();
+        assert_eq!(ep.confidence, 1.0);
+
+        // 默认季数的匹配可信度更低
+        let path = "/Volumes/ZhiTai/影片/电视剧/约会专家.1080P/约会专家第04集.mp4";
+        let ep = Episode::from_path(path).unwrap().unwrap();
+        assert_eq!(ep.confidence, 0.85);
+
+        // 多个模式同时匹配时按可信度排序
+        let parsers = vec![
+            RegexParser::new(r"^(.*?)/([^/]+)/([^/]+)\.(\w+)$", vec![2, 0, 0]),
+            RegexParser::new(r"^(.*?)/([^/]+)第(\d+)集\.(\w+)$", vec![2, 0, 3]),
+            RegexParser::new(r"^(.*?)/([^/]+)S(\d{2})E(\d{2})\.(\w+)$", vec![2, 3, 4]),
+        ];
+        let matches = Episode::all_matches_with_regex("/影片/还珠格格/还珠格格S01E02.mp4", parsers).unwrap();
+        let scores: Vec<f32> = matches.iter().map(|ep| ep.confidence).collect();
+        assert_eq!(scores, vec![1.0, 0.45]);
+        assert_eq!(matches[0].episode, Some(2));
+    }
+
+    #[test]
+    fn test_clean_title() {
+        let parser = RegexParser::new(r"^(.*?)/([^/]+)\.S(\d{2})E(\d{2})[^/]*$", vec![2, 3, 4]);
+        let ep = parser.parse("/TV/Show.Name.2023.S02E10.REPACK.1080p.mkv").unwrap();
+        assert_eq!(ep.title, Some("Show Name".to_string()));
+        assert_eq!(ep.season, Some(2));
+        assert_eq!(ep.episode, Some(10));
+
+        let parser = RegexParser::new(r"^(.*?)/([^/]+)\.E(\d{2})\.", vec![2, 0, 3]);
+        let ep = parser.parse("/龙门镖局/龙门镖局.Longmen.Express.2013.E02.4K.2160p.HEVC.AAC-DHTCLUB.mp4").unwrap();
+        assert_eq!(ep.title, Some("龙门镖局".to_string()));
+        assert_eq!(ep.episode, Some(2));
+    }
+
+    #[test]
+    fn test_match_pattern8() {
+        let item = Episode::from_path("向往的生活第20210115期.mkv").unwrap();
         assert!(item.is_some());
         if let Some(ep) = item {
-            assert_eq!(ep.title, Some("怪侠一枝梅".to_string()));
-            assert_eq!(ep.season, Some(1)); // 默认季数为1
-            assert_eq!(ep.episode, Some(2));
+            assert_eq!(ep.title, Some("向往的生活".to_string()));
+            assert_eq!(ep.episode, None);
+            assert_eq!(ep.air_date, NaiveDate::from_ymd_opt(2021, 1, 15));
+        }
+
+        let path = "/Volumes/ZhiTai/影片/综艺/向往的生活/向往的生活第20210115期.mkv";
+        let ep = Episode::from_path(path).unwrap().unwrap();
+        assert_eq!(ep.title, Some("向往的生活".to_string()));
+        assert_eq!(ep.air_date, NaiveDate::from_ymd_opt(2021, 1, 15));
+    }
+
+    #[test]
+    fn test_from_path_with_index() {
+        let tmp = TempDir::new("lazytool-index-").unwrap();
+        let dir = tmp.child("医馆笑传S01.37集.1080P");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("01.mp4");
+        fs::write(&path, "").unwrap();
+
+        // 未写索引时按正则解析
+        let ep = Episode::from_path_with_index(&path).unwrap().unwrap();
+        assert_eq!(ep.season, Some(1));
+        assert_eq!(ep.episode, Some(1));
+
+        fs::write(
+            dir.join(Episode::INDEX_FILENAME),
+            r#"{"01.mp4": {"season": 2, "episode": 5}, "02.mp4": {"title": "医馆笑传番外"}}"#,
+        ).unwrap();
+        let ep = Episode::from_path_with_index(&path).unwrap().unwrap();
+        assert_eq!(ep.title, Some("医馆笑传".to_string()));
+        assert_eq!(ep.season, Some(2));
+        assert_eq!(ep.episode, Some(5));
+
+        let ep = Episode::from_path_with_index(dir.join("02.mp4")).unwrap().unwrap();
+        assert_eq!(ep.title, Some("医馆笑传番外".to_string()));
+        assert_eq!(ep.season, Some(1));
+        assert_eq!(ep.episode, Some(2));
+
+        // 索引中没有的文件回退到正则解析
+        let ep = Episode::from_path_with_index(dir.join("03.mp4")).unwrap().unwrap();
+        assert_eq!(ep.episode, Some(3));
+
+        // 正则无法解析的文件只要在索引中就能识别
+        fs::write(
+            dir.join(Episode::INDEX_FILENAME),
+            r#"{"花絮.mp4": {"title": "医馆笑传", "season": 0, "episode": 1}}"#,
+        ).unwrap();
+        assert!(Episode::from_path(dir.join("花絮.mp4")).unwrap().is_none());
+        let ep = Episode::from_path_with_index(dir.join("花絮.mp4")).unwrap().unwrap();
+        assert_eq!((ep.title.as_deref(), ep.season, ep.episode), (Some("医馆笑传"), Some(0), Some(1)));
+        assert_eq!(ep.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_classify_extension() {
+        assert_eq!(classify_extension("mp4"), MediaKind::Video);
+        assert_eq!(classify_extension("RMVB"), MediaKind::Video);
+        assert_eq!(classify_extension("Ass"), MediaKind::Subtitle);
+        assert_eq!(classify_extension(".vtt"), MediaKind::Subtitle);
+        assert_eq!(classify_extension("flac"), MediaKind::Audio);
+        assert_eq!(classify_extension("NFO"), MediaKind::Metadata);
+        assert_eq!(classify_extension(""), MediaKind::Unknown);
+        assert_eq!(classify_extension("S01E05"), MediaKind::Unknown);
+    }
+
+    #[test]
+    fn test_find_subtitles() {
+        let tmp = TempDir::new("lazytool-subtitles-").unwrap();
+        let dir = tmp.path();
+        for name in [
+            "还珠格格S01E02.mp4",
+            "还珠格格S01E02.srt",
+            "还珠格格S01E02.zh.srt",
+            "Huanzhugege.s01e02.en.ass",
+            "还珠格格S01E03.srt",
+            "还珠格格S01E02.nfo",
+            "还珠格格S01E020.vtt",
+            "Show Name - 1x05 - Pilot.mkv",
+            "Show.Name.S01E05.en.srt",
+            "Show.Name.S01E05E06.srt",
+            "Other.Show.1x05.srt",
+        ] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        // 字幕与视频使用不同的命名方式，按解析出的剧集匹配
+        let subtitles = find_subtitles(dir.join("Show Name - 1x05 - Pilot.mkv")).unwrap();
+        assert_eq!(subtitles, vec![dir.join("Show.Name.S01E05.en.srt")]);
+
+        let subtitles = find_subtitles(dir.join("还珠格格S01E02.mp4")).unwrap();
+        let names: Vec<_> = subtitles.iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, vec![
+            "Huanzhugege.s01e02.en.ass",
+            "还珠格格S01E02.srt",
+            "还珠格格S01E02.zh.srt",
+        ]);
+    }
+
+    #[test]
+    fn test_parse_logging() {
+        use std::sync::Mutex;
+
+        static RECORDS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+        struct CaptureLogger;
+        impl log::Log for CaptureLogger {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+            fn log(&self, record: &log::Record) {
+                RECORDS.lock().unwrap().push((record.level(), record.args().to_string()));
+            }
+            fn flush(&self) {}
+        }
+        let _ = log::set_logger(&CaptureLogger);
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let path = "/logging/还珠格格S01E02.mp4";
+        let parsers = vec![
+            RegexParser::new(r"^/logging/(\d+)\.mp4$", vec![0, 0, 1]),
+            RegexParser::new(r"^(.*?)/([^/]+)S(\d{2})E(\d{2})\.(\w+)$", vec![2, 3, 4]),
+        ];
+        Episode::from_path_with_regex(path, parsers).unwrap().unwrap();
+        Episode::from_path_with_regex("/logging/readme.txt", vec![RegexParser::new("(", vec![])]).unwrap();
+
+        // 其他测试并行运行时也会输出记录，只检查与本测试路径相关的记录
+        let records: Vec<_> = RECORDS.lock().unwrap().iter()
+            .filter(|(_, msg)| msg.contains("/logging/") || msg.contains("invalid pattern \"(\""))
+            .cloned()
+            .collect();
+        let has = |level: log::Level, text: &str| records.iter().any(|(l, msg)| *l == level && msg.contains(text));
+        assert!(has(log::Level::Trace, r#"did not match "/logging/还珠格格S01E02.mp4""#));
+        assert!(has(log::Level::Debug, "season: Some(1), episode: Some(2)"));
+        assert!(has(log::Level::Debug, r#"parser #1 matched "/logging/还珠格格S01E02.mp4""#));
+        assert!(has(log::Level::Debug, "invalid pattern"));
+        assert!(has(log::Level::Debug, r#"no parser matched "/logging/readme.txt""#));
+    }
+
+    #[test]
+    fn test_error_variants() {
+        assert!(matches!(TemplateParser::new("{title}.S{season"), Err(Error::InvalidPattern { .. })));
+        assert!(matches!(TemplateParser::new("{name}.{ext}"), Err(Error::InvalidPattern { reason, .. }) if reason.contains("{name}")));
+        assert!(matches!(RegexParser::try_new("(", vec![]), Err(Error::PatternError { .. })));
+
+        let tmp = TempDir::new("lazytool-media-errors-").unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join(Episode::INDEX_FILENAME), "{").unwrap();
+        match Episode::from_path_with_index(dir.join("01.mp4")) {
+            Err(Error::Json { path, .. }) => assert_eq!(path, Some(dir.join(Episode::INDEX_FILENAME))),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert_eq!(Episode::scan_dir(dir.join("missing")).unwrap_err().io_kind(), Some(std::io::ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn test_parser_priority() {
+        let path = "/剧集/还珠格格/HZGG.S01E02.mp4";
+        let ep = Episode::from_path(path).unwrap().unwrap();
+        assert_ne!(ep.title, Some("还珠格格".to_string()));
+
+        // 追加在内置解析器之后，依靠优先级先于内置解析器尝试
+        let custom: RegexParser = serde_json::from_str(
+            r#"{"pattern": "/(?P<title>[^/]+)/[^/]+S(?P<season>\\d{2})E(?P<episode>\\d{2})\\.\\w+$", "priority": 10}"#,
+        ).unwrap();
+        assert_eq!(custom.priority(), 10);
+        let mut parsers: Vec<&RegexParser> = Episode::builtin_parsers().iter().collect();
+        parsers.push(&custom);
+        let ep = Episode::from_path_with_regex(path, parsers).unwrap().unwrap();
+        assert_eq!(ep.title, Some("还珠格格".to_string()));
+        assert_eq!(ep.episode, Some(2));
+
+        // 优先级相同时保持原有顺序
+        let parsers = vec![
+            RegexParser::named(r"/(?P<title>[^/]+)/[^/]+S(?P<season>\d{2})E(?P<episode>\d{2})\.\w+$").with_priority(-1),
+            RegexParser::named(r"/(?P<title>HZGG)\.S(?P<season>\d{2})E(?P<episode>\d{2})\.\w+$"),
+            RegexParser::named(r"/(?P<title>HZ)GG\.S(?P<season>\d{2})E(?P<episode>\d{2})\.\w+$"),
+        ];
+        let ep = Episode::from_path_with_regex(path, parsers).unwrap().unwrap();
+        assert_eq!(ep.title, Some("HZGG".to_string()));
+    }
+
+    #[test]
+    fn test_guess_resolution() {
+        // `4K` 与 `2160p` 等价
+        assert_eq!(guess_resolution("Show.4K.mkv"), guess_resolution("Show.2160p.mkv"));
+        assert_eq!(guess_resolution("怪侠一枝梅.全30集.4k/02.mp4"), Some(Resolution::UHD4K));
+        assert_eq!(guess_resolution("Show.UHD.BluRay.mkv"), Some(Resolution::UHD4K));
+        assert_eq!(guess_resolution("Show.8K.mkv"), Some(Resolution::UHD8K));
+        assert_eq!(guess_resolution("Show.4320p.mkv"), Some(Resolution::UHD8K));
+        assert_eq!(guess_resolution("Show.1080i.ts"), Some(Resolution::HD1080));
+        assert_eq!(guess_resolution("Show.720P.mkv"), Some(Resolution::HD720));
+        assert_eq!(guess_resolution("Show.576p.mkv"), Some(Resolution::SD));
+        assert_eq!(guess_resolution("Show.SD.mkv"), Some(Resolution::SD));
+        assert_eq!(guess_resolution("Show 3840x2160.mkv"), Some(Resolution::UHD4K));
+        assert_eq!(guess_resolution("1080p"), Some(Resolution::HD1080));
+
+        // 嵌在其他内容中的标记不识别
+        assert_eq!(guess_resolution("Show.S04K.mkv"), None);
+        assert_eq!(guess_resolution("Show.x265.10bit.mkv"), None);
+        assert_eq!(guess_resolution("Show.Sdk.mkv"), None);
+        assert_eq!(guess_resolution("Show.100p.mkv"), None);
+
+        assert_eq!(Resolution::UHD4K.height(), 2160);
+        let mut list = vec![Resolution::UHD4K, Resolution::SD, Resolution::HD1080];
+        list.sort();
+        assert_eq!(list, vec![Resolution::SD, Resolution::HD1080, Resolution::UHD4K]);
+    }
+
+    #[test]
+    fn test_from_filename() {
+        // 这些文件名不带目录时 from_path 无法解析
+        for name in ["还珠格格S01E02.mp4", "疯狂的赛车.2009.01201.mp4", "还珠格格S01E01-E02.mp4"] {
+            assert!(Episode::from_path(name).unwrap().is_none(), "{name}");
+            assert!(Episode::from_filename(name).is_some(), "{name}");
+        }
+        assert!(Episode::from_filename("向往的生活第20210115期.mkv").is_some());
+
+        let ep = Episode::from_filename("疯狂的赛车.2009.01201.mp4").unwrap();
+        assert_eq!(ep.title, Some("疯狂的赛车".to_string()));
+        assert_eq!(ep.season, Some(2009));
+        assert_eq!(ep.episode, Some(1201));
+
+        let ep = Episode::from_filename("还珠格格S01E01-E02.mp4").unwrap();
+        assert_eq!(ep.episode, Some(1));
+        assert_eq!(ep.episode_end, Some(2));
+        assert_eq!(ep.container, Some("mp4".to_string()));
+
+        // 带目录时只使用文件名，Windows 分隔符同样适用
+        let ep = Episode::from_filename(r"D:\其他\还珠格格S02E03.mkv").unwrap();
+        assert_eq!(ep.title, Some("还珠格格".to_string()));
+        assert_eq!(ep.season, Some(2));
+
+        assert!(Episode::from_filename("").is_none());
+        assert!(Episode::from_filename("/").is_none());
+        assert!(Episode::from_filename("README.md").is_none());
+
+        // 只针对文件名的模式
+        let ep = Episode::from_filename("[SweetSub] Frieren - 12v2 [WebRip 1080p HEVC-10bit].mkv").unwrap();
+        assert_eq!(ep.title, Some("Frieren".to_string()));
+        assert_eq!(ep.season, Some(1));
+        assert_eq!(ep.episode, Some(12));
+        assert_eq!(ep.resolution, Some(Resolution::HD1080));
+        let ep = Episode::from_filename("龙门镖局.Longmen.Express.2013.E02.4K.2160p.HEVC.AAC-DHTCLUB.mp4").unwrap();
+        assert_eq!(ep.year, Some(2013));
+        assert_eq!(ep.episode, Some(2));
+        let ep = Episode::from_filename("Show Name - EP05.mkv").unwrap();
+        assert_eq!(ep.title, Some("Show Name".to_string()));
+        assert_eq!(ep.episode, Some(5));
+        let ep = Episode::from_filename("约会专家 第04话.mkv").unwrap();
+        assert_eq!(ep.title, Some("约会专家".to_string()));
+        assert_eq!(ep.episode, Some(4));
+        // 文件名模式不会抢先匹配 PARSERS 能解析的文件名
+        let ep = Episode::from_filename("还珠格格S01E01-E02.mp4").unwrap();
+        assert_eq!(ep.season, Some(1));
+        let ep = Episode::from_filename("Show.Name.S02E10.1080p.mkv").unwrap();
+        assert_eq!(ep.season, Some(2));
+        assert_eq!(ep.episode, Some(10));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let paths = [
+            "/Volumes/影片/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E01-E02.mp4",
+            "/TV/Show.Name.2023.S02E10.REPACK.1080p.WEB-DL.x265.DDP5.1.mkv",
+            "/Volumes/ZhiTai/影片/综艺/向往的生活第20210115期.mkv",
+        ];
+        for path in paths {
+            let ep = Episode::from_path(path).unwrap().unwrap();
+            let json = ep.to_json().unwrap();
+            let cached = Episode::from_json(&json).unwrap();
+            // 重新序列化的结果一致，说明所有字段都被还原
+            assert_eq!(cached.to_json().unwrap(), json, "{path}");
+            assert_eq!(cached.source_path, Some(PathBuf::from(path)));
+        }
+
+        // 手动构造的剧集不输出空的可选字段
+        let json = Episode { title: Some("还珠格格".to_string()), ..Default::default() }.to_json().unwrap();
+        assert!(!json.contains("source_path"), "{json}");
+        assert!(!json.contains("resolution"), "{json}");
+
+        assert!(matches!(Episode::from_json(r#"{"episode":"two"}"#), Err(Error::Json { path: None, .. })));
+    }
+
+    #[test]
+    fn test_movie_from_path() {
+        let movie = Movie::from_path("/电影/Heat (1995) [2160p].mkv").unwrap().unwrap();
+        assert_eq!(movie.title, Some("Heat".to_string()));
+        assert_eq!(movie.year, Some(1995));
+        assert_eq!(movie.resolution, Some(Resolution::UHD4K));
+        assert_eq!(movie.edition, None);
+        assert_eq!(movie.source_path, Some(PathBuf::from("/电影/Heat (1995) [2160p].mkv")));
+
+        // 中文片名后的英文译名会被去掉，版本支持中文
+        let movie = Movie::from_path(r"D:\电影\让子弹飞.Let.the.Bullets.Fly.2010.导演剪辑版.mp4").unwrap().unwrap();
+        assert_eq!(movie.title, Some("让子弹飞".to_string()));
+        assert_eq!(movie.year, Some(2010));
+        assert_eq!(movie.edition, Some("导演剪辑版".to_string()));
+
+        // 片名本身是数字
+        let movie = Movie::from_path("/电影/2046.2004.mkv").unwrap().unwrap();
+        assert_eq!(movie.title, Some("2046".to_string()));
+        assert_eq!(movie.year, Some(2004));
+        let movie = Movie::from_path("/电影/Aliens.1986.Extended.Edition.mkv").unwrap().unwrap();
+        assert_eq!(movie.edition, Some("Extended Edition".to_string()));
+
+        // 没有年份或带有季集标记
+        assert!(Movie::from_path("/电影/疯狂的赛车.mp4").unwrap().is_none());
+        assert!(Movie::from_path("/电视剧/还珠格格S01E02.mp4").unwrap().is_none());
+        assert!(Movie::from_path("/").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_detect() {
+        let media = detect("/影片/电视剧/爱情公寓/S4 (2014) 4K/01.mp4").unwrap();
+        assert!(matches!(media, Some(Media::Episode(ref ep)) if ep.season == Some(4) && ep.episode == Some(1)));
+
+        // 剧集文件名中的年份不会被识别为电影
+        let media = detect("/TV/Show.Name.2023.S02E10.1080p.mkv").unwrap();
+        assert!(matches!(media, Some(Media::Episode(ref ep)) if ep.year == Some(2023)));
+
+        // 模式 5 不再把电影识别为剧集，from_path 保持不变
+        let path = "/影片/电影/黄渤/疯狂的赛车.2009.01201.mp4";
+        let Some(Media::Movie(movie)) = detect(path).unwrap() else { panic!("expected a movie") };
+        assert_eq!(movie.title, Some("疯狂的赛车".to_string()));
+        assert_eq!(movie.year, Some(2009));
+        assert_eq!(Episode::from_path(path).unwrap().unwrap().season, Some(2009));
+
+        let json = serde_json::to_string(&Media::Movie(movie)).unwrap();
+        assert!(json.contains(r#""kind":"movie""#), "{json}");
+        assert!(matches!(serde_json::from_str(&json).unwrap(), Media::Movie(_)));
+
+        assert!(detect("/影片/电影/疯狂的赛车.mp4").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_from_path_or_filename() {
+        let ep = Episode::from_path_or_filename("/下载/[字幕组] Show - 05.mp4").unwrap().unwrap();
+        assert_eq!(ep.episode, Some(5));
+        assert_eq!(ep.source_path, Some(PathBuf::from("/下载/[字幕组] Show - 05.mp4")));
+
+        let path = "/影片/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4";
+        let ep = Episode::from_path_or_filename(path).unwrap().unwrap();
+        assert_eq!(ep.title, Some("还珠格格".to_string()));
+        assert_eq!(ep.episode, Some(2));
+        assert_eq!(ep.source_path, Some(PathBuf::from(path)));
+        assert!(Episode::from_path_or_filename("/影片/README.md").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_season_zero() {
+        let ep = Episode::from_path("/还珠格格/还珠格格S00E01.mp4").unwrap().unwrap();
+        assert_eq!(ep.season, Some(0));
+        assert_eq!(ep.episode, Some(1));
+        assert_eq!(ep.confidence, 1.0);
+
+        let ep = Episode::from_path("/还珠格格S00.特别篇/还珠格格S00E01-E02.mkv").unwrap().unwrap();
+        assert_eq!(ep.season, Some(0));
+        assert_eq!(ep.episode_end, Some(2));
+
+        // 索引为 0 表示没有季数分组，使用默认季数
+        let ep = Episode::from_path("/Volumes/ZhiTai/影片/电视剧/约会专家.1080P/约会专家第04集.mp4").unwrap().unwrap();
+        assert_eq!(ep.season, Some(1));
+        let parser = RegexParser::new(Episode::PARSERS[5].0, Episode::PARSERS[5].1.to_vec()).with_default_season(None);
+        let ep = Episode::from_path_with_regex("/电视剧/约会专家.1080P/约会专家第04集.mp4", vec![parser]).unwrap().unwrap();
+        assert_eq!(ep.season, None);
+        assert_eq!(ep.episode, Some(4));
+
+        // 合并时显式的第 0 季不会被覆盖
+        let merged = Episode { season: None, ..ep }.merge(Episode::from_filename("还珠格格S00E04.mp4").unwrap());
+        assert_eq!(merged.season, Some(0));
+
+        let parser: RegexParser = serde_json::from_str(r#"{"pattern": "/(?P<title>[^/]+)第(?P<episode>\\d+)集", "default_season": null}"#).unwrap();
+        let ep = Episode::from_path_with_regex("/约会专家第04集.mp4", vec![parser]).unwrap().unwrap();
+        assert_eq!(ep.season, None);
+        let parser: RegexParser = serde_json::from_str(r#"{"pattern": "/(?P<title>[^/]+)第(?P<episode>\\d+)集"}"#).unwrap();
+        let ep = Episode::from_path_with_regex("/约会专家第04集.mp4", vec![parser]).unwrap().unwrap();
+        assert_eq!(ep.season, Some(1));
+
+        let parser = TemplateParser::new("{title}.S{season:2}E{episode:2}.{ext}").unwrap();
+        let ep = Episode::from_path_with_regex("/Show/Show.S00E03.mkv", vec![parser]).unwrap().unwrap();
+        assert_eq!(ep.season, Some(0));
+
+        for path in ["/Show/Specials/E01.mp4", "/Show/SP/01/E02.mkv", "/Show/特别篇/第3集.mp4", "/Show/S00/E01.mp4"] {
+            let ep = Episode::from_path_with_regex(path, vec![DirectoryParser]).unwrap().unwrap();
+            assert_eq!(ep.season, Some(0), "{path}");
+            assert_eq!(ep.title, Some("Show".to_string()), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_number_overflow() {
+        // 超出 u16::MAX 的集数不再被丢弃
+        let ep = Episode::from_path("/影片/电影/疯狂的赛车.2009.70000.mp4").unwrap().unwrap();
+        assert_eq!(ep.title, Some("疯狂的赛车".to_string()));
+        assert_eq!(ep.episode, Some(70000));
+        let ep = Episode::from_path("/影片/电影/疯狂的赛车.2009.01201.mp4").unwrap().unwrap();
+        assert_eq!(ep.episode, Some(1201));
+
+        // 超出 u32::MAX 时视为不匹配，交给后面的解析器
+        let parser = RegexParser::new(r"/([^/]+)\.E(\d+)\.mp4$", vec![1, 0, 2]);
+        assert!(parser.parse("/Show.E99999999999.mp4").is_none());
+        assert_eq!(parser.parse("/Show.E4294967295.mp4").unwrap().episode, Some(u32::MAX));
+        let fallback = RegexParser::new(r"/([^/]+)\.E\d+\.mp4$", vec![1]).with_priority(-1);
+        let ep = Episode::from_path_with_regex("/Show.E99999999999.mp4", vec![parser, fallback]).unwrap().unwrap();
+        assert_eq!(ep.title, Some("Show".to_string()));
+        assert_eq!(ep.episode, None);
+
+        let parser = RegexParser::new(r"/([^/]+)\.S(\d+)E(\d+)-E(\d+)\.mp4$", vec![1, 2, 3, 0, 0, 4]);
+        assert!(parser.parse("/Show.S01E01-E4294967296.mp4").is_none());
+        assert!(parser.parse("/Show.S4294967296E01-E02.mp4").is_none());
+    }
+
+    #[test]
+    fn test_try_parse() {
+        use super::{ChainParser, ParseError};
+
+        let parser = RegexParser::new(r"/([^/]+)\.E(\d+\.mp4$", vec![1, 0, 2]);
+        assert!(parser.parse("/Show.E02.mp4").is_none());
+        assert!(matches!(parser.try_parse("/Show.E02.mp4"), Err(ParseError::InvalidRegex { .. })));
+
+        let parser = RegexParser::new(r"/([^/]+)\.E(\d+)\.mp4$", vec![1, 0, 2]);
+        assert_eq!(parser.try_parse("/Show.E4294967295.mp4").unwrap().unwrap().episode, Some(u32::MAX));
+        assert!(matches!(
+            parser.try_parse("/Show.E99999999999.mp4"),
+            Err(ParseError::InvalidNumber { field: "episode", ref value }) if value == "99999999999",
+        ));
+
+        let parser = RegexParser::new(r"/([^/]+)第(\d{8})期\.mkv$", vec![1, 0, 0, 0, 2]);
+        assert_eq!(parser.parse("/向往的生活第20211315期.mkv").unwrap().air_date, None);
+        assert!(matches!(parser.try_parse("/向往的生活第20211315期.mkv"), Err(ParseError::InvalidDate(_))));
+        let ep = parser.try_parse("/向往的生活第20210115期.mkv").unwrap().unwrap();
+        assert_eq!(ep.air_date, NaiveDate::from_ymd_opt(2021, 1, 15));
+
+        // 设置了转换函数的字段不会返回错误
+        let parser = RegexParser::new(r"/([^/]+)\.E(\w+)\.mp4$", vec![1, 0, 2])
+            .with_episode_transform(|s| (s == "one").then_some(1));
+        assert_eq!(parser.try_parse("/Show.Eone.mp4").unwrap().unwrap().episode, Some(1));
+
+        // 链式解析器返回第一个匹配或第一个错误
+        let chain = ChainParser::new()
+            .with(RegexParser::new(r"/([^/]+)\.S(\d+)\.mp4$", vec![1, 2]))
+            .with(RegexParser::new(r"/([^/]+)\.E(\d+)\.mp4$", vec![1, 0, 3]));
+        assert_eq!(chain.try_parse("/Show.S02.mp4").unwrap().unwrap().season, Some(2));
+        assert!(matches!(chain.try_parse("/Show.E02.mp4"), Err(ParseError::IndexOutOfRange { .. })));
+        assert!(chain.parse("/Show.E02.mp4").is_none());
+    }
+
+    #[cfg(feature = "pinyin")]
+    #[test]
+    fn test_to_pinyin() {
+        use super::{to_pinyin, to_pinyin_with, PinyinStyle};
+
+        assert_eq!(to_pinyin(""), "");
+        assert_eq!(to_pinyin("医馆笑传"), "yi guan xiao chuan");
+        assert_eq!(to_pinyin("龙门镖局 (2013)"), "long men biao ju (2013)");
+        assert_eq!(to_pinyin("The 神探 Show"), "The shen tan Show");
+        assert_eq!(to_pinyin_with("医馆", PinyinStyle::Tone), "yī guǎn");
+        assert_eq!(to_pinyin_with("医馆", PinyinStyle::ToneNum), "yi1 guan3");
+
+        let ep = Episode::from_filename("医馆笑传S02E03.mp4").unwrap();
+        assert_eq!(ep.title_pinyin(), Some("yi guan xiao chuan".to_string()));
+    }
+
+    #[test]
+    fn test_to_filename() {
+        let ep = Episode::from_filename("还珠格格S01E01-E02.mp4").unwrap();
+        assert_eq!(ep.to_filename(), Some("还珠格格.S01E01-E02.mp4".to_string()));
+
+        let ep = Episode { title: Some("AC/DC: Live".to_string()), episode: Some(3), ..Default::default() };
+        assert_eq!(ep.to_filename(), Some("AC_DC_ Live.S01E03".to_string()));
+        let ep = Episode { air_date: NaiveDate::from_ymd_opt(2021, 1, 15), ..Default::default() };
+        assert_eq!(ep.to_filename(), None);
+
+        // 规范的文件名可以解析回相同的剧集
+        for name in ["还珠格格.S01E02.mp4", "还珠格格.S02E01-E02.mkv", "Friends.S10E18.mp4"] {
+            let ep = Episode::from_filename(name).unwrap();
+            assert_eq!(ep.to_filename().as_deref(), Some(name));
+            assert!(ep.matches_filename(Path::new("/tmp").join(name).as_path()), "{name}");
+        }
+
+        let ep = Episode::from_filename("还珠格格S01E02.mp4").unwrap();
+        assert!(ep.matches_filename(Path::new("还珠格格.S01E02.mp4")));
+        assert!(!ep.matches_filename(Path::new("还珠格格S01E02.mp4")));
+        assert!(!ep.matches_filename(Path::new("还珠格格.S01E02.MP4")));
+        assert!(!ep.matches_filename(Path::new("还珠格格.S01E03.mp4")));
+        assert!(!ep.matches_filename(Path::new("/")));
+        assert!(!Episode::default().matches_filename(Path::new("还珠格格.S01E02.mp4")));
+    }
+
+    #[test]
+    fn test_number_transform() {
+        let chinese = |s: &str| "零一二三四五六七八九".chars().position(|c| s == c.to_string()).map(|n| n as u32);
+        let parser = RegexParser::named(r"/(?P<title>[^/]+)第(?P<season>.)季第(?P<episode>.)-(?P<episode_end>.)集\.\w+$")
+            .with_season_transform(chinese)
+            .with_episode_transform(chinese);
+        let ep = parser.parse("/武林外传第二季第三-五集.mp4").unwrap();
+        assert_eq!((ep.season, ep.episode, ep.episode_end), (Some(2), Some(3), Some(5)));
+
+        // 转换失败时字段为 None，季数不会回退到默认季数
+        let ep = parser.parse("/武林外传第X季第三-五集.mp4").unwrap();
+        assert_eq!((ep.season, ep.episode), (None, Some(3)));
+
+        // 设置了转换函数的字段不按 u32 溢出处理
+        let parser = RegexParser::named(r"/(?P<title>[^/]+)\.E(?P<episode>\d+)\.mp4$")
+            .with_episode_transform(|s| s.get(s.len() - 2..)?.parse().ok());
+        assert_eq!(parser.parse("/Show.E99999999999.mp4").unwrap().episode, Some(99));
+    }
+
+    #[test]
+    fn test_required_literals() {
+        let literals = |pattern: &str| super::CompiledPattern::new(pattern).unwrap().literals;
+        assert_eq!(literals(r"(?:^|/)([^/]+?)第(\d{8})期(?:\.(\w+))?$"), vec!["第", "期"]);
+        assert_eq!(literals(r"^(.*?)/([^/]+)S(\d{2})E(\d{2})\.(\w+)$"), vec!["/", "S", "E", "."]);
+        assert_eq!(literals(r"/([^/]+)\.1080P/.*?第(\d{1,2})集"), vec!["/", ".1080P/", "第", "集"]);
+        assert_eq!(literals(r"(?:第(\d+)集|EP(\d+))(x)+"), vec!["x"]);
+        assert!(literals(r"(?i)s(\d+)e(\d+)").is_empty());
+
+        // 缺少字面量时不匹配，全角字符先转为半角再检查
+        let parser = RegexParser::new(r"^(.*?)/([^/]+)S(\d{2})E(\d{2})\.(\w+)$", vec![2, 3, 4]);
+        assert!(parser.parse("/还珠格格/还珠格格01.mp4").is_none());
+        let parser = parser.with_normalize_width(true);
+        assert_eq!(parser.parse("/还珠格格/还珠格格Ｓ０１Ｅ０２.mp4").unwrap().episode, Some(2));
+
+        // 所有内置的模式都能匹配各自的示例
+        for path in [
+            "/Volumes/Getea/影片/电影/黄渤/疯狂的赛车.2009.01201.mp4",
+            "/Volumes/ZhiTai/影片/电视剧/约会专家.1080P/约会专家第04集.mp4",
+            "/Volumes/ZhiTai/影片/电视剧/怪侠一枝梅.全30集.4K/02.mp4",
+            "/Volumes/ZhiTai/影片/综艺/向往的生活第20210115期.mkv",
+            "/电视剧/龙门镖局/龙门镖局 (2013) 4K/龙门镖局.Longmen.Express.2013.E02.4K.2160p.HEVC.AAC-DHTCLUB.mp4",
+        ] {
+            assert!(Episode::from_path(path).unwrap().is_some(), "{path}");
+        }
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn test_parser_config() {
+        use super::ParserConfig;
+
+        let config = ParserConfig::from_toml(r#"
+            [[parsers]]
+            pattern = '/(?P<title>[^/]+)/(?P<air_date>\d{8})\.\w+$'
+            priority = 1
+            hints = ["/综艺/"]
+
+            [[parsers]]
+            pattern = '^(.*?)/([^/]+)/第(\d+)季/(\d+)\.(\w+)$'
+            indexes = [2, 3, 4]
+            normalize_width = true
+            default_season = 2
+        "#).unwrap();
+        assert_eq!(config.parsers.len(), 2);
+        assert_eq!(config.parsers[0].priority(), 1);
+        let ep = config.parsers[0].parse("/综艺/向往的生活/20210115.mp4").unwrap();
+        assert_eq!(ep.air_date, NaiveDate::from_ymd_opt(2021, 1, 15));
+        assert!(config.parsers[0].parse("/照片/20210115.jpg").is_none());
+        let ep = config.parsers[1].parse("/影片/武林外传/第２季/０５.mp4").unwrap();
+        assert_eq!((ep.season, ep.episode), (Some(2), Some(5)));
+
+        assert!(ParserConfig::from_toml("").unwrap().parsers.is_empty());
+        assert!(matches!(ParserConfig::from_toml("[[parsers]]\nindexes = [1]"), Err(Error::Toml { path: None, .. })));
+        assert!(matches!(ParserConfig::from_toml("[[parsers]]\npattern = '(unclosed'"), Err(Error::PatternError { .. })));
+
+        let tmp = TempDir::new("lazytool-parser-config-").unwrap();
+        let path = tmp.child("parsers.toml");
+        fs::write(&path, "parsers = 1").unwrap();
+        match ParserConfig::load(&path) {
+            Err(Error::Toml { path: Some(p), .. }) => assert_eq!(p, path),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert_eq!(ParserConfig::load(tmp.child("missing.toml")).unwrap_err().io_kind(), Some(std::io::ErrorKind::NotFound));
+        assert!(Episode::from_path_with_config("/还珠格格/还珠格格S01E02.mp4", tmp.child("missing.toml")).is_err());
+
+        // 默认路径下没有配置文件时为空的配置，有配置文件时按配置加载
+        let home = TempDir::new("lazytool-parser-config-home-").unwrap();
+        assert!(ParserConfig::load_from_home(home.path()).unwrap().parsers.is_empty());
+        fs::create_dir_all(home.child(".config/lazytool")).unwrap();
+        fs::write(home.child(".config/lazytool/parsers.toml"), "[[parsers]]\npattern = '/(?P<title>[^/]+)/(?P<episode>\\d+)\\.mp4$'\n").unwrap();
+        assert_eq!(ParserConfig::load_from_home(home.path()).unwrap().parsers.len(), 1);
+        fs::write(home.child(".config/lazytool/parsers.toml"), "parsers = 1").unwrap();
+        assert!(matches!(ParserConfig::load_from_home(home.path()), Err(Error::Toml { .. })));
+    }
+
+    #[test]
+    fn test_rename_plan() {
+        let tmp = TempDir::new("lazytool-rename-").unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("下载/还珠格格S01.1080P")).unwrap();
+        fs::write(dir.join("下载/还珠格格S01.1080P/还珠格格S01E01.mp4"), "1").unwrap();
+        fs::write(dir.join("下载/还珠格格S01.1080P/还珠格格S01E02.mkv"), "2").unwrap();
+        fs::write(dir.join("下载/向往的生活第20210115期.mkv"), "3").unwrap();
+        fs::write(dir.join("下载/notes.mp4"), "").unwrap();
+
+        let plan = rename_plan(dir, "{title}/S{season:2}/{title}.S{season:2}E{episode:2}.{ext}").unwrap();
+        assert_eq!(plan.operations, vec![
+            (dir.join("下载/还珠格格S01.1080P/还珠格格S01E01.mp4"), dir.join("还珠格格/S01/还珠格格.S01E01.mp4")),
+            (dir.join("下载/还珠格格S01.1080P/还珠格格S01E02.mkv"), dir.join("还珠格格/S01/还珠格格.S01E02.mkv")),
+        ]);
+        plan.apply().unwrap();
+        assert_eq!(fs::read_to_string(dir.join("还珠格格/S01/还珠格格.S01E02.mkv")).unwrap(), "2");
+        assert!(!dir.join("下载/还珠格格S01.1080P/还珠格格S01E01.mp4").exists());
+        // 已经重命名过的文件不再出现在计划中
+        let plan = rename_plan(dir, "{title}/S{season:2}/{title}.S{season:2}E{episode:2}.{ext}").unwrap();
+        assert!(plan.operations.is_empty());
+
+        // 目标重复或已存在时不移动任何文件
+        let plan = rename_plan(dir, "{title}.mkv").unwrap();
+        assert_eq!(plan.operations.len(), 3);
+        assert!(matches!(plan.apply(), Err(Error::AlreadyExists(_))));
+        assert!(dir.join("还珠格格/S01/还珠格格.S01E01.mp4").exists());
+        assert!(dir.join("下载/向往的生活第20210115期.mkv").exists());
+
+        let ep = Episode::from_filename("向往的生活第20210115期.mkv").unwrap();
+        assert_eq!(ep.to_filename_with("{title}/{air_date}.{ext}").unwrap(), Some("向往的生活/20210115.mkv".to_string()));
+        let ep = Episode { title: Some("AC/DC".to_string()), episode: Some(3), ..Default::default() };
+        assert_eq!(ep.to_filename_with("{title}/{episode:3}. ").unwrap(), Some("AC_DC/003".to_string()));
+
+        for template in ["{title", "{title:2}", "{season:0}", "{ext:x}", "{*}"] {
+            assert!(matches!(ep.to_filename_with(template), Err(Error::InvalidPattern { .. })), "{template}");
+        }
+        assert!(rename_plan(dir, "{title").is_err());
+
+        // 空目录同样检查模板
+        let empty = tmp.child("空目录");
+        fs::create_dir_all(&empty).unwrap();
+        assert!(rename_plan(&empty, "{title}.{ext}").unwrap().operations.is_empty());
+        for template in ["{name}.{ext}", "{title:2}", "{title"] {
+            assert!(matches!(rename_plan(&empty, template), Err(Error::InvalidPattern { .. })), "{template}");
+        }
+    }
+
+    #[test]
+    fn test_scene_release() {
+        let ep = Episode::from_path("/TV/Show.Name.S01E05.1080p.WEB-DL.x265.mkv").unwrap().unwrap();
+        assert_eq!(ep.title, Some("Show Name".to_string()));
+        assert_eq!((ep.season, ep.episode, ep.year), (Some(1), Some(5), None));
+        assert_eq!(ep.resolution, Some(Resolution::HD1080));
+        assert_eq!(ep.container, Some("mkv".to_string()));
+
+        let ep = Episode::from_path("/TV/Show Name - 1x05 - Title.mkv").unwrap().unwrap();
+        assert_eq!(ep.title, Some("Show Name".to_string()));
+        assert_eq!((ep.season, ep.episode), (Some(1), Some(5)));
+        assert_eq!(ep.resolution, None);
+
+        let ep = Episode::from_path("/TV/Show.Name.2023.S02E10.REPACK.mkv").unwrap().unwrap();
+        assert_eq!(ep.title, Some("Show Name".to_string()));
+        assert_eq!((ep.season, ep.episode, ep.year), (Some(2), Some(10), Some(2023)));
+
+        let ep = Episode::from_path("/TV/Show Name (2019) - s03e07e08 - 2160p.mkv").unwrap().unwrap();
+        assert_eq!(ep.title, Some("Show Name".to_string()));
+        assert_eq!((ep.season, ep.episode, ep.episode_end, ep.year), (Some(3), Some(7), Some(8), Some(2019)));
+        assert_eq!(ep.resolution, Some(Resolution::UHD4K));
+
+        // 分辨率在目录名中
+        let ep = Episode::from_path("/TV/Show.S01.720p/Show.S01E02.mkv").unwrap().unwrap();
+        assert_eq!(ep.resolution, Some(Resolution::HD720));
+
+        for path in ["/Movies/1920x1080.mp4", "/Movies/Show.S01.mkv", "/Music/Artist - 01x.mp3"] {
+            assert!(Episode::from_path(path).unwrap().is_none(), "{path}");
         }
     }
+
+    #[test]
+    fn test_extra_fields() {
+        let parser = RegexParser::new(
+            r"/([^/]+)\.S(\d{2})E(\d{2})\.(\d+p)\.([\w-]+)\.(\w+)\.(\w+)\.\w+$",
+            vec![1, 2, 3, 0, 0, 0, 4, 6, 7, 5],
+        );
+        let ep = parser.parse("/Show.S01E02.2160p.BluRay.x265.DTS.mkv").unwrap();
+        assert_eq!(ep.resolution, Some(Resolution::UHD4K));
+        assert_eq!(ep.source, Some("BluRay".to_string()));
+        assert_eq!(ep.video_codec, Some("x265".to_string()));
+        assert_eq!(ep.audio_codec, Some("DTS".to_string()));
+
+        // 分辨率分组无法识别时为 None，不再从路径中识别
+        let parser = RegexParser::named(r"/(?P<title>[^/]+)\.E(?P<episode>\d{2})\.(?P<resolution>\w+)\.mkv$");
+        assert_eq!(parser.parse("/1080p/Show.E02.HQ.mkv").unwrap().resolution, None);
+        assert_eq!(parser.parse("/Show.E02.720p.mkv").unwrap().resolution, Some(Resolution::HD720));
+
+        // 内置模式 1 捕获目录中的分辨率
+        let ep = Episode::from_path("/影片/电视剧/医馆笑传/医馆笑传S01.37集.1080P/01.mkv").unwrap().unwrap();
+        assert_eq!(ep.resolution, Some(Resolution::HD1080));
+        assert_eq!((ep.video_codec, ep.audio_codec, ep.source), (None, None, None));
+
+        let merged = Episode { source: Some("WEB-DL".to_string()), ..Default::default() }
+            .merge(Episode { source: Some("BluRay".to_string()), video_codec: Some("HEVC".to_string()), ..Default::default() });
+        assert_eq!(merged.source, Some("WEB-DL".to_string()));
+        assert_eq!(merged.video_codec, Some("HEVC".to_string()));
+
+        let json = Episode { audio_codec: Some("AAC".to_string()), ..Default::default() }.to_json().unwrap();
+        assert!(json.contains(r#""audio_codec":"AAC""#));
+        assert!(!json.contains("video_codec"));
+    }
+
+    #[test]
+    fn test_scan_library() {
+        let tmp = TempDir::new("lazytool-library-").unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("电视剧/还珠格格S01.1080P")).unwrap();
+        fs::create_dir_all(dir.join(".cache")).unwrap();
+        fs::write(dir.join("电视剧/还珠格格S01.1080P/还珠格格S01E02.mkv"), "").unwrap();
+        fs::write(dir.join("电视剧/还珠格格S01.1080P/还珠格格S01E01.MP4"), "").unwrap();
+        fs::write(dir.join("电视剧/还珠格格S01.1080P/还珠格格S01E01.srt"), "").unwrap();
+        fs::write(dir.join("Show.Name.S01E05.720p.mkv"), "").unwrap();
+        fs::write(dir.join("home video.mp4"), "").unwrap();
+        fs::write(dir.join(".cache/还珠格格S01E03.mp4"), "").unwrap();
+
+        let items = scan_library(dir).unwrap();
+        let paths: Vec<&PathBuf> = items.iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, vec![
+            &dir.join("Show.Name.S01E05.720p.mkv"),
+            &dir.join("home video.mp4"),
+            &dir.join("电视剧/还珠格格S01.1080P/还珠格格S01E01.MP4"),
+            &dir.join("电视剧/还珠格格S01.1080P/还珠格格S01E02.mkv"),
+        ]);
+        let episodes: Vec<Option<u32>> = items.iter().map(|(_, ep)| ep.as_ref().and_then(|ep| ep.episode)).collect();
+        assert_eq!(episodes, vec![Some(5), None, Some(1), Some(2)]);
+        assert_eq!(items[2].1.as_ref().unwrap().source_path.as_ref(), Some(&items[2].0));
+
+        let parallel = scan_library_with(dir, ScanOptions { parallel: true, ..Default::default() }).unwrap();
+        let parallel_paths: Vec<&PathBuf> = parallel.iter().map(|(path, _)| path).collect();
+        assert_eq!(parallel_paths, paths);
+        assert_eq!(parallel[3].1.as_ref().unwrap().episode, Some(2));
+
+        let shallow = scan_library_with(dir, ScanOptions { max_depth: Some(1), ..Default::default() }).unwrap();
+        assert_eq!(shallow.len(), 2);
+
+        assert!(scan_library(dir.join("missing")).is_err());
+        assert!(scan_library_with(dir.join("missing"), ScanOptions { parallel: true, ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn test_builtin_parsers_cached() {
+        let parsers = Episode::builtin_parsers();
+        assert!(std::ptr::eq(parsers, Episode::builtin_parsers()));
+        assert_eq!(parsers.len(), Episode::PARSERS.len());
+
+        Episode::from_path("/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E02.mp4").unwrap().unwrap();
+        // 匹配成功的模式之前的正则都已编译并缓存
+        assert!(parsers[..2].iter().all(|p| p.regex.get().is_some_and(Option::is_some)));
+
+        // 借用的解析器保留优先级
+        let low = RegexParser::named(r"/(?P<title>[^/]+)\.E(?P<episode>\d{2})\.\w+$");
+        let high = RegexParser::named(r"/(?P<title>[^/.]+)\.E\d(?P<episode>\d)\.\w+$").with_priority(3);
+        let ep = Episode::from_path_with_regex("/Show.E12.mp4", vec![&low, &high]).unwrap().unwrap();
+        assert_eq!(ep.episode, Some(2));
+    }
 }
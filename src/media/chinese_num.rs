@@ -0,0 +1,144 @@
+//! 中文数字转换，用于解析 `第十二集`、`第二季` 等中文数字形式的集数和季数
+
+/// 将中文数字转为数字，支持 `零〇一二两三四五六七八九` 以及 `十百千万` 单位，
+/// 也支持不带单位逐位书写的形式如 `二〇二一`，包含其他字符、单位顺序不合法、为空或超出 u32 范围时返回 None
+///
+/// Examples
+///
+/// ```
+/// use lazytool::media::chinese_num;
+///
+/// assert_eq!(chinese_num::parse("十二"), Some(12));
+/// assert_eq!(chinese_num::parse("二十"), Some(20));
+/// assert_eq!(chinese_num::parse("一百零五"), Some(105));
+/// assert_eq!(chinese_num::parse("两千三百"), Some(2300));
+/// assert_eq!(chinese_num::parse("三万零一"), Some(30001));
+/// assert_eq!(chinese_num::parse("一千零十"), Some(1010));
+/// assert_eq!(chinese_num::parse("二〇二一"), Some(2021));
+///
+/// // 单位需要从大到小排列
+/// assert_eq!(chinese_num::parse("十百"), None);
+/// assert_eq!(chinese_num::parse("百十"), None);
+/// assert_eq!(chinese_num::parse("12"), None);
+/// assert_eq!(chinese_num::parse(""), None);
+/// ```
+pub fn parse(s: &str) -> Option<u32> {
+    if s.is_empty() {
+        return None;
+    }
+    // 不带单位时逐位组成数字
+    if s.chars().all(|c| digit(c).is_some()) {
+        return s.chars().try_fold(0u32, |n, c| n.checked_mul(10)?.checked_add(digit(c)?));
+    }
+
+    let mut total: u32 = 0;
+    let mut section: u32 = 0;
+    let mut pending: Option<u32> = None;
+    // 当前节（万以下）中上一个单位，后面的单位必须比它小
+    let mut last_unit = u32::MAX;
+    for c in s.chars() {
+        if let Some(d) = digit(c) {
+            // 两个数字连续出现只允许中间是零，如 `一百零五`
+            if pending.is_some_and(|p| p != 0) {
+                return None;
+            }
+            pending = Some(d);
+            continue;
+        }
+        match unit(c)? {
+            10_000 => {
+                let value = section.checked_add(pending.take().unwrap_or(0))?;
+                // 万只能出现一次
+                if value == 0 || total > 0 {
+                    return None;
+                }
+                total = value.checked_mul(10_000)?;
+                section = 0;
+                last_unit = u32::MAX;
+            }
+            unit => {
+                if unit >= last_unit {
+                    return None;
+                }
+                let d = match pending.take() {
+                    Some(d) if d > 0 => d,
+                    // 省略的一只能用于十
+                    _ if unit != 10 => return None,
+                    // `十二` 省略了开头的一
+                    None if total == 0 && section == 0 => 1,
+                    // `一千零十` 中的零只是占位，后面的十省略了一
+                    Some(_) if total > 0 || section > 0 => 1,
+                    _ => return None,
+                };
+                section = section.checked_add(d.checked_mul(unit)?)?;
+                last_unit = unit;
+            }
+        }
+    }
+    total.checked_add(section)?.checked_add(pending.unwrap_or(0))
+}
+
+fn digit(c: char) -> Option<u32> {
+    let d = match c {
+        '零' | '〇' => 0,
+        '一' => 1,
+        '二' | '两' => 2,
+        '三' => 3,
+        '四' => 4,
+        '五' => 5,
+        '六' => 6,
+        '七' => 7,
+        '八' => 8,
+        '九' => 9,
+        _ => return None,
+    };
+    Some(d)
+}
+
+fn unit(c: char) -> Option<u32> {
+    match c {
+        '十' => Some(10),
+        '百' => Some(100),
+        '千' => Some(1_000),
+        '万' => Some(10_000),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn test_parse() {
+        for (s, n) in [
+            ("零", 0), ("一", 1), ("九", 9), ("十", 10), ("十一", 11), ("一十一", 11), ("九十九", 99),
+            ("一百", 100), ("一百一十", 110), ("一百零一", 101), ("三百二十一", 321), ("一千零一", 1001),
+            ("一万", 10_000), ("十万", 100_000), ("一万二千", 12_000), ("一千零十", 1010), ("一千零一十", 1010),
+            ("十万零十", 100_010), ("一百二十万", 1_200_000),
+        ] {
+            assert_eq!(parse(s), Some(n), "{s}");
+        }
+
+        // 逐位书写
+        assert_eq!(parse("〇五"), Some(5));
+        assert_eq!(parse("一二"), Some(12));
+
+        // 不合法的组合
+        assert_eq!(parse("十二三"), None);
+        assert_eq!(parse("一十百"), None);
+        assert_eq!(parse("一百二百"), None);
+        assert_eq!(parse("一百十"), None);
+        assert_eq!(parse("零十"), None);
+        assert_eq!(parse("一千零百"), None);
+        assert_eq!(parse("一万二万"), None);
+        assert_eq!(parse("第十二"), None);
+        assert_eq!(parse("万"), None);
+        assert_eq!(parse("12集"), None);
+        assert_eq!(parse("四十二亿"), None);
+
+        // 超出 u32 范围
+        assert_eq!(parse("九九九九九九九九九九九"), None);
+        assert_eq!(parse("九十万万"), None);
+    }
+}
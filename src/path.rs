@@ -1,7 +1,17 @@
-use std::{env, path::{Path, PathBuf}};
+use std::{collections::{HashMap, HashSet}, env, fs, io::{self, Write}, path::{Component, Path, PathBuf}, time::UNIX_EPOCH};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::error::{Error, Result};
+use crate::path_str::{self, split_extension};
+pub use crate::path_str::{sanitize_filename, sanitize_filename_with, COMPOUND_EXTENSIONS};
+
+use crate::time;
 
 /// 解析 `~` 家地址
 ///
+/// 支持 `~`、`~/path` 以及 `~username/path`，未知用户或无法获取家目录时原样返回
+///
 /// Examples
 ///
 /// ```
@@ -9,26 +19,249 @@ use std::{env, path::{Path, PathBuf}};
 /// use lazytool::expand_user;
 /// use std::path::PathBuf;
 ///
+/// let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+///
 /// let path = expand_user("~/.bash_profile");
-/// assert_eq!(path, PathBuf::from("/Users/wxnacy/.bash_profile"));
+/// assert_eq!(path, home.join(".bash_profile"));
+///
+/// let path = expand_user("~");
+/// assert_eq!(path, home);
 ///
 /// let path = expand_user("/tmp/foo");
 /// assert_eq!(path, PathBuf::from("/tmp/foo"));
+///
+/// let path = expand_user("~no_such_user_lazytool/foo");
+/// assert_eq!(path, PathBuf::from("~no_such_user_lazytool/foo"));
+///
+/// # #[cfg(unix)]
+/// assert!(!expand_user("~root/foo").starts_with("~root"));
 /// ```
 pub fn expand_user<P: AsRef<Path>>(path: P) -> PathBuf {
-    let mut path_buf = PathBuf::from(path.as_ref());
+    let path = path.as_ref();
+    let path_str = match path.to_str() {
+        Some(s) if s.starts_with('~') => s,
+        _ => return path.to_path_buf(),
+    };
+
+    // 拆分 `~username` 与剩余部分
+    let (name, rest) = match path_str.find(is_separator) {
+        Some(i) => (&path_str[1..i], path_str[i + 1..].trim_start_matches(is_separator)),
+        None => (&path_str[1..], ""),
+    };
+    let home = if name.is_empty() { home_dir() } else { user_home_dir(name) };
+
+    match home {
+        Some(home) if rest.is_empty() => home,
+        Some(home) => home.join(rest),
+        None => path.to_path_buf(),
+    }
+}
+
+/// 将家目录前缀替换为 `~`，是 `expand_user` 的逆操作，仅按完整的路径层级匹配
+///
+/// Examples
+///
+/// ```
+/// use lazytool::{expand_user, path};
+/// use std::path::PathBuf;
+///
+/// let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+///
+/// assert_eq!(path::contract_user(home.join("projects/foo")), PathBuf::from("~/projects/foo"));
+/// assert_eq!(path::contract_user(&home), PathBuf::from("~"));
+/// assert_eq!(path::contract_user("/tmp/foo"), PathBuf::from("/tmp/foo"));
+///
+/// // 家目录只是字符串前缀时不替换
+/// let sibling = PathBuf::from(format!("{}2/x", home.display()));
+/// assert_eq!(path::contract_user(&sibling), sibling);
+///
+/// // 与 expand_user 互逆
+/// let path = home.join("Movies/还珠格格");
+/// assert_eq!(expand_user(path::contract_user(&path)), path);
+/// assert_eq!(path::contract_user_str(&path), "~/Movies/还珠格格");
+/// ```
+pub fn contract_user<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    let home = match home_dir() {
+        Some(home) if home.components().count() > 1 => home,
+        _ => return path.to_path_buf(),
+    };
+
+    match path.strip_prefix(&home) {
+        Ok(rest) if rest.as_os_str().is_empty() => PathBuf::from("~"),
+        Ok(rest) => Path::new("~").join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// 将家目录前缀替换为 `~` 并转为 `String`
+pub fn contract_user_str<P: AsRef<Path>>(path: P) -> String {
+    must_to_string(contract_user(path))
+}
+
+/// 展开路径中的环境变量
+///
+/// 支持 `$VAR` 和 `${VAR}`，`$$` 表示字面的 `$`，Windows 下还支持 `%VAR%`。
+/// 未定义的变量原样保留，替换后的值不会再次展开
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::path::PathBuf;
+///
+/// std::env::set_var("LAZYTOOL_MEDIA_ROOT", "/Volumes/Getea");
+/// std::env::set_var("LAZYTOOL_BACKUP_DIR", "/backup/$LAZYTOOL_MEDIA_ROOT");
+///
+/// assert_eq!(path::expand_vars("$LAZYTOOL_MEDIA_ROOT/电视剧"), PathBuf::from("/Volumes/Getea/电视剧"));
+/// assert_eq!(path::expand_vars("${LAZYTOOL_MEDIA_ROOT}_2/daily"), PathBuf::from("/Volumes/Getea_2/daily"));
+/// assert_eq!(path::expand_vars("/price/$$5"), PathBuf::from("/price/$5"));
+/// assert_eq!(path::expand_vars("$LAZYTOOL_NO_SUCH_VAR/a"), PathBuf::from("$LAZYTOOL_NO_SUCH_VAR/a"));
+///
+/// // 值中的变量不会再次展开
+/// assert_eq!(path::expand_vars("${LAZYTOOL_BACKUP_DIR}"), PathBuf::from("/backup/$LAZYTOOL_MEDIA_ROOT"));
+/// ```
+pub fn expand_vars<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    match path.to_str() {
+        Some(s) => substitute_vars(s, cfg!(windows), false).unwrap_or_else(|_| path.to_path_buf()),
+        None => path.to_path_buf(),
+    }
+}
+
+/// 展开路径中的环境变量，存在未定义的变量时返回错误
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::path::PathBuf;
+///
+/// std::env::set_var("LAZYTOOL_MEDIA_ROOT", "/Volumes/Getea");
+///
+/// let path = path::expand_vars_strict("$LAZYTOOL_MEDIA_ROOT/电视剧").unwrap();
+/// assert_eq!(path, PathBuf::from("/Volumes/Getea/电视剧"));
+///
+/// let err = path::expand_vars_strict("${LAZYTOOL_NO_SUCH_VAR}/daily").unwrap_err();
+/// assert!(err.to_string().contains("LAZYTOOL_NO_SUCH_VAR"));
+/// ```
+pub fn expand_vars_strict<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = path.as_ref();
+    let s = path.to_str().ok_or_else(|| Error::InvalidPath(path.to_path_buf()))?;
+    substitute_vars(s, cfg!(windows), true)
+}
+
+/// 先展开 `~` 再展开环境变量，变量的值以 `~` 开头时不会再展开为家目录
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::path::PathBuf;
+///
+/// let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+/// std::env::set_var("LAZYTOOL_SHOW", "还珠格格");
+///
+/// assert_eq!(path::expand("~/Movies/$LAZYTOOL_SHOW"), home.join("Movies/还珠格格"));
+/// ```
+pub fn expand<P: AsRef<Path>>(path: P) -> PathBuf {
+    expand_vars(expand_user(path))
+}
+
+/// 替换字符串中的环境变量，`percent` 为 true 时同时支持 `%VAR%`
+fn substitute_vars(s: &str, percent: bool, strict: bool) -> Result<PathBuf> {
+    let mut out = std::ffi::OsString::new();
+    let mut rest = s;
+    while let Some(i) = rest.find(|c| c == '$' || (percent && c == '%')) {
+        out.push(&rest[..i]);
+        let sigil = &rest[i..i + 1];
+        let after = &rest[i + 1..];
 
-    // 检查路径是否以 '~' 开头
-    if path_buf.starts_with("~") {
-        // 获取用户的主目录
-        if let Some(home_dir) = env::var_os("HOME") {
-            // 替换 '~' 为主目录路径
-            let home = Path::new(&home_dir);
-            path_buf = home.join(path_buf.strip_prefix("~").unwrap());
+        // 解析变量名以及变量引用之后的剩余部分
+        let parsed = if sigil == "%" {
+            after.find('%')
+                .map(|end| (&after[..end], &after[end + 1..]))
+                .filter(|(name, _)| is_var_name(name))
+        } else if let Some(after) = after.strip_prefix('$') {
+            out.push("$");
+            rest = after;
+            continue;
+        } else if let Some(braced) = after.strip_prefix('{') {
+            braced.find('}').map(|end| (&braced[..end], &braced[end + 1..]))
+                .filter(|(name, _)| is_var_name(name))
+        } else {
+            let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+            Some((&after[..end], &after[end..])).filter(|(name, _)| is_var_name(name))
+        };
+
+        let Some((name, after)) = parsed else {
+            // 不是变量引用，按字面保留
+            out.push(sigil);
+            rest = after;
+            continue;
+        };
+        match env::var_os(name) {
+            Some(value) => out.push(value),
+            None if strict => return Err(Error::UndefinedVariable { name: name.to_string(), input: s.to_string() }),
+            None => out.push(&rest[i..rest.len() - after.len()]),
+        }
+        rest = after;
+    }
+    out.push(rest);
+    Ok(PathBuf::from(out))
+}
+
+/// 是否为合法的变量名，以字母或下划线开头，由字母、数字和下划线组成
+fn is_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 是否为路径分隔符
+fn is_separator(c: char) -> bool {
+    c == '/' || (cfg!(windows) && c == '\\')
+}
+
+/// 获取当前用户的家目录，依次尝试 `HOME`、`USERPROFILE` 以及 `HOMEDRIVE` + `HOMEPATH`
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    if let Some(home) = env::var_os("HOME").filter(|h| !h.is_empty()) {
+        return Some(PathBuf::from(home));
+    }
+    if let Some(home) = env::var_os("USERPROFILE").filter(|h| !h.is_empty()) {
+        return Some(PathBuf::from(home));
+    }
+    match (env::var_os("HOMEDRIVE"), env::var_os("HOMEPATH")) {
+        (Some(drive), Some(path)) => {
+            let mut home = drive;
+            home.push(path);
+            Some(PathBuf::from(home))
         }
+        _ => None,
     }
+}
+
+/// 获取指定用户的家目录，unix 下读取 `/etc/passwd`
+#[cfg(unix)]
+fn user_home_dir(name: &str) -> Option<PathBuf> {
+    let passwd = fs::read_to_string("/etc/passwd").ok()?;
+    passwd.lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.len() >= 6 && fields[0] == name)
+        .map(|fields| PathBuf::from(fields[5]))
+}
+
+/// 获取指定用户的家目录，windows 下取与当前用户家目录同级的目录
+#[cfg(windows)]
+fn user_home_dir(name: &str) -> Option<PathBuf> {
+    let home = home_dir()?.parent()?.join(name);
+    home.is_dir().then_some(home)
+}
 
-    path_buf
+#[cfg(not(any(unix, windows)))]
+fn user_home_dir(_name: &str) -> Option<PathBuf> {
+    None
 }
 
 /// 地址强行转为 `String`
@@ -49,6 +282,20 @@ pub fn must_to_string<P: AsRef<Path>>(path: P) -> String {
     String::new()
 }
 
+/// 地址转为字符串，不是合法的 UTF-8 时返回 `Error::InvalidPath`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// assert_eq!(path::try_to_string("/tmp/filter").unwrap(), "/tmp/filter");
+/// ```
+pub fn try_to_string<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
+    path.to_str().map(str::to_string).ok_or_else(|| Error::InvalidPath(path.to_path_buf()))
+}
+
 /// 强行获取地址文件名
 ///
 /// Examples
@@ -63,3 +310,2601 @@ pub fn must_to_string<P: AsRef<Path>>(path: P) -> String {
 pub fn must_get_filename<P: AsRef<Path>>(path: P) -> String {
     path.as_ref().file_name().expect("Failed get filename").to_string_lossy().into_owned()
 }
+
+/// 获取地址文件名，没有文件名时返回 `Error::InvalidPath`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::{path, Error};
+///
+/// assert_eq!(path::try_get_filename("/tmp/filter.json").unwrap(), "filter.json");
+/// assert!(matches!(path::try_get_filename("/"), Err(Error::InvalidPath(_))));
+/// ```
+pub fn try_get_filename<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| Error::InvalidPath(path.to_path_buf()))
+}
+
+/// 拆分 Windows 盘符（或 UNC 前缀）与剩余路径，非 Windows 风格的路径盘符为 `None`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// assert_eq!(path::split_drive(r"C:\a\b"), (Some("C:".to_string()), r"\a\b".to_string()));
+/// assert_eq!(path::split_drive("d:/movies"), (Some("d:".to_string()), "/movies".to_string()));
+/// assert_eq!(
+///     path::split_drive(r"\\server\share\dir\file.mp4"),
+///     (Some(r"\\server\share".to_string()), r"\dir\file.mp4".to_string()),
+/// );
+/// assert_eq!(path::split_drive("/tmp/foo"), (None, "/tmp/foo".to_string()));
+/// ```
+pub fn split_drive(path: &str) -> (Option<String>, String) {
+    let (drive, rest) = path_str::split_drive(path);
+    (drive.map(str::to_string), rest.to_string())
+}
+
+/// 获取文件修改时间相对当前时间的描述，如 `3 days ago`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::fs::File;
+/// use std::time::{Duration, SystemTime};
+///
+/// let file = std::env::temp_dir().join("lazytool-modified-ago.txt");
+/// File::create(&file).unwrap();
+/// assert_eq!(path::modified_ago(&file).unwrap(), "just now");
+///
+/// let aged = SystemTime::now() - Duration::from_secs(3 * 86400 + 60);
+/// File::options().write(true).open(&file).unwrap().set_modified(aged).unwrap();
+/// assert_eq!(path::modified_ago(&file).unwrap(), "3 days ago");
+///
+/// std::fs::remove_file(&file).unwrap();
+/// ```
+pub fn modified_ago<P: AsRef<Path>>(path: P) -> Result<String> {
+    let modified = fs::metadata(path)?.modified()?;
+    let ts = match modified.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    Ok(time::time_ago(ts))
+}
+
+/// 获取相对于 `base` 的路径，`path` 不在 `base` 下时返回 `None`
+///
+/// 需要通过 `..` 跳出 `base` 的相对路径时使用 `relative_path`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::path::PathBuf;
+///
+/// let p = path::relative_to("/Volumes/ZhiTai/影片/电视剧/爱情公寓/01.mp4", "/Volumes/ZhiTai/影片");
+/// assert_eq!(p, Some(PathBuf::from("电视剧/爱情公寓/01.mp4")));
+///
+/// assert_eq!(path::relative_to("/Volumes/Getea/01.mp4", "/Volumes/ZhiTai"), None);
+/// ```
+pub fn relative_to<P, B>(path: P, base: B) -> Option<PathBuf>
+    where P: AsRef<Path>,
+          B: AsRef<Path>,
+{
+    path.as_ref().strip_prefix(base).ok().map(Path::to_path_buf)
+}
+
+/// 按字面计算 `target` 相对于 `base` 的路径，必要时插入 `..`，不访问文件系统
+///
+/// 两个路径都会先解析 `~` 并经过 `normalize`，一个是绝对路径而另一个是相对路径，
+/// 或在 Windows 上位于不同盘符时返回 `None`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::path::PathBuf;
+///
+/// assert_eq!(path::relative_path("/a/b/c", "/a/d"), Some(PathBuf::from("../b/c")));
+/// assert_eq!(path::relative_path("/a/b/c", "/a"), Some(PathBuf::from("b/c")));
+/// assert_eq!(path::relative_path("/a", "/a/b/c"), Some(PathBuf::from("../..")));
+/// assert_eq!(path::relative_path("/a/./b/../c", "/a/c"), Some(PathBuf::from(".")));
+/// assert_eq!(path::relative_path("/a/b", "a"), None);
+/// ```
+pub fn relative_path<P, B>(target: P, base: B) -> Option<PathBuf>
+    where P: AsRef<Path>,
+          B: AsRef<Path>,
+{
+    let target = expand_and_normalize(target);
+    let base = expand_and_normalize(base);
+    if target.has_root() != base.has_root() {
+        return None;
+    }
+
+    let target: Vec<Component> = target.components().filter(|c| *c != Component::CurDir).collect();
+    let base: Vec<Component> = base.components().filter(|c| *c != Component::CurDir).collect();
+    // 盘符不同时无法得到相对路径
+    let prefix = |parts: &[Component]| parts.iter().find_map(|c| match c {
+        Component::Prefix(p) => Some(p.as_os_str().to_ascii_lowercase()),
+        _ => None,
+    });
+    if prefix(&target) != prefix(&base) {
+        return None;
+    }
+
+    let common = target.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    // 相对路径的 base 中剩余的 `..` 无法还原
+    if base[common..].contains(&Component::ParentDir) {
+        return None;
+    }
+
+    let mut path = PathBuf::new();
+    for _ in common..base.len() {
+        path.push("..");
+    }
+    path.extend(&target[common..]);
+    if path.as_os_str().is_empty() {
+        path.push(".");
+    }
+    Some(path)
+}
+
+/// 多个路径按层级比较得到的最长公共前缀，没有公共部分（如根目录不同，或绝对路径与相对路径混用）或输入为空时返回 `None`
+///
+/// 所有输入路径都与公共前缀相同且带有扩展名时，视为文件并返回其所在目录，
+/// 因此只有一个文件路径时返回其父目录，只有一个目录路径时返回其本身
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::path::PathBuf;
+///
+/// let prefix = path::common_prefix([
+///     "/Volumes/ZhiTai/影片/电视剧/爱情公寓/01.mp4",
+///     "/Volumes/ZhiTai/影片/电视剧/武林外传/01.mp4",
+///     "/Volumes/ZhiTai/影片/电视剧/武林外传/02.mp4",
+/// ]);
+/// assert_eq!(prefix, Some(PathBuf::from("/Volumes/ZhiTai/影片/电视剧")));
+///
+/// // 按层级而不是按字节比较
+/// assert_eq!(path::common_prefix(["/a/bc", "/a/bd"]), Some(PathBuf::from("/a")));
+/// assert_eq!(path::common_prefix(["/a", "/b"]), Some(PathBuf::from("/")));
+/// assert_eq!(path::common_prefix(["/a/b", "a/b"]), None);
+/// assert_eq!(path::common_prefix(Vec::<&str>::new()), None);
+///
+/// // 单个路径
+/// assert_eq!(path::common_prefix(["/影片/武林外传/01.mp4"]), Some(PathBuf::from("/影片/武林外传")));
+/// assert_eq!(path::common_prefix(["/影片/武林外传"]), Some(PathBuf::from("/影片/武林外传")));
+/// ```
+pub fn common_prefix<I, P>(paths: I) -> Option<PathBuf>
+    where I: IntoIterator<Item = P>,
+          P: AsRef<Path>,
+{
+    let mut paths = paths.into_iter();
+    let first = paths.next()?;
+    let first = first.as_ref();
+    let mut prefix: Vec<Component> = first.components().collect();
+    let mut longest = prefix.len();
+
+    for path in paths {
+        let components: Vec<Component> = path.as_ref().components().collect();
+        let len = prefix.iter()
+            .zip(&components)
+            .take_while(|(a, b)| a == b)
+            .count();
+        prefix.truncate(len);
+        if prefix.is_empty() {
+            return None;
+        }
+        longest = longest.max(components.len());
+    }
+
+    // 没有输入延伸到公共前缀之后时，公共前缀才可能是文件，取其所在目录
+    if prefix.len() == longest && matches!(prefix.last(), Some(Component::Normal(name)) if Path::new(name).extension().is_some()) {
+        prefix.pop();
+    }
+    if prefix.is_empty() {
+        return None;
+    }
+    Some(prefix.iter().collect())
+}
+
+/// 计算公共前缀并返回各路径去掉前缀后的剩余部分，没有公共前缀时前缀为空、路径保持不变
+///
+/// 与前缀相同的路径剩余部分为 `.`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::path::PathBuf;
+///
+/// let paths = vec![
+///     PathBuf::from("/影片/电视剧/爱情公寓/01.mp4"),
+///     PathBuf::from("/影片/电视剧/武林外传/01.mp4"),
+/// ];
+/// let (prefix, rest) = path::strip_common_prefix(&paths);
+/// assert_eq!(prefix, PathBuf::from("/影片/电视剧"));
+/// assert_eq!(rest, vec![PathBuf::from("爱情公寓/01.mp4"), PathBuf::from("武林外传/01.mp4")]);
+/// ```
+pub fn strip_common_prefix(paths: &[PathBuf]) -> (PathBuf, Vec<PathBuf>) {
+    let prefix = match common_prefix(paths) {
+        Some(prefix) => prefix,
+        None => return (PathBuf::new(), paths.to_vec()),
+    };
+    let rest = paths.iter()
+        .map(|path| match path.strip_prefix(&prefix) {
+            Ok(rest) if rest.as_os_str().is_empty() => PathBuf::from("."),
+            Ok(rest) => rest.to_path_buf(),
+            Err(_) => path.clone(),
+        })
+        .collect();
+    (prefix, rest)
+}
+
+/// 是否为隐藏文件或目录，即最后一级名称以 `.` 开头，包括 macOS 的 `._` 资源文件
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// assert!(path::is_hidden("/Volumes/ZhiTai/.DS_Store"));
+/// assert!(path::is_hidden("/Volumes/ZhiTai/影片/.git"));
+/// assert!(path::is_hidden("/Volumes/ZhiTai/影片/._01.mp4"));
+/// assert!(!path::is_hidden("/Volumes/ZhiTai/.影片/01.mp4"));
+/// assert!(!path::is_hidden("/Volumes/ZhiTai/影片/01.mp4"));
+/// assert!(!path::is_hidden("/Volumes/ZhiTai/影片/.."));
+/// ```
+pub fn is_hidden<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .file_name()
+        .map(|name| name.to_string_lossy().starts_with('.'))
+        .unwrap_or(false)
+}
+
+/// 按字面解析 `.` 与 `..` 并合并重复的分隔符，不访问文件系统
+///
+/// 相对路径开头的 `..` 会保留，绝对路径中超出根目录的 `..` 会被丢弃
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::path::PathBuf;
+///
+/// assert_eq!(path::normalize("/a/b/../c/./d//e"), PathBuf::from("/a/c/d/e"));
+/// assert_eq!(path::normalize("a/./b/../../c"), PathBuf::from("c"));
+/// assert_eq!(path::normalize("../x"), PathBuf::from("../x"));
+/// assert_eq!(path::normalize("a/../../x"), PathBuf::from("../x"));
+/// assert_eq!(path::normalize("/../x"), PathBuf::from("/x"));
+/// assert_eq!(path::normalize("./././"), PathBuf::from("."));
+/// assert_eq!(path::normalize("/a/b/.."), PathBuf::from("/a"));
+/// assert_eq!(path::normalize("/a/.."), PathBuf::from("/"));
+/// ```
+pub fn normalize<P: AsRef<Path>>(p: P) -> PathBuf {
+    let mut prefix = PathBuf::new();
+    let mut parts: Vec<Component> = vec![];
+    let mut absolute = false;
+
+    for component in p.as_ref().components() {
+        match component {
+            Component::Prefix(_) => prefix.push(component.as_os_str()),
+            Component::RootDir => {
+                prefix.push(component.as_os_str());
+                absolute = true;
+            }
+            Component::CurDir => {}
+            Component::ParentDir => match parts.last() {
+                Some(Component::Normal(_)) => {
+                    parts.pop();
+                }
+                // 绝对路径的根目录之上没有目录
+                _ if absolute => {}
+                _ => parts.push(component),
+            },
+            Component::Normal(_) => parts.push(component),
+        }
+    }
+
+    let mut path = prefix;
+    path.extend(parts);
+    if path.as_os_str().is_empty() {
+        path.push(".");
+    }
+    path
+}
+
+/// 先解析 `~` 再按字面规范化路径
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::path::PathBuf;
+///
+/// let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+/// assert_eq!(path::expand_and_normalize("~/a/../b"), path::normalize(home.join("b")));
+/// ```
+pub fn expand_and_normalize<P: AsRef<Path>>(p: P) -> PathBuf {
+    normalize(expand_user(p))
+}
+
+/// 确保目录存在，不存在时连同父目录一起创建，返回解析 `~` 后的绝对路径
+///
+/// 路径已存在但不是目录时返回错误
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let dir = std::env::temp_dir().join("lazytool-ensure-dir-doc/a/b");
+/// let created = path::ensure_dir(&dir).unwrap();
+/// assert!(created.is_dir());
+/// assert!(created.is_absolute());
+///
+/// std::fs::remove_dir_all(std::env::temp_dir().join("lazytool-ensure-dir-doc")).unwrap();
+/// ```
+pub fn ensure_dir<P: AsRef<Path>>(p: P) -> Result<PathBuf> {
+    let path = absolutize(p)?;
+    if path.exists() && !path.is_dir() {
+        return Err(Error::NotADirectory(path));
+    }
+    fs::create_dir_all(&path).map_err(|e| Error::io(format!("Failed to create directory {path:?}"), e))?;
+    Ok(path)
+}
+
+/// 确保文件的父目录存在，返回解析 `~` 后的文件绝对路径，可直接传给 `File::create`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let file = std::env::temp_dir().join("lazytool-ensure-parent-doc/a/b.txt");
+/// let file = path::ensure_parent(&file).unwrap();
+/// std::fs::File::create(&file).unwrap();
+/// assert!(file.is_file());
+///
+/// std::fs::remove_dir_all(std::env::temp_dir().join("lazytool-ensure-parent-doc")).unwrap();
+/// ```
+pub fn ensure_parent<P: AsRef<Path>>(file_path: P) -> Result<PathBuf> {
+    let path = absolutize(file_path)?;
+    if let Some(parent) = path.parent() {
+        ensure_dir(parent)?;
+    }
+    Ok(path)
+}
+
+/// 解析 `~` 并基于当前目录转为绝对路径，只按字面规范化，不解析符号链接，路径不需要存在
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let cwd = std::env::current_dir().unwrap();
+/// assert_eq!(path::absolutize("a/./b/../c").unwrap(), cwd.join("a/c"));
+/// assert_eq!(path::absolutize("/a/b/../c").unwrap(), std::path::PathBuf::from("/a/c"));
+/// ```
+pub fn absolutize<P: AsRef<Path>>(p: P) -> Result<PathBuf> {
+    let path = expand_user(p);
+    if path.is_absolute() {
+        return Ok(normalize(path));
+    }
+    let cwd = env::current_dir().map_err(|e| Error::io("Failed to get current directory", e))?;
+    Ok(normalize(cwd.join(path)))
+}
+
+/// 宽松的 `canonicalize`，路径不存在时也能使用
+///
+/// 解析 `~` 并转为绝对路径后，对最长的已存在部分解析符号链接，再按字面拼接其余不存在的部分，
+/// 已存在部分中的符号链接循环等错误会原样返回
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let tmp = std::fs::canonicalize(std::env::temp_dir()).unwrap();
+/// let p = path::canonicalize_lenient(std::env::temp_dir().join("lazytool-not-exists/a/../b")).unwrap();
+/// assert_eq!(p, tmp.join("lazytool-not-exists/b"));
+/// ```
+pub fn canonicalize_lenient<P: AsRef<Path>>(p: P) -> Result<PathBuf> {
+    let path = expand_user(p);
+    let path = if path.is_absolute() {
+        path
+    } else {
+        env::current_dir().map_err(|e| Error::io("Failed to get current directory", e))?.join(path)
+    };
+
+    for ancestor in path.ancestors() {
+        match fs::canonicalize(ancestor) {
+            Ok(mut base) => {
+                // 不存在的部分按字面拼接
+                let rest = path.strip_prefix(ancestor).unwrap_or(Path::new(""));
+                for component in rest.components() {
+                    match component {
+                        Component::ParentDir => {
+                            base.pop();
+                        }
+                        Component::Normal(name) => base.push(name),
+                        _ => {}
+                    }
+                }
+                return Ok(base);
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(Error::io(format!("Failed to canonicalize {ancestor:?}"), e)),
+        }
+    }
+    Err(Error::io(format!("Failed to canonicalize {path:?}"), io::Error::new(io::ErrorKind::NotFound, "no existing ancestor")))
+}
+
+/// `copy_file_with` 和 `move_file_with` 的选项
+#[derive(Debug, Clone, Copy)]
+pub struct CopyOptions {
+    /// 目标文件已存在时是否覆盖，不覆盖时返回错误
+    pub overwrite: bool,
+    /// 每次读写的字节数，每写完一块调用一次进度回调
+    pub chunk_size: usize,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self { overwrite: false, chunk_size: 1024 * 1024 }
+    }
+}
+
+/// 复制进度回调，参数依次为已复制的字节数和总字节数
+pub type Progress<'a> = Option<&'a mut dyn FnMut(u64, u64)>;
+
+/// 分块复制文件并回调进度，返回复制的字节数
+///
+/// 目标文件的父目录不存在时会自动创建，目标文件已存在时返回错误，复制失败时会删除写了一部分的目标文件
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let dir = std::env::temp_dir().join("lazytool-copy-file-doc");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("01.mkv"), "0123456789").unwrap();
+///
+/// let mut calls = 0;
+/// let mut progress = |copied: u64, total: u64| {
+///     calls += 1;
+///     assert!(copied <= total);
+/// };
+/// let copied = path::copy_file(dir.join("01.mkv"), dir.join("backup/01.mkv"), Some(&mut progress)).unwrap();
+/// assert_eq!(copied, 10);
+/// assert!(calls > 0);
+/// assert!(path::copy_file(dir.join("01.mkv"), dir.join("backup/01.mkv"), None).is_err());
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn copy_file<P, Q>(src: P, dst: Q, progress: Progress) -> Result<u64>
+    where P: AsRef<Path>,
+          Q: AsRef<Path>,
+{
+    copy_file_with(src, dst, CopyOptions::default(), progress)
+}
+
+/// 按 `CopyOptions` 分块复制文件并回调进度，返回复制的字节数
+pub fn copy_file_with<P, Q>(src: P, dst: Q, opts: CopyOptions, mut progress: Progress) -> Result<u64>
+    where P: AsRef<Path>,
+          Q: AsRef<Path>,
+{
+    let src = expand_user(src);
+    let mut reader = fs::File::open(&src).map_err(|e| Error::io(format!("Failed to open {src:?}"), e))?;
+    let metadata = reader.metadata()?;
+    if !metadata.is_file() {
+        return Err(Error::NotAFile(src));
+    }
+    let total = metadata.len();
+
+    let dst = ensure_parent(dst)?;
+    if !opts.overwrite && (dst.exists() || dst.is_symlink()) {
+        return Err(Error::AlreadyExists(dst));
+    }
+    let mut writer = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .create_new(!opts.overwrite)
+        .open(&dst)
+        .map_err(|e| Error::io(format!("Failed to create {dst:?}"), e))?;
+    // 写了一部分的目标文件需要删除
+    let guard = RemoveGuard(dst);
+
+    let mut buf = vec![0u8; opts.chunk_size.max(1)];
+    let mut copied = 0;
+    if let Some(progress) = progress.as_mut() {
+        progress(copied, total);
+    }
+    loop {
+        let n = match io::Read::read(&mut reader, &mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Error::io(format!("Failed to read {src:?}"), e)),
+        };
+        writer.write_all(&buf[..n]).map_err(|e| Error::io(format!("Failed to write {:?}", guard.0), e))?;
+        copied += n as u64;
+        if let Some(progress) = progress.as_mut() {
+            progress(copied, total);
+        }
+    }
+    writer.set_permissions(metadata.permissions())?;
+    writer.sync_all()?;
+    std::mem::forget(guard);
+    Ok(copied)
+}
+
+/// 移动文件，优先使用 `rename`，跨文件系统时改为复制、校验大小后删除源文件
+///
+/// 目标文件的父目录不存在时会自动创建，目标文件已存在时返回错误，仅在复制时回调进度
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let dir = std::env::temp_dir().join("lazytool-move-file-doc");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("01.mkv"), "0123456789").unwrap();
+///
+/// path::move_file(dir.join("01.mkv"), dir.join("S01/01.mkv"), None).unwrap();
+/// assert!(!dir.join("01.mkv").exists());
+/// assert_eq!(std::fs::read_to_string(dir.join("S01/01.mkv")).unwrap(), "0123456789");
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn move_file<P, Q>(src: P, dst: Q, progress: Progress) -> Result<u64>
+    where P: AsRef<Path>,
+          Q: AsRef<Path>,
+{
+    move_file_with(src, dst, CopyOptions::default(), progress)
+}
+
+/// 按 `CopyOptions` 移动文件，返回文件的字节数
+pub fn move_file_with<P, Q>(src: P, dst: Q, opts: CopyOptions, progress: Progress) -> Result<u64>
+    where P: AsRef<Path>,
+          Q: AsRef<Path>,
+{
+    let src = expand_user(src);
+    let metadata = fs::metadata(&src).map_err(|e| Error::io(format!("Failed to read metadata of {src:?}"), e))?;
+    if !metadata.is_file() {
+        return Err(Error::NotAFile(src));
+    }
+    let dst = ensure_parent(dst)?;
+    if !opts.overwrite && (dst.exists() || dst.is_symlink()) {
+        return Err(Error::AlreadyExists(dst));
+    }
+
+    match fs::rename(&src, &dst) {
+        Ok(()) => Ok(metadata.len()),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => move_by_copy(&src, &dst, opts, progress),
+        Err(e) => Err(Error::io(format!("Failed to move {src:?} to {dst:?}"), e)),
+    }
+}
+
+/// 复制后校验大小再删除源文件
+fn move_by_copy(src: &Path, dst: &Path, opts: CopyOptions, progress: Progress) -> Result<u64> {
+    let copied = copy_file_with(src, dst, opts, progress)?;
+    let expected = fs::metadata(src)?.len();
+    let actual = fs::metadata(dst)?.len();
+    if copied != expected || actual != expected {
+        let _ = fs::remove_file(dst);
+        return Err(Error::SizeMismatch { path: dst.to_path_buf(), expected, actual });
+    }
+    fs::remove_file(src).map_err(|e| Error::io(format!("Failed to remove {src:?} after copying"), e))?;
+    Ok(copied)
+}
+
+/// `backup_file_with` 的选项
+#[derive(Debug, Clone)]
+pub struct BackupOptions {
+    /// 备份文件名中的时间格式，需要能按字符串顺序排列出时间先后，不能包含用于分隔序号的 `~`
+    pub format: String,
+    /// 最多保留的备份数量，超出时删除最旧的备份
+    pub max_backups: Option<usize>,
+}
+
+impl Default for BackupOptions {
+    fn default() -> Self {
+        Self { format: "%Y%m%dT%H%M%S".to_string(), max_backups: None }
+    }
+}
+
+/// 在同目录下创建带时间戳的备份，如 `foo.toml` 备份为 `foo.toml.20250115T181613.bak`，返回备份文件路径
+///
+/// 同一秒内多次备份时追加序号，如 `foo.toml.20250115T181613~1.bak`，
+/// 文件不存在或不是文件时返回 `Error::NotAFile`，与读写失败的 `Error::Io` 区分
+///
+/// Examples
+///
+/// ```
+/// use lazytool::{path, Error};
+///
+/// let dir = std::env::temp_dir().join("lazytool-backup-file-doc");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("foo.toml"), "a = 1").unwrap();
+///
+/// let backup = path::backup_file(dir.join("foo.toml")).unwrap();
+/// assert_eq!(std::fs::read_to_string(&backup).unwrap(), "a = 1");
+/// assert_eq!(path::latest_backup(dir.join("foo.toml")).unwrap(), Some(backup));
+///
+/// let err = path::backup_file(dir.join("missing.toml")).unwrap_err();
+/// assert!(matches!(err, Error::NotAFile(_)));
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn backup_file<P: AsRef<Path>>(p: P) -> Result<PathBuf> {
+    backup_file_with(p, BackupOptions::default())
+}
+
+/// 按 `BackupOptions` 创建带时间戳的备份
+pub fn backup_file_with<P: AsRef<Path>>(p: P, opts: BackupOptions) -> Result<PathBuf> {
+    let path = expand_user(p);
+    if !path.is_file() {
+        return Err(Error::NotAFile(path));
+    }
+    let name = path.file_name()
+        .ok_or_else(|| Error::InvalidPath(path.clone()))?
+        .to_string_lossy()
+        .into_owned();
+
+    let now = time::Timestamp::detect(time::current_timestamp() as i64).to_datetime()?;
+    let stamp = now.format(&opts.format).to_string();
+    if stamp.contains(BACKUP_SEQ_SEPARATOR) {
+        return Err(Error::InvalidPattern {
+            pattern: opts.format,
+            reason: format!("backup time format must not contain {BACKUP_SEQ_SEPARATOR:?}"),
+        });
+    }
+    let mut content = fs::File::open(&path).map_err(|e| Error::io(format!("Failed to open {path:?}"), e))?;
+
+    // 同一秒内的备份追加序号，序号接在已有的最大序号之后，保证按序号排列时仍是从旧到新
+    let mut n = list_backups(&path)?
+        .iter()
+        .filter_map(|b| backup_key(b, &name))
+        .filter(|(s, _)| *s == stamp)
+        .map(|(_, n)| n + 1)
+        .max()
+        .unwrap_or(0);
+    let mut backup;
+    let mut file = loop {
+        let suffix = if n == 0 { stamp.clone() } else { format!("{stamp}{BACKUP_SEQ_SEPARATOR}{n}") };
+        backup = path.with_file_name(format!("{name}.{suffix}.bak"));
+        match fs::OpenOptions::new().write(true).create_new(true).open(&backup) {
+            Ok(file) => break file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(Error::io(format!("Failed to create backup {backup:?}"), e)),
+        }
+    };
+    if let Err(e) = io::copy(&mut content, &mut file) {
+        let _ = fs::remove_file(&backup);
+        return Err(Error::io(format!("Failed to write backup {backup:?}"), e));
+    }
+
+    if let Some(max) = opts.max_backups {
+        let backups = list_backups(&path)?;
+        let excess = backups.len().saturating_sub(max);
+        for old in &backups[..excess] {
+            fs::remove_file(old).map_err(|e| Error::io(format!("Failed to remove old backup {old:?}"), e))?;
+        }
+    }
+    Ok(backup)
+}
+
+/// 获取最新的备份文件，没有备份时返回 `None`
+pub fn latest_backup<P: AsRef<Path>>(p: P) -> Result<Option<PathBuf>> {
+    Ok(list_backups(&expand_user(p))?.pop())
+}
+
+/// 同目录下的备份文件，按时间从旧到新排列
+fn list_backups(path: &Path) -> Result<Vec<PathBuf>> {
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => return Ok(vec![]),
+    };
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut backups = vec![];
+    for entry in fs::read_dir(dir).map_err(|e| Error::io(format!("Failed to read directory {dir:?}"), e))? {
+        let backup = dir.join(entry?.file_name());
+        if let Some(key) = backup_key(&backup, &name) {
+            backups.push((key, backup));
+        }
+    }
+    backups.sort();
+    Ok(backups.into_iter().map(|(_, path)| path).collect())
+}
+
+/// 备份文件名中时间与同一秒内序号的分隔符，时间格式中不允许出现
+const BACKUP_SEQ_SEPARATOR: char = '~';
+
+/// 从备份文件名中拆出时间和同一秒内追加的序号，不是 `name` 的备份时返回 `None`
+fn backup_key(backup: &Path, name: &str) -> Option<(String, usize)> {
+    let file_name = backup.file_name()?.to_str()?;
+    let stamp = file_name.strip_prefix(name)?.strip_prefix('.')?.strip_suffix(".bak")?;
+    if stamp.is_empty() {
+        return None;
+    }
+    match stamp.split_once(BACKUP_SEQ_SEPARATOR) {
+        Some((stamp, n)) => Some((stamp.to_string(), n.parse().ok()?)),
+        None => Some((stamp.to_string(), 0)),
+    }
+}
+
+/// 原子地写入文件
+///
+/// 先写入同目录下的临时文件并落盘，再重命名覆盖目标文件，写入过程中崩溃不会留下写了一半的文件，
+/// 目标文件已存在时保留其权限，出错时会删除临时文件
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let file = std::env::temp_dir().join("lazytool-atomic-write-doc.json");
+/// path::atomic_write(&file, b"{}").unwrap();
+/// assert_eq!(std::fs::read_to_string(&file).unwrap(), "{}");
+///
+/// std::fs::remove_file(&file).unwrap();
+/// ```
+pub fn atomic_write<P: AsRef<Path>>(p: P, contents: impl AsRef<[u8]>) -> Result<()> {
+    atomic_write_with(p, |w| Ok(w.write_all(contents.as_ref())?))
+}
+
+/// 原子地写入字符串
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let file = std::env::temp_dir().join("lazytool-atomic-write-str-doc.txt");
+/// path::atomic_write_str(&file, "hello").unwrap();
+/// assert_eq!(std::fs::read_to_string(&file).unwrap(), "hello");
+///
+/// std::fs::remove_file(&file).unwrap();
+/// ```
+pub fn atomic_write_str<P: AsRef<Path>>(p: P, contents: &str) -> Result<()> {
+    atomic_write(p, contents)
+}
+
+/// 通过回调流式写入内容，适合较大的文件，回调返回错误时目标文件保持不变
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let file = std::env::temp_dir().join("lazytool-atomic-write-with-doc.txt");
+/// path::atomic_write_with(&file, |w| {
+///     for i in 0..3 {
+///         writeln!(w, "line {i}")?;
+///     }
+///     Ok(())
+/// }).unwrap();
+/// assert_eq!(std::fs::read_to_string(&file).unwrap(), "line 0\nline 1\nline 2\n");
+///
+/// std::fs::remove_file(&file).unwrap();
+/// ```
+pub fn atomic_write_with<P, F>(p: P, f: F) -> Result<()>
+    where P: AsRef<Path>,
+          F: FnOnce(&mut dyn Write) -> Result<()>,
+{
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let path = expand_user(p);
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let name = path.file_name()
+        .ok_or_else(|| Error::InvalidPath(path.clone()))?
+        .to_string_lossy()
+        .into_owned();
+
+    // 临时文件与目标文件位于同一目录，保证重命名是原子的
+    let (tmp_path, file) = loop {
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let candidate = dir.join(format!(".{name}.{}.{n}.tmp", std::process::id()));
+        match fs::OpenOptions::new().write(true).create_new(true).open(&candidate) {
+            Ok(file) => break (candidate, file),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(Error::io(format!("Failed to create temp file {candidate:?}"), e)),
+        }
+    };
+    let guard = RemoveGuard(tmp_path);
+
+    {
+        let mut writer = io::BufWriter::new(file);
+        f(&mut writer)?;
+        let file = writer.into_inner().map_err(|e| Error::io(format!("Failed to write {:?}", guard.0), e.into_error()))?;
+        if let Ok(meta) = fs::metadata(&path) {
+            file.set_permissions(meta.permissions())?;
+        }
+        file.sync_all()?;
+    }
+
+    fs::rename(&guard.0, &path).map_err(|e| Error::io(format!("Failed to rename {:?} to {path:?}", guard.0), e))?;
+    std::mem::forget(guard);
+    Ok(())
+}
+
+/// 离开作用域时删除文件，操作成功后通过 `mem::forget` 保留
+struct RemoveGuard(PathBuf);
+
+impl Drop for RemoveGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// 生成临时文件或目录的名称，进程号、时间和计数保证唯一
+fn temp_name(prefix: &str, ext: Option<&str>) -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let name = format!("{prefix}{}-{nanos:x}-{n}", std::process::id());
+    match ext.map(|ext| ext.trim_start_matches('.')) {
+        Some(ext) if !ext.is_empty() => format!("{name}.{ext}"),
+        _ => name,
+    }
+}
+
+/// 临时目录，离开作用域时连同其中的内容一起删除
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path::TempDir;
+///
+/// let path = {
+///     let dir = TempDir::new("lazytool-").unwrap();
+///     std::fs::write(dir.child("01.mp4"), "").unwrap();
+///     assert!(dir.child("01.mp4").is_file());
+///     dir.path().to_path_buf()
+/// };
+/// assert!(!path.exists());
+///
+/// // 保留目录
+/// let path = TempDir::new("lazytool-").unwrap().into_path();
+/// assert!(path.is_dir());
+/// std::fs::remove_dir(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl TempDir {
+    /// 在系统临时目录下创建以 `prefix` 开头的目录
+    pub fn new(prefix: &str) -> Result<Self> {
+        Self::new_in(env::temp_dir(), prefix)
+    }
+
+    /// 在 `base` 下创建以 `prefix` 开头的目录，`base` 需要已经存在
+    pub fn new_in<P: AsRef<Path>>(base: P, prefix: &str) -> Result<Self> {
+        let base = base.as_ref();
+        loop {
+            let path = base.join(temp_name(prefix, None));
+            // `create_dir` 在目录已存在时失败，不会与其他进程冲突
+            match fs::create_dir(&path) {
+                Ok(()) => return Ok(Self { path, keep: false }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(Error::io(format!("Failed to create temp dir in {base:?}"), e)),
+            }
+        }
+    }
+
+    /// 目录路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 目录下的路径，不会创建
+    pub fn child<P: AsRef<Path>>(&self, name: P) -> PathBuf {
+        self.path.join(name)
+    }
+
+    /// 离开作用域时不再删除
+    pub fn keep(&mut self) {
+        self.keep = true;
+    }
+
+    /// 不再自动删除并返回目录路径
+    pub fn into_path(mut self) -> PathBuf {
+        self.keep = true;
+        std::mem::take(&mut self.path)
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if !self.keep {
+            // 目录可能已被删除，忽略错误
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+/// 临时文件，离开作用域时删除
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path::TempFile;
+///
+/// let path = {
+///     let file = TempFile::new("lazytool-", Some("json")).unwrap();
+///     std::fs::write(file.path(), "{}").unwrap();
+///     assert_eq!(file.path().extension().unwrap(), "json");
+///     file.path().to_path_buf()
+/// };
+/// assert!(!path.exists());
+/// ```
+#[derive(Debug)]
+pub struct TempFile {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl TempFile {
+    /// 在系统临时目录下创建以 `prefix` 开头、扩展名为 `ext` 的空文件
+    pub fn new(prefix: &str, ext: Option<&str>) -> Result<Self> {
+        Self::new_in(env::temp_dir(), prefix, ext)
+    }
+
+    /// 在 `base` 下创建以 `prefix` 开头、扩展名为 `ext` 的空文件，`base` 需要已经存在
+    pub fn new_in<P: AsRef<Path>>(base: P, prefix: &str, ext: Option<&str>) -> Result<Self> {
+        let base = base.as_ref();
+        loop {
+            let path = base.join(temp_name(prefix, ext));
+            match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Self { path, keep: false }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(Error::io(format!("Failed to create temp file in {base:?}"), e)),
+            }
+        }
+    }
+
+    /// 文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 离开作用域时不再删除
+    pub fn keep(&mut self) {
+        self.keep = true;
+    }
+
+    /// 不再自动删除并返回文件路径
+    pub fn into_path(mut self) -> PathBuf {
+        self.keep = true;
+        std::mem::take(&mut self.path)
+    }
+}
+
+impl AsRef<Path> for TempFile {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.keep {
+            // 文件可能已被删除，忽略错误
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// `unique_path_with` 使用的序号样式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SuffixStyle {
+    /// `report (1).pdf`
+    #[default]
+    Paren,
+    /// `report_1.pdf`
+    Underscore,
+}
+
+impl SuffixStyle {
+    fn apply(&self, stem: &str, n: usize) -> String {
+        match self {
+            Self::Paren => format!("{stem} ({n})"),
+            Self::Underscore => format!("{stem}_{n}"),
+        }
+    }
+}
+
+/// 获取一个不会覆盖已有文件的路径
+///
+/// 路径不存在时原样返回，否则在扩展名前追加递增的序号，如 `report (1).pdf`、`report (2).pdf`，
+/// `.tar.gz` 这类多段扩展名会整体保留在序号之后
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let dir = std::env::temp_dir().join("lazytool-unique-path-doc");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("report.pdf"), "").unwrap();
+///
+/// assert_eq!(path::unique_path(dir.join("report.pdf")), dir.join("report (1).pdf"));
+/// assert_eq!(path::unique_path(dir.join("other.pdf")), dir.join("other.pdf"));
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn unique_path<P: AsRef<Path>>(p: P) -> PathBuf {
+    unique_path_with(p, SuffixStyle::default())
+}
+
+/// 使用指定的序号样式获取不会覆盖已有文件的路径
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path::{self, SuffixStyle};
+///
+/// let dir = std::env::temp_dir().join("lazytool-unique-path-with-doc");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("backup.tar.gz"), "").unwrap();
+///
+/// assert_eq!(path::unique_path_with(dir.join("backup.tar.gz"), SuffixStyle::Underscore), dir.join("backup_1.tar.gz"));
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn unique_path_with<P: AsRef<Path>>(p: P, style: SuffixStyle) -> PathBuf {
+    let path = p.as_ref();
+    if !path.exists() && !path.is_symlink() {
+        return path.to_path_buf();
+    }
+    (1..)
+        .map(|n| numbered_path(path, style, n))
+        .find(|candidate| !candidate.exists() && !candidate.is_symlink())
+        .expect("unbounded range always yields a candidate")
+}
+
+/// 原子地创建一个不会覆盖已有文件的新文件，返回实际路径和文件句柄
+///
+/// `unique_path` 在检查和创建之间存在竞争，这里使用 `create_new` 在冲突时继续尝试下一个序号
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let dir = std::env::temp_dir().join("lazytool-create-unique-doc");
+/// std::fs::create_dir_all(&dir).unwrap();
+///
+/// let (first, _) = path::create_unique(dir.join("a.txt")).unwrap();
+/// let (second, _) = path::create_unique(dir.join("a.txt")).unwrap();
+/// assert_eq!(first, dir.join("a.txt"));
+/// assert_eq!(second, dir.join("a (1).txt"));
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn create_unique<P: AsRef<Path>>(p: P) -> Result<(PathBuf, fs::File)> {
+    let path = p.as_ref();
+    for n in 0.. {
+        let candidate = match n {
+            0 => path.to_path_buf(),
+            n => numbered_path(path, SuffixStyle::default(), n),
+        };
+        match fs::OpenOptions::new().write(true).create_new(true).open(&candidate) {
+            Ok(file) => return Ok((candidate, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(Error::io(format!("Failed to create file {candidate:?}"), e)),
+        }
+    }
+    unreachable!()
+}
+
+/// 在扩展名前插入序号
+fn numbered_path(path: &Path, style: SuffixStyle, n: usize) -> PathBuf {
+    let name = path.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let (stem, ext) = split_name(&name);
+    path.with_file_name(format!("{}{ext}", style.apply(stem, n)))
+}
+
+/// 将文件名拆分为主干和扩展名（包含 `.`）
+fn split_name(name: &str) -> (&str, &str) {
+    let (stem, _) = split_extension(name, COMPOUND_EXTENSIONS);
+    (stem, &name[stem.len()..])
+}
+
+/// 获取完整的扩展名，`backup.tar.gz` 返回 `tar.gz`，隐藏文件如 `.bashrc` 没有扩展名
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// assert_eq!(path::full_extension("/backup/db.tar.gz"), Some("tar.gz".to_string()));
+/// assert_eq!(path::full_extension("/影片/01.MKV"), Some("MKV".to_string()));
+/// assert_eq!(path::full_extension("/root/.bashrc"), None);
+/// assert_eq!(path::full_extension("/root/README"), None);
+/// ```
+pub fn full_extension<P: AsRef<Path>>(p: P) -> Option<String> {
+    full_extension_with(p, COMPOUND_EXTENSIONS)
+}
+
+/// 使用自定义的多段扩展名列表获取完整的扩展名
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let mut compound = path::COMPOUND_EXTENSIONS.to_vec();
+/// compound.push("zh.srt");
+/// assert_eq!(path::full_extension_with("/影片/01.zh.srt", &compound), Some("zh.srt".to_string()));
+/// assert_eq!(path::full_extension("/影片/01.zh.srt"), Some("srt".to_string()));
+/// ```
+pub fn full_extension_with<P, S>(p: P, compound: &[S]) -> Option<String>
+    where P: AsRef<Path>,
+          S: AsRef<str>,
+{
+    let name = p.as_ref().file_name()?.to_string_lossy();
+    split_extension(&name, compound).1.map(str::to_string)
+}
+
+/// 去掉完整扩展名后的文件名
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// assert_eq!(path::stem("/backup/db.tar.gz"), "db");
+/// assert_eq!(path::stem("/影片/还珠格格S01E02.mp4"), "还珠格格S01E02");
+/// assert_eq!(path::stem("/root/.bashrc"), ".bashrc");
+/// ```
+pub fn stem<P: AsRef<Path>>(p: P) -> String {
+    let name = match p.as_ref().file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => return String::new(),
+    };
+    split_extension(&name, COMPOUND_EXTENSIONS).0.to_string()
+}
+
+/// 替换完整的扩展名，`ext` 可带 `.` 前缀，为空时去掉扩展名
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::path::PathBuf;
+///
+/// assert_eq!(path::with_full_extension("/backup/db.tar.gz", "zip"), PathBuf::from("/backup/db.zip"));
+/// assert_eq!(path::with_full_extension("/backup/db.zip", ".tar.xz"), PathBuf::from("/backup/db.tar.xz"));
+/// assert_eq!(path::with_full_extension("/root/.bashrc", "bak"), PathBuf::from("/root/.bashrc.bak"));
+/// assert_eq!(path::with_full_extension("/影片/01.mp4", ""), PathBuf::from("/影片/01"));
+/// ```
+pub fn with_full_extension<P: AsRef<Path>>(p: P, ext: &str) -> PathBuf {
+    let path = p.as_ref();
+    let stem = stem(path);
+    let ext = ext.trim_start_matches('.');
+    let name = if ext.is_empty() { stem } else { format!("{stem}.{ext}") };
+    path.with_file_name(name)
+}
+
+/// 扩展名是否在列表中，不区分大小写，列表中的扩展名可带 `.` 前缀，也可以是 `tar.gz` 这样的多段扩展名
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// assert!(path::has_extension("/影片/01.MKV", &["mp4", "mkv"]));
+/// assert!(path::has_extension("/backup/db.tar.gz", &[".tar.gz"]));
+/// assert!(path::has_extension("/backup/db.tar.gz", &["gz"]));
+/// assert!(!path::has_extension("/root/.mkv", &["mkv"]));
+/// ```
+pub fn has_extension<P, S>(p: P, exts: &[S]) -> bool
+    where P: AsRef<Path>,
+          S: AsRef<str>,
+{
+    let name = match p.as_ref().file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => return false,
+    };
+    let simple = split_extension::<&str>(&name, &[]).1;
+    let full = split_extension(&name, exts).1;
+    exts.iter()
+        .map(|e| e.as_ref().trim_start_matches('.'))
+        .any(|e| [simple, full].iter().flatten().any(|ext| ext.eq_ignore_ascii_case(e)))
+}
+
+/// `walk_files` 的选项
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    /// 只保留这些扩展名的文件，不区分大小写，可带 `.` 前缀
+    pub extensions: Option<Vec<String>>,
+    /// 最大深度，根目录下的直接子项深度为 1
+    pub max_depth: Option<usize>,
+    /// 是否包含隐藏文件和目录
+    pub include_hidden: bool,
+    /// 是否跟随符号链接，不跟随时符号链接会被跳过
+    pub follow_symlinks: bool,
+    /// 文件最小字节数
+    pub min_size: Option<u64>,
+}
+
+/// 遍历时跳过的路径及其错误
+pub type WalkWarning = (PathBuf, io::Error);
+
+/// 递归列出目录下的文件，结果按路径排序
+///
+/// 子目录无法读取时跳过该目录继续遍历，需要获取这些错误时使用 `walk_files_with_warnings`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path::{self, WalkOptions};
+///
+/// let dir = std::env::temp_dir().join("lazytool-walk-files-doc");
+/// std::fs::create_dir_all(dir.join("S01")).unwrap();
+/// std::fs::write(dir.join("S01/01.MP4"), "").unwrap();
+/// std::fs::write(dir.join("S01/01.srt"), "").unwrap();
+///
+/// let opts = WalkOptions { extensions: Some(vec!["mp4".to_string()]), ..Default::default() };
+/// let files = path::walk_files(&dir, opts).unwrap();
+/// assert_eq!(files, vec![dir.join("S01/01.MP4")]);
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn walk_files<P: AsRef<Path>>(dir: P, opts: WalkOptions) -> Result<Vec<PathBuf>> {
+    let (files, _) = walk_files_with_warnings(dir, opts)?;
+    Ok(files)
+}
+
+/// 递归列出目录下不超过 `max_depth` 层的文件，跳过隐藏的文件和目录，结果按路径排序
+///
+/// 根目录下的直接子项深度为 1，`max_depth` 为 0 或 1 时都只列出根目录下的文件。
+/// 与 `Episode::scan_dir` 使用相同的遍历逻辑，需要过滤扩展名等更多条件时使用 `walk_files`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path::{self, TempDir};
+///
+/// let tmp = TempDir::new("lazytool-walk-with-depth-").unwrap();
+/// let dir = tmp.path();
+/// std::fs::create_dir_all(dir.join("电视剧/还珠格格/S01")).unwrap();
+/// std::fs::write(dir.join("电视剧/还珠格格/poster.jpg"), "").unwrap();
+/// std::fs::write(dir.join("电视剧/还珠格格/S01/01.mp4"), "").unwrap();
+///
+/// let files = path::walk_with_depth(dir, 3).unwrap();
+/// assert_eq!(files, vec![dir.join("电视剧/还珠格格/poster.jpg")]);
+///
+/// let files = path::walk_with_depth(dir, 4).unwrap();
+/// assert_eq!(files.len(), 2);
+/// ```
+pub fn walk_with_depth<P: AsRef<Path>>(root: P, max_depth: usize) -> Result<Vec<PathBuf>> {
+    walk_files(root, WalkOptions { max_depth: Some(max_depth), ..Default::default() })
+}
+
+/// 递归列出目录下的文件，同时返回遍历过程中跳过的目录及其错误
+///
+/// 根目录本身无法读取时返回错误
+pub fn walk_files_with_warnings<P: AsRef<Path>>(dir: P, opts: WalkOptions) -> Result<(Vec<PathBuf>, Vec<WalkWarning>)> {
+    let mut files = vec![];
+    let warnings = walk(dir.as_ref(), &opts, |path, _| files.push(path))?;
+    files.sort();
+    Ok((files, warnings))
+}
+
+/// 遍历目录下符合条件的文件，回调中带上遍历时已获取的元数据，返回跳过的路径及其错误
+fn walk<F>(root: &Path, opts: &WalkOptions, mut visit: F) -> Result<Vec<WalkWarning>>
+    where F: FnMut(PathBuf, &fs::Metadata),
+{
+    let mut warnings = vec![];
+    for item in Walker::new(root, opts.clone())? {
+        match item {
+            Ok((path, metadata)) => visit(path, &metadata),
+            Err(warning) => warnings.push(warning),
+        }
+    }
+    Ok(warnings)
+}
+
+/// 惰性遍历目录，依次产出符合条件的文件及其元数据，无法读取的路径作为错误产出，不会中断遍历
+pub(crate) struct Walker {
+    opts: WalkOptions,
+    /// 已访问的目录，防止跟随符号链接时出现循环
+    visited: HashSet<PathBuf>,
+    /// 待遍历的目录及其深度
+    stack: Vec<(PathBuf, usize)>,
+    /// 正在遍历的目录
+    current: Option<(PathBuf, usize, fs::ReadDir)>,
+}
+
+impl Walker {
+    /// 根目录无法读取时返回错误
+    pub(crate) fn new(root: &Path, opts: WalkOptions) -> io::Result<Self> {
+        let entries = fs::read_dir(root)?;
+        let mut visited = HashSet::new();
+        if let Ok(real) = fs::canonicalize(root) {
+            visited.insert(real);
+        }
+        Ok(Self { opts, visited, stack: vec![], current: Some((root.to_path_buf(), 1, entries)) })
+    }
+
+    /// 处理单个目录项，返回 None 表示跳过
+    fn visit(&mut self, entry: io::Result<fs::DirEntry>, dir: &Path, depth: usize) -> Option<std::result::Result<(PathBuf, fs::Metadata), WalkWarning>> {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => return Some(Err((dir.to_path_buf(), e))),
+        };
+        let path = entry.path();
+        if !self.opts.include_hidden && is_hidden(&path) {
+            return None;
+        }
+
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(e) => return Some(Err((path, e))),
+        };
+        let metadata = if file_type.is_symlink() {
+            if !self.opts.follow_symlinks {
+                return None;
+            }
+            fs::metadata(&path)
+        } else {
+            entry.metadata()
+        };
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => return Some(Err((path, e))),
+        };
+
+        if metadata.is_dir() {
+            if self.opts.max_depth.is_some_and(|max| depth >= max) {
+                return None;
+            }
+            if self.opts.follow_symlinks {
+                match fs::canonicalize(&path) {
+                    Ok(real) => {
+                        if !self.visited.insert(real) {
+                            return None;
+                        }
+                    }
+                    Err(e) => return Some(Err((path, e))),
+                }
+            }
+            self.stack.push((path, depth + 1));
+            None
+        } else if metadata.is_file() {
+            if self.opts.min_size.is_some_and(|min| metadata.len() < min) {
+                return None;
+            }
+            if self.opts.extensions.as_ref().is_some_and(|exts| !has_extension(&path, exts)) {
+                return None;
+            }
+            Some(Ok((path, metadata)))
+        } else {
+            None
+        }
+    }
+}
+
+impl Iterator for Walker {
+    type Item = std::result::Result<(PathBuf, fs::Metadata), WalkWarning>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (dir, depth, mut entries) = match self.current.take() {
+                Some(current) => current,
+                None => {
+                    let (dir, depth) = self.stack.pop()?;
+                    match fs::read_dir(&dir) {
+                        Ok(entries) => (dir, depth, entries),
+                        Err(e) => return Some(Err((dir, e))),
+                    }
+                }
+            };
+            while let Some(entry) = entries.next() {
+                if let Some(item) = self.visit(entry, &dir, depth) {
+                    self.current = Some((dir, depth, entries));
+                    return Some(item);
+                }
+            }
+        }
+    }
+}
+
+/// 文件字节数
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let file = std::env::temp_dir().join("lazytool-file-size-doc.txt");
+/// std::fs::write(&file, "hello").unwrap();
+/// assert_eq!(path::file_size(&file).unwrap(), 5);
+///
+/// std::fs::remove_file(&file).unwrap();
+/// ```
+pub fn file_size<P: AsRef<Path>>(p: P) -> Result<u64> {
+    let path = expand_user(p);
+    let metadata = fs::metadata(&path).map_err(|e| Error::io(format!("Failed to read metadata of {path:?}"), e))?;
+    Ok(metadata.len())
+}
+
+/// 递归统计目录下所有文件（包括隐藏文件）的字节数，不跟随符号链接，无法读取的子目录会被跳过
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let dir = std::env::temp_dir().join("lazytool-dir-size-doc");
+/// std::fs::create_dir_all(dir.join("S01")).unwrap();
+/// std::fs::write(dir.join("S01/01.mp4"), "0123456789").unwrap();
+/// std::fs::write(dir.join(".nfo"), "01234").unwrap();
+/// assert_eq!(path::dir_size(&dir).unwrap(), 15);
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn dir_size<P: AsRef<Path>>(p: P) -> Result<u64> {
+    let opts = WalkOptions { include_hidden: true, ..Default::default() };
+    let (size, _) = dir_size_with_warnings(p, opts)?;
+    Ok(size)
+}
+
+/// 按 `WalkOptions` 统计目录下文件的字节数，同时返回遍历过程中跳过的路径及其错误
+pub fn dir_size_with_warnings<P: AsRef<Path>>(p: P, opts: WalkOptions) -> Result<(u64, Vec<WalkWarning>)> {
+    let mut size = 0;
+    let warnings = walk(&expand_user(p), &opts, |_, metadata| size += metadata.len())?;
+    Ok((size, warnings))
+}
+
+/// `human_size_with` 使用的单位制
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SizeUnits {
+    /// 以 1000 为进制，`KB`、`MB`、`GB`
+    #[default]
+    Decimal,
+    /// 以 1024 为进制，`KiB`、`MiB`、`GiB`
+    Binary,
+}
+
+/// 将字节数转为易读的形式，以 1000 为进制
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// assert_eq!(path::human_size(87), "87 B");
+/// assert_eq!(path::human_size(523_000_000), "523 MB");
+/// assert_eq!(path::human_size(1_400_000_000), "1.4 GB");
+/// ```
+pub fn human_size(bytes: u64) -> String {
+    human_size_with(bytes, SizeUnits::Decimal)
+}
+
+/// 按指定的单位制将字节数转为易读的形式，小于 10 时保留一位小数
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path::{self, SizeUnits};
+///
+/// assert_eq!(path::human_size_with(1023, SizeUnits::Binary), "1023 B");
+/// assert_eq!(path::human_size_with(1024, SizeUnits::Binary), "1.0 KiB");
+/// assert_eq!(path::human_size_with(1536 * 1024 * 1024, SizeUnits::Binary), "1.5 GiB");
+/// ```
+pub fn human_size_with(bytes: u64, units: SizeUnits) -> String {
+    let (base, names) = match units {
+        SizeUnits::Decimal => (1000.0, ["B", "KB", "MB", "GB", "TB", "PB", "EB"]),
+        SizeUnits::Binary => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]),
+    };
+    if (bytes as f64) < base {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    // 四舍五入后达到进制时进位，避免出现 `1000 KB`
+    while unit + 1 < names.len() && value.round() >= base {
+        value /= base;
+        unit += 1;
+    }
+    if value < 9.95 {
+        format!("{value:.1} {}", names[unit])
+    } else {
+        format!("{value:.0} {}", names[unit])
+    }
+}
+
+/// 文件或目录的易读大小，目录会递归统计
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let file = std::env::temp_dir().join("lazytool-human-file-size-doc.txt");
+/// std::fs::write(&file, vec![0u8; 2048]).unwrap();
+/// assert_eq!(path::human_file_size(&file).unwrap(), "2.0 KB");
+///
+/// std::fs::remove_file(&file).unwrap();
+/// ```
+pub fn human_file_size<P: AsRef<Path>>(p: P) -> Result<String> {
+    let path = expand_user(p);
+    let size = if path.is_dir() { dir_size(&path)? } else { file_size(&path)? };
+    Ok(human_size(size))
+}
+
+/// `find_duplicates` 的选项
+#[derive(Debug, Clone)]
+pub struct DuplicateOptions {
+    /// 只比较这些扩展名的文件，不区分大小写，可带 `.` 前缀
+    pub extensions: Option<Vec<String>>,
+    /// 文件最小字节数
+    pub min_size: Option<u64>,
+    /// 是否包含隐藏文件和目录
+    pub include_hidden: bool,
+    /// 是否把空文件归为一组，空文件内容都相同，默认跳过
+    pub include_empty: bool,
+    /// 完整计算哈希前先比较开头的字节数，为 `None` 时直接计算完整哈希
+    pub prefix_bytes: Option<u64>,
+}
+
+impl Default for DuplicateOptions {
+    fn default() -> Self {
+        Self {
+            extensions: None,
+            min_size: None,
+            include_hidden: false,
+            include_empty: false,
+            prefix_bytes: Some(64 * 1024),
+        }
+    }
+}
+
+/// 查找目录下内容相同的文件
+///
+/// 先按文件大小分组，再依次比较开头部分和完整内容的哈希，返回两个及以上相同文件组成的分组，
+/// 组内按修改时间从旧到新排列，分组之间按第一个文件的路径排列
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path::{self, DuplicateOptions};
+///
+/// let dir = std::env::temp_dir().join("lazytool-find-duplicates-doc");
+/// std::fs::create_dir_all(dir.join("S01")).unwrap();
+/// std::fs::write(dir.join("01.mp4"), "episode 1").unwrap();
+/// std::fs::write(dir.join("S01/第01集.mp4"), "episode 1").unwrap();
+/// std::fs::write(dir.join("02.mp4"), "episode 2").unwrap();
+///
+/// let groups = path::find_duplicates(&dir, DuplicateOptions::default()).unwrap();
+/// assert_eq!(groups.len(), 1);
+/// assert_eq!(groups[0].len(), 2);
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn find_duplicates<P: AsRef<Path>>(dir: P, opts: DuplicateOptions) -> Result<Vec<Vec<PathBuf>>> {
+    let walk_opts = WalkOptions {
+        extensions: opts.extensions.clone(),
+        min_size: opts.min_size,
+        include_hidden: opts.include_hidden,
+        ..Default::default()
+    };
+    let mut by_size: HashMap<u64, Vec<(PathBuf, std::time::SystemTime)>> = HashMap::new();
+    walk(&expand_user(dir), &walk_opts, |path, metadata| {
+        if metadata.len() == 0 && !opts.include_empty {
+            return;
+        }
+        let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+        by_size.entry(metadata.len()).or_default().push((path, modified));
+    })?;
+
+    let mut groups = vec![];
+    for (size, files) in by_size {
+        if files.len() < 2 {
+            continue;
+        }
+        // 先比较开头部分，文件较小时开头部分就是完整内容
+        let candidates = match opts.prefix_bytes {
+            Some(limit) if limit < size => group_by_hash(files, Some(limit))?,
+            _ => vec![files],
+        };
+        for files in candidates {
+            groups.extend(group_by_hash(files, None)?);
+        }
+    }
+
+    for group in &mut groups {
+        group.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    }
+    let mut groups: Vec<Vec<PathBuf>> = groups.into_iter()
+        .map(|group| group.into_iter().map(|(path, _)| path).collect())
+        .collect();
+    groups.sort();
+    Ok(groups)
+}
+
+/// 按内容哈希对文件分组，只保留两个及以上文件的分组，`limit` 为只读取开头的字节数
+fn group_by_hash<T>(files: Vec<(PathBuf, T)>, limit: Option<u64>) -> Result<Vec<Vec<(PathBuf, T)>>> {
+    let mut by_hash: HashMap<u64, Vec<(PathBuf, T)>> = HashMap::new();
+    for (path, extra) in files {
+        let file = fs::File::open(&path).map_err(|e| Error::io(format!("Failed to open {path:?}"), e))?;
+        let mut reader = io::Read::take(io::BufReader::new(file), limit.unwrap_or(u64::MAX));
+        let mut hasher = DefaultHasher::new();
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let n = match io::Read::read(&mut reader, &mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(Error::io(format!("Failed to read {path:?}"), e)),
+            };
+            hasher.write(&buf[..n]);
+        }
+        by_hash.entry(hasher.finish()).or_default().push((path, extra));
+    }
+    Ok(by_hash.into_values().filter(|group| group.len() > 1).collect())
+}
+
+/// glob 模式中的单个路径层级
+#[derive(Debug, Clone)]
+enum GlobSegment {
+    /// `**`，匹配零个或多个层级
+    Recursive,
+    /// 普通层级
+    Component(Vec<GlobToken>),
+}
+
+#[derive(Debug, Clone)]
+enum GlobToken {
+    Literal(char),
+    /// `?`
+    Any,
+    /// `*`
+    Star,
+    /// `[abc]`、`[a-z]`、`[!abc]`
+    Class { negated: bool, ranges: Vec<(char, char)> },
+}
+
+impl GlobSegment {
+    /// 是否为不含通配符的普通层级
+    fn literal(&self) -> Option<String> {
+        match self {
+            Self::Component(tokens) => tokens.iter()
+                .map(|t| match t {
+                    GlobToken::Literal(c) => Some(*c),
+                    _ => None,
+                })
+                .collect(),
+            Self::Recursive => None,
+        }
+    }
+
+    /// 是否以字面的 `.` 开头，只有这样的层级才能匹配隐藏文件
+    fn allows_hidden(&self) -> bool {
+        matches!(self, Self::Component(tokens) if matches!(tokens.first(), Some(GlobToken::Literal('.'))))
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Recursive => true,
+            Self::Component(tokens) => {
+                if name.starts_with('.') && !self.allows_hidden() {
+                    return false;
+                }
+                let chars: Vec<char> = name.chars().collect();
+                match_tokens(tokens, &chars)
+            }
+        }
+    }
+}
+
+/// 按层级解析 glob 模式，方括号未闭合时返回错误
+fn compile_glob(pattern: &str) -> Result<Vec<GlobSegment>> {
+    let mut segments = vec![];
+    for component in pattern.split('/').filter(|c| !c.is_empty()) {
+        if component == "**" {
+            segments.push(GlobSegment::Recursive);
+            continue;
+        }
+
+        let mut tokens = vec![];
+        let mut chars = component.chars().peekable();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '?' => GlobToken::Any,
+                '*' => GlobToken::Star,
+                '[' => {
+                    let negated = matches!(chars.peek(), Some('!') | Some('^'));
+                    if negated {
+                        chars.next();
+                    }
+                    let mut ranges = vec![];
+                    let mut closed = false;
+                    while let Some(c) = chars.next() {
+                        // 第一个字符为 `]` 时视为普通字符
+                        if c == ']' && !ranges.is_empty() {
+                            closed = true;
+                            break;
+                        }
+                        if chars.peek() == Some(&'-') {
+                            chars.next();
+                            match chars.next() {
+                                Some(']') | None => {
+                                    ranges.push((c, c));
+                                    ranges.push(('-', '-'));
+                                    closed = true;
+                                    break;
+                                }
+                                Some(end) => ranges.push((c, end)),
+                            }
+                        } else {
+                            ranges.push((c, c));
+                        }
+                    }
+                    if !closed {
+                        return Err(Error::InvalidPattern { pattern: pattern.to_string(), reason: "unclosed bracket".to_string() });
+                    }
+                    GlobToken::Class { negated, ranges }
+                }
+                c => GlobToken::Literal(c),
+            };
+            tokens.push(token);
+        }
+        segments.push(GlobSegment::Component(tokens));
+    }
+    Ok(segments)
+}
+
+/// 单个层级的通配符匹配
+fn match_tokens(tokens: &[GlobToken], chars: &[char]) -> bool {
+    match tokens.split_first() {
+        None => chars.is_empty(),
+        Some((GlobToken::Star, rest)) => (0..=chars.len()).any(|i| match_tokens(rest, &chars[i..])),
+        Some((token, rest)) => match chars.split_first() {
+            None => false,
+            Some((c, chars)) => {
+                let matched = match token {
+                    GlobToken::Literal(l) => l == c,
+                    GlobToken::Any => true,
+                    GlobToken::Class { negated, ranges } => {
+                        ranges.iter().any(|(start, end)| (*start..=*end).contains(c)) != *negated
+                    }
+                    GlobToken::Star => unreachable!(),
+                };
+                matched && match_tokens(rest, chars)
+            }
+        },
+    }
+}
+
+/// 多个层级的匹配，`**` 可以匹配零个或多个层级
+fn match_segments(segments: &[GlobSegment], names: &[String]) -> bool {
+    match segments.split_first() {
+        None => names.is_empty(),
+        Some((GlobSegment::Recursive, rest)) => {
+            (0..=names.len()).any(|i| {
+                names[..i].iter().all(|n| !n.starts_with('.')) && match_segments(rest, &names[i..])
+            })
+        }
+        Some((segment, rest)) => match names.split_first() {
+            Some((name, names)) => segment.matches(name) && match_segments(rest, names),
+            None => false,
+        },
+    }
+}
+
+/// 判断路径是否匹配 glob 模式，不访问文件系统
+///
+/// 支持 `*`、`?`、`[abc]`、`[a-z]`、`[!abc]` 以及匹配任意层级的 `**`，
+/// `*` 不会跨越 `/`，以 `.` 开头的隐藏文件只能被以 `.` 开头的层级匹配，模式无效时返回 `false`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+/// use std::path::Path;
+///
+/// assert!(path::glob_match("/Movies/*.mp4", Path::new("/Movies/01.mp4")));
+/// assert!(!path::glob_match("/Movies/*.mp4", Path::new("/Movies/S01/01.mp4")));
+/// assert!(path::glob_match("/Movies/**/*.mp4", Path::new("/Movies/S01/01.mp4")));
+/// assert!(path::glob_match("/Movies/**/*.mp4", Path::new("/Movies/01.mp4")));
+/// assert!(path::glob_match("/Movies/0?.[ma][pk][4v]", Path::new("/Movies/01.mkv")));
+/// assert!(path::glob_match("/Movies/[!0]*.mp4", Path::new("/Movies/11.mp4")));
+/// assert!(!path::glob_match("/Movies/*", Path::new("/Movies/.DS_Store")));
+/// assert!(path::glob_match("/Movies/.*", Path::new("/Movies/.DS_Store")));
+/// assert!(!path::glob_match("/Movies/[0-9.mp4", Path::new("/Movies/1.mp4")));
+/// ```
+pub fn glob_match(pattern: &str, path: &Path) -> bool {
+    let segments = match compile_glob(pattern) {
+        Ok(segments) => segments,
+        Err(_) => return false,
+    };
+    if pattern.starts_with('/') != path.has_root() {
+        return false;
+    }
+    let names: Vec<String> = path.components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    match_segments(&segments, &names)
+}
+
+/// 按 glob 模式查找文件和目录，结果按路径排序
+///
+/// 模式会先经过 `expand_user` 解析 `~`，匹配规则与 `glob_match` 相同，`**` 不会进入符号链接目录，
+/// 模式无效（如方括号未闭合）时返回错误
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path;
+///
+/// let dir = std::env::temp_dir().join("lazytool-glob-doc");
+/// std::fs::create_dir_all(dir.join("S01")).unwrap();
+/// std::fs::write(dir.join("S01/01.mp4"), "").unwrap();
+/// std::fs::write(dir.join("S01/01.srt"), "").unwrap();
+///
+/// let files = path::glob(&format!("{}/**/*.mp4", dir.display())).unwrap();
+/// assert_eq!(files, vec![dir.join("S01/01.mp4")]);
+///
+/// assert!(path::glob(&format!("{}/[0-9.mp4", dir.display())).is_err());
+///
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn glob(pattern: &str) -> Result<Vec<PathBuf>> {
+    let pattern = must_to_string(expand_user(pattern));
+    let segments = compile_glob(&pattern)?;
+    let base = if pattern.starts_with('/') { PathBuf::from("/") } else { PathBuf::new() };
+
+    let mut results = vec![];
+    glob_walk(&base, &segments, &mut results);
+    results.sort();
+    results.dedup();
+    Ok(results)
+}
+
+fn glob_walk(base: &Path, segments: &[GlobSegment], results: &mut Vec<PathBuf>) {
+    let dir = if base.as_os_str().is_empty() { Path::new(".") } else { base };
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            if dir.exists() {
+                results.push(base.to_path_buf());
+            }
+            return;
+        }
+    };
+
+    // 不含通配符的层级直接拼接
+    if let Some(name) = segment.literal() {
+        let path = base.join(name);
+        if rest.is_empty() || path.is_dir() {
+            glob_walk(&path, rest, results);
+        }
+        return;
+    }
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    if matches!(segment, GlobSegment::Recursive) {
+        glob_walk(base, rest, results);
+    }
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = base.join(&name);
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        match segment {
+            GlobSegment::Recursive => {
+                if is_dir && !name.starts_with('.') {
+                    glob_walk(&path, segments, results);
+                }
+            }
+            GlobSegment::Component(_) => {
+                if segment.matches(&name) && (rest.is_empty() || is_dir || path.is_dir()) {
+                    glob_walk(&path, rest, results);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        absolutize, atomic_write, atomic_write_with, backup_file, backup_file_with, canonicalize_lenient,
+        common_prefix, copy_file, copy_file_with, create_unique, dir_size, dir_size_with_warnings, ensure_dir,
+        ensure_parent, expand_vars_strict, file_size, find_duplicates, full_extension, glob, has_extension,
+        human_size, human_size_with, latest_backup, move_by_copy, move_file, relative_path, sanitize_filename,
+        sanitize_filename_with, stem, strip_common_prefix, substitute_vars, try_to_string, unique_path,
+        unique_path_with, walk_files, walk_with_depth, with_full_extension,
+        BackupOptions, CopyOptions, DuplicateOptions, Error, SizeUnits, SuffixStyle, TempDir, TempFile, WalkOptions,
+    };
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_walk_files() {
+        let tmp = TempDir::new("lazytool-walk-").unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("剧集/S01")).unwrap();
+        fs::create_dir_all(dir.join(".hidden")).unwrap();
+        fs::write(dir.join("a.mp4"), "0123456789").unwrap();
+        fs::write(dir.join("剧集/b.MKV"), "01234").unwrap();
+        fs::write(dir.join("剧集/S01/c.mp4"), "0").unwrap();
+        fs::write(dir.join("剧集/S01/c.srt"), "0").unwrap();
+        fs::write(dir.join(".hidden/d.mp4"), "0").unwrap();
+        fs::write(dir.join(".e.mp4"), "0").unwrap();
+
+        let files = walk_files(dir, WalkOptions::default()).unwrap();
+        assert_eq!(files, vec![
+            dir.join("a.mp4"),
+            dir.join("剧集/S01/c.mp4"),
+            dir.join("剧集/S01/c.srt"),
+            dir.join("剧集/b.MKV"),
+        ]);
+
+        let opts = WalkOptions {
+            extensions: Some(vec!["mp4".to_string(), ".mkv".to_string()]),
+            include_hidden: true,
+            ..Default::default()
+        };
+        let files = walk_files(dir, opts).unwrap();
+        assert_eq!(files, vec![
+            dir.join(".e.mp4"),
+            dir.join(".hidden/d.mp4"),
+            dir.join("a.mp4"),
+            dir.join("剧集/S01/c.mp4"),
+            dir.join("剧集/b.MKV"),
+        ]);
+
+        let opts = WalkOptions { max_depth: Some(2), min_size: Some(2), ..Default::default() };
+        let files = walk_files(dir, opts).unwrap();
+        assert_eq!(files, vec![dir.join("a.mp4"), dir.join("剧集/b.MKV")]);
+
+        assert!(walk_files(dir.join("missing"), WalkOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_walk_with_depth() {
+        let tmp = TempDir::new("lazytool-walk-depth-").unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("a/b/c")).unwrap();
+        fs::create_dir_all(dir.join("a/.cache")).unwrap();
+        fs::write(dir.join("1.txt"), "").unwrap();
+        fs::write(dir.join("a/2.txt"), "").unwrap();
+        fs::write(dir.join("a/.3.txt"), "").unwrap();
+        fs::write(dir.join("a/.cache/4.txt"), "").unwrap();
+        fs::write(dir.join("a/b/5.txt"), "").unwrap();
+        fs::write(dir.join("a/b/c/6.txt"), "").unwrap();
+
+        assert_eq!(walk_with_depth(dir, 0).unwrap(), vec![dir.join("1.txt")]);
+        assert_eq!(walk_with_depth(dir, 1).unwrap(), vec![dir.join("1.txt")]);
+        assert_eq!(walk_with_depth(dir, 2).unwrap(), vec![dir.join("1.txt"), dir.join("a/2.txt")]);
+        assert_eq!(walk_with_depth(dir, 3).unwrap(), vec![
+            dir.join("1.txt"),
+            dir.join("a/2.txt"),
+            dir.join("a/b/5.txt"),
+        ]);
+        assert_eq!(walk_with_depth(dir, 10).unwrap(), vec![
+            dir.join("1.txt"),
+            dir.join("a/2.txt"),
+            dir.join("a/b/5.txt"),
+            dir.join("a/b/c/6.txt"),
+        ]);
+
+        assert!(walk_with_depth(dir.join("missing"), 3).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_files_symlink_cycle() {
+        let tmp = TempDir::new("lazytool-walk-symlink-").unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("a/b")).unwrap();
+        fs::write(dir.join("a/b/01.mp4"), "").unwrap();
+        // 指向上级目录形成循环
+        std::os::unix::fs::symlink(dir.join("a"), dir.join("a/b/loop")).unwrap();
+
+        let files = walk_files(dir, WalkOptions::default()).unwrap();
+        assert_eq!(files, vec![dir.join("a/b/01.mp4")]);
+
+        let opts = WalkOptions { follow_symlinks: true, ..Default::default() };
+        let files = walk_files(dir, opts).unwrap();
+        assert_eq!(files, vec![dir.join("a/b/01.mp4")]);
+    }
+
+    #[test]
+    fn test_glob() {
+        let tmp = TempDir::new("lazytool-glob-").unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("电视剧/S01")).unwrap();
+        fs::create_dir_all(dir.join(".cache")).unwrap();
+        fs::write(dir.join("01.mp4"), "").unwrap();
+        fs::write(dir.join("02.mkv"), "").unwrap();
+        fs::write(dir.join("电视剧/S01/01.mp4"), "").unwrap();
+        fs::write(dir.join("电视剧/S01/.01.mp4"), "").unwrap();
+        fs::write(dir.join(".cache/01.mp4"), "").unwrap();
+
+        let root = dir.display();
+        assert_eq!(glob(&format!("{root}/*.mp4")).unwrap(), vec![dir.join("01.mp4")]);
+        assert_eq!(glob(&format!("{root}/0?.m[kp][v4]")).unwrap(), vec![
+            dir.join("01.mp4"),
+            dir.join("02.mkv"),
+        ]);
+        assert_eq!(glob(&format!("{root}/**/*.mp4")).unwrap(), vec![
+            dir.join("01.mp4"),
+            dir.join("电视剧/S01/01.mp4"),
+        ]);
+        assert_eq!(glob(&format!("{root}/*/S*")).unwrap(), vec![dir.join("电视剧/S01")]);
+        assert_eq!(glob(&format!("{root}/.cache/*")).unwrap(), vec![dir.join(".cache/01.mp4")]);
+        assert_eq!(glob(&format!("{root}/电视剧/S01/.*")).unwrap(), vec![dir.join("电视剧/S01/.01.mp4")]);
+        assert!(glob(&format!("{root}/*.avi")).unwrap().is_empty());
+        assert!(glob(&format!("{root}/[abc")).is_err());
+    }
+
+    #[test]
+    fn test_ensure_dir() {
+        let tmp = TempDir::new("lazytool-ensure-dir-").unwrap();
+        let dir = tmp.path();
+
+        // 多级目录
+        let nested = dir.join("a/b/c");
+        assert_eq!(ensure_dir(&nested).unwrap(), nested);
+        assert!(nested.is_dir());
+        // 重复调用
+        assert_eq!(ensure_dir(&nested).unwrap(), nested);
+
+        // 同名文件占位
+        let file = dir.join("a/file");
+        fs::write(&file, "").unwrap();
+        let err = ensure_dir(&file).unwrap_err();
+        assert!(err.to_string().contains("a/file"));
+        assert!(ensure_dir(file.join("sub")).is_err());
+
+        let target = dir.join("x/y/z.txt");
+        assert_eq!(ensure_parent(&target).unwrap(), target);
+        assert!(dir.join("x/y").is_dir());
+        assert!(!target.exists());
+        assert!(ensure_parent(file.join("z.txt")).is_err());
+    }
+
+    #[test]
+    fn test_unique_path() {
+        let tmp = TempDir::new("lazytool-unique-path-").unwrap();
+        let dir = tmp.path();
+
+        let target = dir.join("report.pdf");
+        for expected in ["report.pdf", "report (1).pdf", "report (2).pdf", "report (3).pdf"] {
+            let path = unique_path(&target);
+            assert_eq!(path, dir.join(expected));
+            fs::write(&path, "").unwrap();
+        }
+        assert_eq!(unique_path_with(&target, SuffixStyle::Underscore), dir.join("report_1.pdf"));
+
+        // 多段扩展名和没有扩展名的文件
+        fs::write(dir.join("backup.tar.gz"), "").unwrap();
+        assert_eq!(unique_path(dir.join("backup.tar.gz")), dir.join("backup (1).tar.gz"));
+        fs::write(dir.join("README"), "").unwrap();
+        assert_eq!(unique_path(dir.join("README")), dir.join("README (1)"));
+        fs::write(dir.join(".env"), "").unwrap();
+        assert_eq!(unique_path(dir.join(".env")), dir.join(".env (1)"));
+
+        let target = dir.join("a.txt");
+        for expected in ["a.txt", "a (1).txt", "a (2).txt", "a (3).txt"] {
+            let (path, _) = create_unique(&target).unwrap();
+            assert_eq!(path, dir.join(expected));
+        }
+        assert!(create_unique(dir.join("missing/a.txt")).is_err());
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("a\\b:c*d?e\"f<g>h|i"), "a_b_c_d_e_f_g_h_i");
+        assert_eq!(sanitize_filename("tab\there\n"), "tab_here_");
+        assert_eq!(sanitize_filename("  trailing . . "), "  trailing");
+        assert_eq!(sanitize_filename("com1.mkv"), "com1_.mkv");
+        assert_eq!(sanitize_filename("Console"), "Console");
+        assert_eq!(sanitize_filename(""), "unnamed");
+        assert_eq!(sanitize_filename_with("///", "", 255), "unnamed");
+        assert_eq!(sanitize_filename_with("a/b", "/", 255), "ab");
+
+        // 多字节字符的截断边界
+        let name = "武林外传第01集";
+        assert_eq!(sanitize_filename_with(name, "_", 12), "武林外传");
+        assert_eq!(sanitize_filename_with(name, "_", 14), "武林外传");
+        assert_eq!(sanitize_filename_with(name, "_", 15), "武林外传第");
+        assert_eq!(sanitize_filename_with("ab.", "_", 3), "ab");
+        assert!(sanitize_filename(&"长".repeat(100)).len() <= 255);
+    }
+
+    #[test]
+    fn test_sanitize_filename_for_rename() {
+        // Windows 保留名，不区分大小写
+        for name in ["CON", "con", "Prn", "AUX", "NUL", "COM1", "lpt9"] {
+            assert_eq!(sanitize_filename(name), format!("{name}_"));
+        }
+        assert_eq!(sanitize_filename("CON.S01E01.mkv"), "CON_.S01E01.mkv");
+        assert_eq!(sanitize_filename("CONAN.mkv"), "CONAN.mkv");
+
+        // 中文字幕中常见的半角冒号、问号需要替换，全角符号本身是合法的
+        assert_eq!(sanitize_filename("名侦探柯南:零的执行人.ass"), "名侦探柯南_零的执行人.ass");
+        assert_eq!(sanitize_filename("名侦探柯南：零的执行人.ass"), "名侦探柯南：零的执行人.ass");
+        assert_eq!(sanitize_filename("谁是凶手?.srt"), "谁是凶手_.srt");
+        assert_eq!(sanitize_filename("未完待续..."), "未完待续");
+
+        // 截断到 255 字节
+        let name = sanitize_filename(&format!("{}.mkv", "剧".repeat(90)));
+        assert_eq!(name.len(), 255);
+        assert!(name.chars().all(|c| c == '剧'));
+    }
+
+    #[test]
+    fn test_atomic_write() {
+        let tmp = TempDir::new("lazytool-atomic-write-").unwrap();
+        let dir = tmp.path();
+        let file = dir.join("config.json");
+
+        atomic_write(&file, "old").unwrap();
+        atomic_write_with(&file, |w| {
+            w.write_all(b"ne")?;
+            // 写入过程中目标文件仍是完整的旧内容
+            assert_eq!(fs::read_to_string(&file)?, "old");
+            w.write_all(b"w")?;
+            Ok(())
+        }).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "new");
+
+        // 写入失败时目标文件不变，且不会残留临时文件
+        let result = atomic_write_with(&file, |w| {
+            w.write_all(b"partial")?;
+            Err(std::io::Error::other("disk full").into())
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "new");
+        let names: Vec<_> = fs::read_dir(dir).unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["config.json"]);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&file, fs::Permissions::from_mode(0o600)).unwrap();
+            atomic_write(&file, "secret").unwrap();
+            assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_sizes() {
+        let tmp = TempDir::new("lazytool-sizes-").unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("S01/extras")).unwrap();
+        fs::write(dir.join("a.mp4"), vec![0u8; 1000]).unwrap();
+        fs::write(dir.join("S01/b.mp4"), vec![0u8; 24]).unwrap();
+        fs::write(dir.join("S01/extras/c.mp4"), vec![0u8; 1]).unwrap();
+        fs::write(dir.join(".hidden"), vec![0u8; 3]).unwrap();
+
+        assert_eq!(file_size(dir.join("a.mp4")).unwrap(), 1000);
+        assert!(file_size(dir.join("missing")).is_err());
+        assert_eq!(dir_size(dir).unwrap(), 1028);
+        assert_eq!(dir_size(dir.join("S01")).unwrap(), 25);
+        let (size, warnings) = dir_size_with_warnings(dir, WalkOptions::default()).unwrap();
+        assert_eq!(size, 1025);
+        assert!(warnings.is_empty());
+
+        #[cfg(unix)]
+        {
+            // 不跟随符号链接
+            std::os::unix::fs::symlink(dir.join("a.mp4"), dir.join("link.mp4")).unwrap();
+            assert_eq!(dir_size(dir).unwrap(), 1028);
+        }
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(0), "0 B");
+        assert_eq!(human_size(999), "999 B");
+        assert_eq!(human_size(1000), "1.0 KB");
+        assert_eq!(human_size(1023), "1.0 KB");
+        assert_eq!(human_size(1024), "1.0 KB");
+        assert_eq!(human_size(1025), "1.0 KB");
+        assert_eq!(human_size(999_999), "1.0 MB");
+        assert_eq!(human_size(12_345_678), "12 MB");
+        assert_eq!(human_size_with(1023, SizeUnits::Binary), "1023 B");
+        assert_eq!(human_size_with(1024, SizeUnits::Binary), "1.0 KiB");
+        assert_eq!(human_size_with(1025, SizeUnits::Binary), "1.0 KiB");
+        assert_eq!(human_size_with(10 * 1024 - 1, SizeUnits::Binary), "10 KiB");
+        assert_eq!(human_size_with(u64::MAX, SizeUnits::Binary), "16 EiB");
+    }
+
+    #[test]
+    fn test_full_extension() {
+        assert_eq!(full_extension("a.tar.gz"), Some("tar.gz".to_string()));
+        assert_eq!(full_extension("A.TAR.GZ"), Some("TAR.GZ".to_string()));
+        assert_eq!(full_extension(".tar.gz"), Some("gz".to_string()));
+        assert_eq!(full_extension("a."), None);
+        assert_eq!(full_extension("/"), None);
+        assert_eq!(full_extension("还珠格格.tar.zst"), Some("tar.zst".to_string()));
+        assert_eq!(stem("a.tar.gz"), "a");
+        assert_eq!(stem(".config.tar.xz"), ".config");
+        assert_eq!(stem(".tar.gz"), ".tar");
+        assert_eq!(stem("README"), "README");
+        assert_eq!(with_full_extension("a.tar.bz2", "tar.gz"), PathBuf::from("a.tar.gz"));
+        assert!(has_extension("a.TAR.BZ2", &["tar.bz2"]));
+        assert!(!has_extension("a.mp4.part", &["mp4"]));
+        assert!(!has_extension("README", &[""]));
+    }
+
+    #[test]
+    fn test_relative_path() {
+        // 相同路径
+        assert_eq!(relative_path("/a/b", "/a/b/"), Some(PathBuf::from(".")));
+        assert_eq!(relative_path("a/b", "./a/b"), Some(PathBuf::from(".")));
+        // base 是 target 的前缀
+        assert_eq!(relative_path("/影片/电视剧/01.mp4", "/影片"), Some(PathBuf::from("电视剧/01.mp4")));
+        // target 是 base 的前缀
+        assert_eq!(relative_path("/影片", "/影片/电视剧/S01"), Some(PathBuf::from("../..")));
+        // 完全不相交的绝对路径
+        assert_eq!(relative_path("/Volumes/Getea/01.mp4", "/Users/wxnacy"), Some(PathBuf::from("../../Volumes/Getea/01.mp4")));
+        // 相对路径
+        assert_eq!(relative_path("a/b", "c"), Some(PathBuf::from("../a/b")));
+        assert_eq!(relative_path("../a", "b"), Some(PathBuf::from("../../a")));
+        assert_eq!(relative_path("a", "../b"), None);
+        assert_eq!(relative_path("a", "/b"), None);
+
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+        assert_eq!(relative_path("~/Movies", &home), Some(PathBuf::from("Movies")));
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        assert_eq!(common_prefix(["/foo/bar1", "/foo/bar2"]), Some(PathBuf::from("/foo")));
+        assert_eq!(common_prefix(["/影片/电视剧", "/影片/电视剧集"]), Some(PathBuf::from("/影片")));
+        assert_eq!(common_prefix(["影片/电视剧/a", "影片/电视剧/b"]), Some(PathBuf::from("影片/电视剧")));
+        assert_eq!(common_prefix(["影片/a", "电视剧/a"]), None);
+        assert_eq!(common_prefix(["/影片/a", "影片/a"]), None);
+        // 多个相同的文件路径
+        assert_eq!(common_prefix(["/影片/01.mp4", "/影片/01.mp4"]), Some(PathBuf::from("/影片")));
+        // 某个输入是另一个输入所在的目录
+        assert_eq!(common_prefix(["/影片/S01", "/影片/S01/01.mp4"]), Some(PathBuf::from("/影片/S01")));
+        // 带扩展名的目录下还有其他输入时不视为文件
+        assert_eq!(common_prefix(["/a/x.d/f", "/a/x.d"]), Some(PathBuf::from("/a/x.d")));
+        assert_eq!(common_prefix(["/a/x.d", "/a/x.d/f"]), Some(PathBuf::from("/a/x.d")));
+
+        let paths = vec![PathBuf::from("/影片/S01"), PathBuf::from("/影片/S01/01.mp4")];
+        assert_eq!(strip_common_prefix(&paths), (PathBuf::from("/影片/S01"), vec![PathBuf::from("."), PathBuf::from("01.mp4")]));
+        let paths = vec![PathBuf::from("/影片"), PathBuf::from("影片")];
+        assert_eq!(strip_common_prefix(&paths), (PathBuf::new(), paths.clone()));
+        assert_eq!(strip_common_prefix(&[]), (PathBuf::new(), vec![]));
+    }
+
+    #[test]
+    fn test_copy_and_move_file() {
+        let tmp = TempDir::new("lazytool-copy-file-").unwrap();
+        let dir = tmp.path();
+        let src = dir.join("01.mkv");
+        fs::write(&src, vec![7u8; 10]).unwrap();
+
+        // 按块回调进度
+        let mut calls = vec![];
+        let mut progress = |copied, total| calls.push((copied, total));
+        let opts = CopyOptions { chunk_size: 4, ..Default::default() };
+        let copied = copy_file_with(&src, dir.join("a/b/01.mkv"), opts, Some(&mut progress)).unwrap();
+        assert_eq!(copied, 10);
+        assert_eq!(calls, vec![(0, 10), (4, 10), (8, 10), (10, 10)]);
+        assert_eq!(fs::read(dir.join("a/b/01.mkv")).unwrap(), vec![7u8; 10]);
+
+        // 目标已存在
+        fs::write(dir.join("exists.mkv"), "old").unwrap();
+        assert!(copy_file(&src, dir.join("exists.mkv"), None).is_err());
+        assert_eq!(fs::read_to_string(dir.join("exists.mkv")).unwrap(), "old");
+        let opts = CopyOptions { overwrite: true, ..Default::default() };
+        assert_eq!(copy_file_with(&src, dir.join("exists.mkv"), opts, None).unwrap(), 10);
+        assert!(move_file(&src, dir.join("exists.mkv"), None).is_err());
+
+        // 源文件不存在或不是文件
+        assert!(copy_file(dir.join("missing.mkv"), dir.join("x.mkv"), None).is_err());
+        assert!(copy_file(dir.join("a"), dir.join("x.mkv"), None).is_err());
+        assert!(!dir.join("x.mkv").exists());
+
+        // 跨文件系统时的复制移动
+        let mut calls = 0;
+        let mut progress = |_, _| calls += 1;
+        let copied = move_by_copy(&src, &dir.join("moved.mkv"), CopyOptions::default(), Some(&mut progress)).unwrap();
+        assert_eq!(copied, 10);
+        assert!(calls > 0);
+        assert!(!src.exists());
+
+        move_file(dir.join("moved.mkv"), dir.join("c/01.mkv"), None).unwrap();
+        assert_eq!(fs::read(dir.join("c/01.mkv")).unwrap(), vec![7u8; 10]);
+    }
+
+    #[test]
+    fn test_canonicalize_lenient() {
+        let tmp = TempDir::new("lazytool-canonicalize-").unwrap();
+        let dir = tmp.path();
+        let real = fs::canonicalize(dir).unwrap();
+
+        // 最后两级不存在
+        assert_eq!(canonicalize_lenient(dir.join("a/b")).unwrap(), real.join("a/b"));
+        assert_eq!(canonicalize_lenient(dir.join("a/./b/../c")).unwrap(), real.join("a/c"));
+        assert_eq!(canonicalize_lenient(dir).unwrap(), real);
+
+        // 相对路径基于当前目录
+        let cwd = fs::canonicalize(std::env::current_dir().unwrap()).unwrap();
+        assert_eq!(canonicalize_lenient("lazytool-missing/x").unwrap(), cwd.join("lazytool-missing/x"));
+        assert_eq!(absolutize("lazytool-missing/../x").unwrap(), std::env::current_dir().unwrap().join("x"));
+
+        #[cfg(unix)]
+        {
+            // 经过符号链接目录
+            fs::create_dir_all(dir.join("target/S01")).unwrap();
+            std::os::unix::fs::symlink(dir.join("target"), dir.join("link")).unwrap();
+            assert_eq!(canonicalize_lenient(dir.join("link/S01/new.mp4")).unwrap(), real.join("target/S01/new.mp4"));
+            // absolutize 不解析符号链接
+            assert_eq!(absolutize(dir.join("link/S01")).unwrap(), dir.join("link/S01"));
+
+            // 符号链接循环返回错误
+            std::os::unix::fs::symlink(dir.join("loop1"), dir.join("loop2")).unwrap();
+            std::os::unix::fs::symlink(dir.join("loop2"), dir.join("loop1")).unwrap();
+            assert!(canonicalize_lenient(dir.join("loop1/x")).is_err());
+        }
+    }
+
+    #[test]
+    fn test_backup_file() {
+        let tmp = TempDir::new("lazytool-backup-").unwrap();
+        let dir = tmp.path();
+        let file = dir.join("foo.toml");
+        assert_eq!(latest_backup(&file).unwrap(), None);
+        fs::write(&file, "v1").unwrap();
+
+        // 固定的时间格式模拟同一秒内的多次备份
+        let opts = BackupOptions { format: "20250115T181613".to_string(), max_backups: None };
+        let first = backup_file_with(&file, opts.clone()).unwrap();
+        fs::write(&file, "v2").unwrap();
+        let second = backup_file_with(&file, opts.clone()).unwrap();
+        assert_eq!(first, dir.join("foo.toml.20250115T181613.bak"));
+        assert_eq!(second, dir.join("foo.toml.20250115T181613~1.bak"));
+        assert_eq!(fs::read_to_string(&first).unwrap(), "v1");
+        assert_eq!(latest_backup(&file).unwrap(), Some(second.clone()));
+
+        // 清理最旧的备份
+        let opts = BackupOptions { max_backups: Some(2), ..opts };
+        let mut created = vec![first, second];
+        for i in 3..=12 {
+            fs::write(&file, format!("v{i}")).unwrap();
+            created.push(backup_file_with(&file, opts.clone()).unwrap());
+        }
+        let mut remaining: Vec<_> = fs::read_dir(dir).unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| *p != file)
+            .collect();
+        remaining.sort();
+        let mut expected = created[created.len() - 2..].to_vec();
+        expected.sort();
+        assert_eq!(remaining, expected);
+        assert_eq!(fs::read_to_string(latest_backup(&file).unwrap().unwrap()).unwrap(), "v12");
+
+        // 其他文件的备份不受影响
+        fs::write(dir.join("foo.toml.bak"), "").unwrap();
+        fs::write(dir.join("foo.tomlx.1.bak"), "").unwrap();
+        assert_eq!(latest_backup(&file).unwrap(), Some(created[created.len() - 1].clone()));
+
+        assert!(matches!(backup_file(dir.join("missing.toml")), Err(Error::NotAFile(_))));
+        assert!(matches!(backup_file(dir), Err(Error::NotAFile(_))));
+        let opts = BackupOptions { format: "%Y~%m".to_string(), max_backups: None };
+        assert!(matches!(backup_file_with(&file, opts), Err(Error::InvalidPattern { .. })));
+    }
+
+    #[test]
+    fn test_backup_file_dashed_format() {
+        let tmp = TempDir::new("lazytool-backup-dashed-").unwrap();
+        let file = tmp.child("foo.toml");
+        fs::write(&file, "v1").unwrap();
+
+        // 时间中带 `-` 时不能与序号混淆
+        let opts = |format: &str| BackupOptions { format: format.to_string(), max_backups: Some(2) };
+        let first = backup_file_with(&file, opts("2025-01-15")).unwrap();
+        let second = backup_file_with(&file, opts("2025-01-15")).unwrap();
+        assert_eq!(second, tmp.child("foo.toml.2025-01-15~1.bak"));
+        fs::write(&file, "v3").unwrap();
+        let third = backup_file_with(&file, opts("2025-01-16")).unwrap();
+        assert_eq!(third, tmp.child("foo.toml.2025-01-16.bak"));
+        assert_eq!(latest_backup(&file).unwrap(), Some(third.clone()));
+
+        // 清理时删除的是最旧的备份
+        assert!(!first.exists());
+        assert!(second.exists() && third.exists());
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let tmp = TempDir::new("lazytool-duplicates-").unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("S01")).unwrap();
+        let big: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let mut near = big.clone();
+        *near.last_mut().unwrap() ^= 1;
+
+        fs::write(dir.join("a.mp4"), &big).unwrap();
+        fs::write(dir.join("S01/a copy.mp4"), &big).unwrap();
+        // 只有最后一个字节不同
+        fs::write(dir.join("S01/near.mp4"), &near).unwrap();
+        fs::write(dir.join("b.mkv"), "episode").unwrap();
+        fs::write(dir.join("S01/b.mkv"), "episode").unwrap();
+        fs::write(dir.join("c.mkv"), "episodf").unwrap();
+        fs::write(dir.join("empty1.mp4"), "").unwrap();
+        fs::write(dir.join("empty2.mp4"), "").unwrap();
+
+        let expected = vec![
+            vec![dir.join("S01/a copy.mp4"), dir.join("a.mp4")],
+            vec![dir.join("S01/b.mkv"), dir.join("b.mkv")],
+        ];
+        let sorted = |groups: Vec<Vec<PathBuf>>| -> Vec<Vec<PathBuf>> {
+            groups.into_iter().map(|mut g| { g.sort(); g }).collect()
+        };
+        let groups = find_duplicates(dir, DuplicateOptions::default()).unwrap();
+        assert_eq!(sorted(groups), expected);
+
+        // 不比较开头部分
+        let opts = DuplicateOptions { prefix_bytes: None, ..Default::default() };
+        assert_eq!(sorted(find_duplicates(dir, opts).unwrap()), expected);
+        let opts = DuplicateOptions { prefix_bytes: Some(16), ..Default::default() };
+        assert_eq!(sorted(find_duplicates(dir, opts).unwrap()), expected);
+
+        // 扩展名和最小字节数
+        let opts = DuplicateOptions { extensions: Some(vec!["mkv".to_string()]), ..Default::default() };
+        assert_eq!(sorted(find_duplicates(dir, opts).unwrap()), expected[1..].to_vec());
+        let opts = DuplicateOptions { min_size: Some(1000), ..Default::default() };
+        assert_eq!(sorted(find_duplicates(dir, opts).unwrap()), expected[..1].to_vec());
+
+        // 空文件
+        let opts = DuplicateOptions { include_empty: true, ..Default::default() };
+        let groups = sorted(find_duplicates(dir, opts).unwrap());
+        assert_eq!(groups.len(), 3);
+        assert!(groups.contains(&vec![dir.join("empty1.mp4"), dir.join("empty2.mp4")]));
+    }
+
+    #[test]
+    fn test_temp_dir() {
+        let base = TempDir::new("lazytool-temp-").unwrap();
+
+        let a = TempDir::new_in(&base, "a-").unwrap();
+        let b = TempDir::new_in(&base, "a-").unwrap();
+        assert_ne!(a.path(), b.path());
+        assert!(a.path().is_dir());
+        assert!(a.path().file_name().unwrap().to_string_lossy().starts_with("a-"));
+        fs::create_dir_all(a.child("S01")).unwrap();
+        fs::write(a.child("S01/01.mp4"), "").unwrap();
+        let a_path = a.path().to_path_buf();
+        drop(a);
+        assert!(!a_path.exists());
+
+        // 已被删除时不会 panic
+        fs::remove_dir_all(b.path()).unwrap();
+        drop(b);
+
+        let mut kept = TempDir::new_in(&base, "kept-").unwrap();
+        kept.keep();
+        let kept_path = kept.path().to_path_buf();
+        drop(kept);
+        assert!(kept_path.is_dir());
+
+        let file = TempFile::new_in(&base, "f-", Some(".srt")).unwrap();
+        assert!(file.path().is_file());
+        assert_eq!(file.path().extension().unwrap(), "srt");
+        let file_path = file.path().to_path_buf();
+        drop(file);
+        assert!(!file_path.exists());
+
+        let file = TempFile::new_in(&base, "f-", None).unwrap();
+        fs::remove_file(file.path()).unwrap();
+        drop(file);
+        let file_path = TempFile::new_in(&base, "f-", None).unwrap().into_path();
+        assert!(file_path.is_file());
+
+        assert!(TempDir::new_in(base.child("missing"), "x-").is_err());
+        assert!(TempFile::new_in(base.child("missing"), "x-", None).is_err());
+
+        let base_path = base.path().to_path_buf();
+        drop(base);
+        assert!(!base_path.exists());
+    }
+
+    #[test]
+    fn test_expand_vars() {
+        std::env::set_var("LAZYTOOL_TEST_ROOT", "/media");
+        std::env::set_var("LAZYTOOL_TEST_EMPTY", "");
+
+        let expand = |s: &str, percent: bool| substitute_vars(s, percent, false).unwrap();
+        assert_eq!(expand("$LAZYTOOL_TEST_ROOT", false), PathBuf::from("/media"));
+        assert_eq!(expand("a${LAZYTOOL_TEST_ROOT}b", false), PathBuf::from("a/mediab"));
+        assert_eq!(expand("$LAZYTOOL_TEST_ROOT.bak", false), PathBuf::from("/media.bak"));
+        assert_eq!(expand("/a/$LAZYTOOL_TEST_EMPTY/b", false), PathBuf::from("/a//b"));
+        assert_eq!(expand("$$LAZYTOOL_TEST_ROOT", false), PathBuf::from("$LAZYTOOL_TEST_ROOT"));
+        // 不构成变量引用的 `$` 原样保留
+        assert_eq!(expand("/a/$/b$", false), PathBuf::from("/a/$/b$"));
+        assert_eq!(expand("/a/$1/${LAZYTOOL", false), PathBuf::from("/a/$1/${LAZYTOOL"));
+        assert_eq!(expand("/a/${}/b", false), PathBuf::from("/a/${}/b"));
+        assert_eq!(expand("${LAZYTOOL_TEST_MISSING}/$LAZYTOOL_TEST_MISSING", false),
+            PathBuf::from("${LAZYTOOL_TEST_MISSING}/$LAZYTOOL_TEST_MISSING"));
+
+        // `%VAR%` 仅在开启时展开
+        assert_eq!(expand("%LAZYTOOL_TEST_ROOT%/a", false), PathBuf::from("%LAZYTOOL_TEST_ROOT%/a"));
+        assert_eq!(expand("%LAZYTOOL_TEST_ROOT%/a", true), PathBuf::from("/media/a"));
+        assert_eq!(expand("100%/%LAZYTOOL_TEST_MISSING%", true), PathBuf::from("100%/%LAZYTOOL_TEST_MISSING%"));
+        assert_eq!(expand("50% off%", true), PathBuf::from("50% off%"));
+
+        assert!(substitute_vars("$LAZYTOOL_TEST_MISSING", false, true).is_err());
+        assert!(substitute_vars("%LAZYTOOL_TEST_MISSING%", true, true).is_err());
+        assert!(substitute_vars("$$LAZYTOOL_TEST_MISSING/$1", false, true).is_ok());
+    }
+
+    #[test]
+    fn test_error_variants() {
+        let tmp = TempDir::new("lazytool-errors-").unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+
+        assert!(matches!(ensure_dir(dir.join("a.txt")), Err(Error::NotADirectory(p)) if p == dir.join("a.txt")));
+        assert!(matches!(copy_file(dir.join("a.txt"), dir.join("a.txt"), None), Err(Error::AlreadyExists(_))));
+        assert!(matches!(copy_file(dir, dir.join("b"), None), Err(Error::NotAFile(_))));
+        match file_size(dir.join("missing")) {
+            Err(Error::Io { source, .. }) => assert_eq!(source.kind(), std::io::ErrorKind::NotFound),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(matches!(glob("/tmp/[ab"), Err(Error::InvalidPattern { .. })));
+        assert!(matches!(
+            substitute_vars("$LAZYTOOL_TEST_UNDEFINED", false, true),
+            Err(Error::UndefinedVariable { name, .. }) if name == "LAZYTOOL_TEST_UNDEFINED"
+        ));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let invalid = std::path::Path::new(std::ffi::OsStr::from_bytes(b"/tmp/\xff"));
+            assert!(matches!(try_to_string(invalid), Err(Error::InvalidPath(_))));
+            assert!(matches!(expand_vars_strict(invalid), Err(Error::InvalidPath(_))));
+        }
+    }
+}
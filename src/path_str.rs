@@ -0,0 +1,273 @@
+use alloc::string::{String, ToString};
+
+/// 是否为路径分隔符，`/` 与 `\` 都视为分隔符
+pub fn is_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+/// 拆分 Windows 盘符（或 UNC 前缀）与剩余路径，非 Windows 风格的路径盘符为 `None`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path_str;
+///
+/// assert_eq!(path_str::split_drive(r"C:\a\b"), (Some("C:"), r"\a\b"));
+/// assert_eq!(path_str::split_drive(r"\\server\share\dir"), (Some(r"\\server\share"), r"\dir"));
+/// assert_eq!(path_str::split_drive("/tmp/foo"), (None, "/tmp/foo"));
+/// ```
+pub fn split_drive(path: &str) -> (Option<&str>, &str) {
+    // UNC 路径: \\server\share\rest
+    let mut chars = path.chars();
+    if let (Some(a), Some(b), Some(c)) = (chars.next(), chars.next(), chars.next()) {
+        if is_separator(a) && is_separator(b) && !is_separator(c) {
+            let rest = &path[2..];
+            // server 与 share 之间的分隔符
+            if let Some(i) = rest.find(is_separator) {
+                let share = &rest[i + 1..];
+                if !share.is_empty() && !share.starts_with(is_separator) {
+                    let end = share.find(is_separator).map(|j| 2 + i + 1 + j).unwrap_or(path.len());
+                    return (Some(&path[..end]), &path[end..]);
+                }
+            }
+            return (None, path);
+        }
+    }
+
+    // 盘符路径: C:\rest
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        return (Some(&path[..2]), &path[2..]);
+    }
+
+    (None, path)
+}
+
+/// 是否为绝对路径，以分隔符开头或带有盘符及分隔符，如 `/tmp`、`C:\tmp`、`\\server\share`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path_str;
+///
+/// assert!(path_str::is_absolute("/tmp/foo"));
+/// assert!(path_str::is_absolute(r"D:\影片"));
+/// assert!(!path_str::is_absolute("D:影片"));
+/// assert!(!path_str::is_absolute("影片/01.mp4"));
+/// ```
+pub fn is_absolute(path: &str) -> bool {
+    match split_drive(path) {
+        (Some(drive), rest) => drive.len() > 2 || rest.starts_with(is_separator),
+        (None, rest) => rest.starts_with(is_separator),
+    }
+}
+
+/// 获取最后一级名称，忽略结尾的分隔符和 `.`，最后一级为 `..` 或没有名称时返回 `None`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path_str;
+///
+/// assert_eq!(path_str::file_name("/tmp/filter.json"), Some("filter.json"));
+/// assert_eq!(path_str::file_name(r"D:\影片\还珠格格\"), Some("还珠格格"));
+/// assert_eq!(path_str::file_name("01.mp4"), Some("01.mp4"));
+/// assert_eq!(path_str::file_name("/tmp/.."), None);
+/// assert_eq!(path_str::file_name("/"), None);
+/// ```
+pub fn file_name(path: &str) -> Option<&str> {
+    let (_, rest) = split_drive(path);
+    let name = rest.split(is_separator).rev().find(|name| !name.is_empty() && *name != ".")?;
+    (name != "..").then_some(name)
+}
+
+/// 拼接路径，`path` 为绝对路径或带有盘符时直接返回 `path`，否则使用 `base` 中已有的分隔符连接
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path_str;
+///
+/// assert_eq!(path_str::join("/Volumes/Getea", "电视剧/01.mp4"), "/Volumes/Getea/电视剧/01.mp4");
+/// assert_eq!(path_str::join("/Volumes/Getea/", "/tmp"), "/tmp");
+/// assert_eq!(path_str::join(r"D:\影片", "01.mp4"), r"D:\影片\01.mp4");
+/// assert_eq!(path_str::join("", "01.mp4"), "01.mp4");
+/// // 只有盘符时与 Windows 一致，表示该盘的当前目录
+/// assert_eq!(path_str::join("D:", "01.mp4"), "D:01.mp4");
+/// ```
+pub fn join(base: &str, path: &str) -> String {
+    if base.is_empty() || is_absolute(path) || split_drive(path).0.is_some() {
+        return path.to_string();
+    }
+    let mut joined = String::with_capacity(base.len() + path.len() + 1);
+    joined.push_str(base);
+    let bare_drive = matches!(split_drive(base), (Some(drive), "") if drive.len() == 2);
+    if !base.ends_with(is_separator) && !path.is_empty() && !bare_drive {
+        // 沿用 base 中的分隔符风格，没有时使用 `/`
+        let sep = base.find(is_separator).map(|i| &base[i..i + 1]).unwrap_or("/");
+        joined.push_str(sep);
+    }
+    joined.push_str(path);
+    joined
+}
+
+/// 内置的多段扩展名
+pub const COMPOUND_EXTENSIONS: &[&str] = &["tar.gz", "tar.bz2", "tar.xz", "tar.zst"];
+
+/// 将文件名拆分为主干和扩展名（不含 `.`），`compound` 中的多段扩展名视为一个扩展名，
+/// 隐藏文件开头的 `.` 不视为扩展名
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path_str::{self, COMPOUND_EXTENSIONS};
+///
+/// assert_eq!(path_str::split_extension("db.tar.gz", COMPOUND_EXTENSIONS), ("db", Some("tar.gz")));
+/// assert_eq!(path_str::split_extension("01.mp4", COMPOUND_EXTENSIONS), ("01", Some("mp4")));
+/// assert_eq!(path_str::split_extension(".bashrc", COMPOUND_EXTENSIONS), (".bashrc", None));
+/// ```
+pub fn split_extension<'a, S: AsRef<str>>(name: &'a str, compound: &[S]) -> (&'a str, Option<&'a str>) {
+    for ext in compound {
+        let ext = ext.as_ref().trim_start_matches('.');
+        let n = ext.len() + 1;
+        if name.len() <= n || !name.is_char_boundary(name.len() - n) {
+            continue;
+        }
+        let (stem, tail) = name.split_at(name.len() - n);
+        if tail.starts_with('.') && tail[1..].eq_ignore_ascii_case(ext) && !stem.ends_with('.') {
+            return (stem, Some(&tail[1..]));
+        }
+    }
+    match name.rfind('.') {
+        Some(i) if i > 0 && i + 1 < name.len() => (&name[..i], Some(&name[i + 1..])),
+        _ => (name, None),
+    }
+}
+
+/// 文件名中不允许出现的字符
+const ILLEGAL_FILENAME_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Windows 保留的设备名
+const RESERVED_FILENAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// 将文件名转为跨平台安全的形式，非法字符替换为 `_`，最长 255 字节
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path_str;
+///
+/// assert_eq!(path_str::sanitize_filename("AC/DC: Live?"), "AC_DC_ Live_");
+/// assert_eq!(path_str::sanitize_filename("还珠格格 第1部/第2部"), "还珠格格 第1部_第2部");
+/// assert_eq!(path_str::sanitize_filename("CON"), "CON_");
+/// assert_eq!(path_str::sanitize_filename("notes. . "), "notes");
+/// assert_eq!(path_str::sanitize_filename(".."), "unnamed");
+/// ```
+pub fn sanitize_filename(name: &str) -> String {
+    sanitize_filename_with(name, "_", 255)
+}
+
+/// 将文件名转为跨平台安全的形式
+///
+/// 非法字符和控制字符替换为 `replacement`（为空时直接去掉），去掉结尾的 `.` 和空格，
+/// Windows 保留名（如 `CON`、`nul.txt`）在主干后追加 `_`，并在不拆分 UTF-8 字符的前提下截断到 `max_bytes` 字节，
+/// 结果为空时返回 `unnamed`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::path_str;
+///
+/// assert_eq!(path_str::sanitize_filename_with("a<b>c", "", 255), "abc");
+/// assert_eq!(path_str::sanitize_filename_with("nul.txt", "-", 255), "nul_.txt");
+/// // 每个汉字 3 字节，不会截断在字符中间
+/// assert_eq!(path_str::sanitize_filename_with("爱情公寓", "_", 10), "爱情公");
+/// ```
+pub fn sanitize_filename_with(name: &str, replacement: &str, max_bytes: usize) -> String {
+    // 替换字符本身也不能包含非法字符
+    let replacement: String = replacement.chars()
+        .filter(|c| !ILLEGAL_FILENAME_CHARS.contains(c) && !c.is_control())
+        .collect();
+
+    let mut result = String::with_capacity(name.len());
+    for c in name.chars() {
+        if ILLEGAL_FILENAME_CHARS.contains(&c) || c.is_control() {
+            result.push_str(&replacement);
+        } else {
+            result.push(c);
+        }
+    }
+    let mut result = result.trim_end_matches(['.', ' ']).to_string();
+
+    let stem = result.split('.').next().unwrap_or_default();
+    if RESERVED_FILENAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        result.insert(stem.len(), '_');
+    }
+
+    if result.len() > max_bytes {
+        let mut end = max_bytes;
+        while !result.is_char_boundary(end) {
+            end -= 1;
+        }
+        result.truncate(end);
+        result = result.trim_end_matches(['.', ' ']).to_string();
+    }
+
+    if result.is_empty() {
+        return "unnamed".to_string();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_name, is_absolute, join, split_drive, split_extension, COMPOUND_EXTENSIONS};
+
+    #[test]
+    fn test_split_drive() {
+        assert_eq!(split_drive("d:/movies"), (Some("d:"), "/movies"));
+        assert_eq!(split_drive(r"\\server\share"), (Some(r"\\server\share"), ""));
+        assert_eq!(split_drive(r"\\server"), (None, r"\\server"));
+        assert_eq!(split_drive(r"\\server\\share"), (None, r"\\server\\share"));
+        assert_eq!(split_drive("还珠格格/01.mp4"), (None, "还珠格格/01.mp4"));
+        assert_eq!(split_drive(""), (None, ""));
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name("/tmp/foo/."), Some("foo"));
+        assert_eq!(file_name("/tmp//foo//"), Some("foo"));
+        assert_eq!(file_name("C:"), None);
+        assert_eq!(file_name(r"C:\影片\01.mp4"), Some("01.mp4"));
+        assert_eq!(file_name(r"\\server\share"), None);
+        assert_eq!(file_name(".."), None);
+        assert_eq!(file_name(""), None);
+    }
+
+    #[test]
+    fn test_join() {
+        assert!(is_absolute(r"\\server\share"));
+        assert!(is_absolute(r"\tmp"));
+        assert!(!is_absolute(""));
+        assert_eq!(join("a", "b/c"), "a/b/c");
+        assert_eq!(join("a/", "b"), "a/b");
+        assert_eq!(join("a", ""), "a");
+        assert_eq!(join("C:", "01.mp4"), "C:01.mp4");
+        assert_eq!(join(r"\\server\share", "01.mp4"), r"\\server\share\01.mp4");
+        assert_eq!(join(r"C:\影片", r"D:\tmp"), r"D:\tmp");
+        assert_eq!(join(r"C:\影片", "D:tmp"), "D:tmp");
+    }
+
+    #[test]
+    fn test_split_extension() {
+        assert_eq!(split_extension("a.TAR.GZ", COMPOUND_EXTENSIONS), ("a", Some("TAR.GZ")));
+        assert_eq!(split_extension("tar.gz", COMPOUND_EXTENSIONS), ("tar", Some("gz")));
+        assert_eq!(split_extension("a..tar.gz", COMPOUND_EXTENSIONS), ("a..tar", Some("gz")));
+        assert_eq!(split_extension("a.", COMPOUND_EXTENSIONS), ("a.", None));
+        assert_eq!(split_extension("还珠格格.mp4", &["mp4"]), ("还珠格格", Some("mp4")));
+    }
+}
@@ -1,7 +1,12 @@
+use std::time::Instant;
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
 use std::time::{SystemTime, UNIX_EPOCH};
-use chrono::{DateTime, NaiveDateTime, TimeZone, Local};
+use chrono::{Datelike, DateTime, Duration, FixedOffset, LocalResult, Months, NaiveDate, NaiveDateTime, NaiveTime, Weekday, Offset, SecondsFormat, TimeZone, Local};
+#[cfg(feature = "timezone")]
 use chrono_tz::Tz;
-use anyhow::Result;
+use crate::error::{Error, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
 
 /// 获取当前时间戳，单位秒
 ///
@@ -14,6 +19,7 @@ use anyhow::Result;
 /// assert!(ts > 1736838663);
 ///
 /// ```
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
 pub fn current_timestamp() -> u64 {
     let start = SystemTime::now();
 
@@ -25,24 +31,113 @@ pub fn current_timestamp() -> u64 {
     duration.as_secs()
 }
 
-/// 通过时间字符串解析得到 `DateTime<Local>` 结构体
+/// 获取当前时间戳，单位秒
+///
+/// `wasm32` 下 `SystemTime::now()` 不可用，使用 JS 的 `Date.now()` 获取
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub fn current_timestamp() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+/// 获取当前时间戳，单位毫秒
 ///
 /// Examples
 ///
 /// ```
 /// use lazytool::time;
 ///
-/// let dt = time::from_str("2025-01-15 18:16:13", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let ms = time::current_timestamp_millis();
+/// assert!(ms > 1736838663000);
+/// assert!(ms / 1000 >= time::current_timestamp() - 1);
+/// ```
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+pub fn current_timestamp_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .expect("时间错误")
+        .as_millis() as u64
+}
+
+/// 获取当前时间戳，单位毫秒
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub fn current_timestamp_millis() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// 获取当前时间戳，单位纳秒，可以表示到 2554 年
+///
+/// Examples
 ///
-/// assert_eq!(dt.timestamp(), 1736936173);
-/// assert_eq!(dt.format("%Y-%m-%d %H:%M:%S").to_string(), "2025-01-15 18:16:13");
+/// ```
+/// use lazytool::time;
+///
+/// let ns = time::current_timestamp_nanos();
+/// assert!(ns > 1736838663000000000);
+/// assert!(ns / 1_000_000 >= time::current_timestamp_millis() - 1000);
+/// ```
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+pub fn current_timestamp_nanos() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .expect("时间错误")
+        .as_nanos() as u64
+}
+
+/// 获取当前时间戳，单位纳秒
+///
+/// `Date.now()` 只精确到毫秒，纳秒部分为 0
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub fn current_timestamp_nanos() -> u64 {
+    js_sys::Date::now() as u64 * 1_000_000
+}
+
+/// 通过时间字符串解析得到 `DateTime<Local>` 结构体
+///
+/// 本地时间因夏令时切换不存在或出现两次时返回 `Error::InvalidTime` 而不会 panic，
+/// 需要自动取其中一个时间时使用 `from_str_with_policy`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::{time, Error};
+/// use lazytool::time::AmbiguityPolicy;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let dt = time::from_str("2025-01-15 18:16:13", fmt).unwrap();
+///
+/// // 按本地时区解析，与 UTC 时间戳相差本地时区的偏移量
+/// let utc = time::to_timestamp_utc("2025-01-15 18:16:13", fmt).unwrap();
+/// assert_eq!(dt.timestamp(), utc - dt.offset().local_minus_utc() as i64);
+/// assert_eq!(dt.format(fmt).to_string(), "2025-01-15 18:16:13");
+///
+/// // 常见时区夏令时切换时的本地时间，结果取决于本地时区，但都不会 panic
+/// for s in ["2024-03-10 02:30:00", "2024-11-03 01:30:00", "2024-03-31 02:30:00", "2024-10-27 02:30:00", "1986-05-04 02:30:00"] {
+///     match time::from_str(s, fmt) {
+///         Ok(_) | Err(Error::InvalidTime(_)) => {}
+///         Err(e) => panic!("unexpected error for {s}: {e}"),
+///     }
+///     assert!(time::from_str_with_policy(s, fmt, AmbiguityPolicy::Earliest).is_ok(), "{s}");
+///     assert!(time::from_str_with_policy(s, fmt, AmbiguityPolicy::Latest).is_ok(), "{s}");
+/// }
 /// ```
 pub fn from_str(s: &str, fmt: &str) -> Result<DateTime<Local>>{
-    let datetime = NaiveDateTime::parse_from_str(s, fmt)?;
+    from_str_with_policy(s, fmt, AmbiguityPolicy::Reject)
+}
+
+/// 通过时间字符串解析得到 `DateTime<Local>` 结构体，并指定夏令时歧义的处理策略
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time::{self, AmbiguityPolicy};
+///
+/// let dt = time::from_str_with_policy("2025-01-15 18:16:13", "%Y-%m-%d %H:%M:%S", AmbiguityPolicy::Earliest).unwrap();
+///
+/// assert_eq!(dt.format("%Y-%m-%d %H:%M:%S").to_string(), "2025-01-15 18:16:13");
+/// ```
+pub fn from_str_with_policy(s: &str, fmt: &str, policy: AmbiguityPolicy) -> Result<DateTime<Local>>{
+    let datetime = parse_naive(s, fmt)?;
     let tz = Local::now().timezone();
     // 将 NaiveDateTime 转换为具有时区的 DateTime
-    let dt = tz.from_local_datetime(&datetime).single().unwrap();
-    Ok(dt)
+    resolve_local(&tz, &datetime, policy)
 }
 
 /// 通过时间字符串解析得到带时区的 `DateTime<Tz>` 结构体
@@ -57,13 +152,39 @@ pub fn from_str(s: &str, fmt: &str) -> Result<DateTime<Local>>{
 /// assert_eq!(dt.timestamp(), 1736936173);
 /// assert_eq!(dt.format("%Y-%m-%d %H:%M:%S").to_string(), "2025-01-15 18:16:13");
 /// ```
+#[cfg(feature = "timezone")]
 pub fn from_str_with_timezone(s: &str, fmt: &str, timezone: &str) -> Result<DateTime<Tz>>{
-    let datetime = NaiveDateTime::parse_from_str(s, fmt)?;
-    // 获取时区
-    let tz: Tz = timezone.parse()?;
+    from_str_with_timezone_policy(s, fmt, timezone, AmbiguityPolicy::Reject)
+}
+
+/// 通过时间字符串解析得到带时区的 `DateTime<Tz>` 结构体，并指定夏令时歧义的处理策略
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time::{self, AmbiguityPolicy};
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+///
+/// // 纽约 2024-11-03 01:30 出现两次
+/// assert!(time::from_str_with_timezone("2024-11-03 01:30:00", fmt, "America/New_York").is_err());
+/// let dt = time::from_str_with_timezone_policy("2024-11-03 01:30:00", fmt, "America/New_York", AmbiguityPolicy::Earliest).unwrap();
+/// assert_eq!(dt.to_rfc3339(), "2024-11-03T01:30:00-04:00");
+/// let dt = time::from_str_with_timezone_policy("2024-11-03 01:30:00", fmt, "America/New_York", AmbiguityPolicy::Latest).unwrap();
+/// assert_eq!(dt.to_rfc3339(), "2024-11-03T01:30:00-05:00");
+///
+/// // 纽约 2024-03-10 02:30 不存在，顺延跳过的时长
+/// assert!(time::from_str_with_timezone("2024-03-10 02:30:00", fmt, "America/New_York").is_err());
+/// let dt = time::from_str_with_timezone_policy("2024-03-10 02:30:00", fmt, "America/New_York", AmbiguityPolicy::Earliest).unwrap();
+/// assert_eq!(dt.to_rfc3339(), "2024-03-10T03:30:00-04:00");
+/// ```
+#[cfg(feature = "timezone")]
+pub fn from_str_with_timezone_policy(s: &str, fmt: &str, timezone: &str, policy: AmbiguityPolicy) -> Result<DateTime<Tz>>{
+    // 先检查时区，使时区错误与时间格式错误互不混淆
+    let tz = parse_timezone(timezone)?;
+    let datetime = parse_naive(s, fmt)?;
     // 将 NaiveDateTime 转换为具有时区的 DateTime
-    let dt = tz.from_local_datetime(&datetime).single().unwrap();
-    Ok(dt)
+    resolve_local(&tz, &datetime, policy)
 }
 
 /// 字符串转为时间戳
@@ -73,11 +194,1552 @@ pub fn from_str_with_timezone(s: &str, fmt: &str, timezone: &str) -> Result<Date
 /// ```
 /// use lazytool::time;
 ///
-/// let dt = time::to_timestamp("2025-01-15 18:16:13", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let ts = time::to_timestamp("2025-01-15 18:16:13", fmt).unwrap();
 ///
-/// assert_eq!(dt, 1736936173);
+/// // 本地时间戳与 UTC 时间戳相差本地时区的偏移量
+/// let utc = time::to_timestamp_utc("2025-01-15 18:16:13", fmt).unwrap();
+/// let offset = time::from_str("2025-01-15 18:16:13", fmt).unwrap().offset().local_minus_utc();
+/// assert_eq!(utc - ts, offset as i64);
 /// ```
 pub fn to_timestamp(s: &str, fmt: &str) -> Result<i64>{
     let dt = from_str(s, fmt)?;
     Ok(dt.timestamp())
 }
+
+/// 按本地时区解析字符串并转为毫秒时间戳，保留格式中 `%.3f`、`%.f` 等解析到的毫秒
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let ms = time::to_timestamp_millis("2025-01-15 18:16:13.123", "%Y-%m-%d %H:%M:%S%.3f").unwrap();
+///
+/// // 与 UTC 时间戳相差本地时区的偏移量，毫秒部分保留
+/// let utc = time::to_timestamp_utc("2025-01-15 18:16:13", fmt).unwrap();
+/// let offset = time::from_str("2025-01-15 18:16:13", fmt).unwrap().offset().local_minus_utc();
+/// assert_eq!(ms, (utc - offset as i64) * 1000 + 123);
+///
+/// let ts = time::to_timestamp("2025-01-15 18:16:13", fmt).unwrap();
+/// assert_eq!(time::to_timestamp_millis("2025-01-15 18:16:13", fmt).unwrap(), ts * 1000);
+///
+/// assert!(time::to_timestamp_millis("2025-01-15", "%Y-%m-%d %H:%M:%S").is_err());
+/// ```
+pub fn to_timestamp_millis(s: &str, fmt: &str) -> Result<i64>{
+    let dt = from_str(s, fmt)?;
+    Ok(dt.timestamp_millis())
+}
+
+/// 按指定时区解析字符串并转为时间戳
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let ts = time::to_timestamp_with_timezone("2025-01-15 18:16:13", fmt, "Asia/Shanghai").unwrap();
+/// assert_eq!(ts, 1736936173);
+///
+/// let err = time::to_timestamp_with_timezone("2025-01-15 18:16:13", fmt, "Asia/Nowhere").unwrap_err();
+/// assert!(err.to_string().contains("Invalid timezone"));
+///
+/// let err = time::to_timestamp_with_timezone("2025/01/15", fmt, "Asia/Shanghai").unwrap_err();
+/// assert!(err.to_string().contains("Invalid datetime"));
+/// ```
+#[cfg(feature = "timezone")]
+pub fn to_timestamp_with_timezone(s: &str, fmt: &str, timezone: &str) -> Result<i64>{
+    let dt = from_str_with_timezone(s, fmt, timezone)?;
+    Ok(dt.timestamp())
+}
+
+/// 按 UTC 时区解析字符串并转为时间戳
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let ts = time::to_timestamp_utc("2025-01-15 10:16:13", "%Y-%m-%d %H:%M:%S").unwrap();
+/// assert_eq!(ts, 1736936173);
+/// ```
+pub fn to_timestamp_utc(s: &str, fmt: &str) -> Result<i64>{
+    let datetime = parse_naive(s, fmt)?;
+    Ok(datetime.and_utc().timestamp())
+}
+
+/// 距离目标时间的倒计时字符串，格式如 `2d 03:15:42`，不足一天时省略天数，已过期返回 `expired`
+///
+/// Examples
+///
+/// ```
+/// use chrono::{Duration, Local};
+/// use lazytool::time;
+///
+/// let target = Local::now() + Duration::days(3);
+/// assert!(time::countdown_string(target).starts_with("2d 23:59:"));
+///
+/// let target = Local::now() - Duration::seconds(1);
+/// assert_eq!(time::countdown_string(target), "expired");
+/// ```
+pub fn countdown_string(target: DateTime<Local>) -> String {
+    countdown_string_from(target, Local::now())
+}
+
+/// 以指定的 `now` 作为当前时间计算倒计时字符串，方便测试
+///
+/// Examples
+///
+/// ```
+/// use chrono::{Duration, Local, TimeZone};
+/// use lazytool::time;
+///
+/// let now = Local.timestamp_opt(1736936173, 0).unwrap();
+///
+/// let target = now + Duration::seconds(2 * 86400 + 3 * 3600 + 15 * 60 + 42);
+/// assert_eq!(time::countdown_string_from(target, now), "2d 03:15:42");
+///
+/// let target = now + Duration::seconds(65);
+/// assert_eq!(time::countdown_string_from(target, now), "00:01:05");
+///
+/// let target = now - Duration::seconds(10);
+/// assert_eq!(time::countdown_string_from(target, now), "expired");
+/// ```
+pub fn countdown_string_from(target: DateTime<Local>, now: DateTime<Local>) -> String {
+    let secs = (target - now).num_seconds();
+    if secs < 0 {
+        return "expired".to_string();
+    }
+
+    let days = secs / 86400;
+    let hours = secs % 86400 / 3600;
+    let minutes = secs % 3600 / 60;
+    let seconds = secs % 60;
+    if days > 0 {
+        format!("{days}d {hours:02}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{hours:02}:{minutes:02}:{seconds:02}")
+    }
+}
+
+/// 将带时区的时间转换到目标时区
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let dt = time::from_str_with_timezone("2025-01-15 10:16:13", "%Y-%m-%d %H:%M:%S", "UTC").unwrap();
+/// let dt = time::convert_timezone(dt, "Asia/Shanghai").unwrap();
+///
+/// assert_eq!(dt.format("%Y-%m-%d %H:%M:%S").to_string(), "2025-01-15 18:16:13");
+/// assert!(time::convert_timezone(dt, "Mars/Olympus").is_err());
+/// ```
+#[cfg(feature = "timezone")]
+pub fn convert_timezone(dt: DateTime<Tz>, target: &str) -> Result<DateTime<Tz>> {
+    let tz = parse_timezone(target)?;
+    Ok(dt.with_timezone(&tz))
+}
+
+/// 按源时区解析时间字符串，并以相同格式输出目标时区的时间字符串
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let s = time::convert_str("2025-01-15 10:16:13", fmt, "UTC", "Asia/Shanghai").unwrap();
+/// assert_eq!(s, "2025-01-15 18:16:13");
+///
+/// // 纽约冬令时为 UTC-5，夏令时为 UTC-4
+/// let s = time::convert_str("2025-01-15 12:00:00", fmt, "UTC", "America/New_York").unwrap();
+/// assert_eq!(s, "2025-01-15 07:00:00");
+/// let s = time::convert_str("2025-07-15 12:00:00", fmt, "UTC", "America/New_York").unwrap();
+/// assert_eq!(s, "2025-07-15 08:00:00");
+///
+/// let err = time::convert_str("2025-01-15 12:00:00", fmt, "UTC", "Mars/Olympus").unwrap_err();
+/// assert!(err.to_string().contains("Mars/Olympus"));
+/// ```
+#[cfg(feature = "timezone")]
+pub fn convert_str(s: &str, fmt: &str, from: &str, to: &str) -> Result<String> {
+    let dt = from_str_with_timezone(s, fmt, from)?;
+    let dt = convert_timezone(dt, to)?;
+    Ok(dt.format(fmt).to_string())
+}
+
+/// 时区当前相对 UTC 的偏移秒数，夏令时期间包含夏令时的偏移
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// assert_eq!(time::utc_offset_seconds("Asia/Shanghai").unwrap(), 8 * 3600);
+/// assert_eq!(time::utc_offset_seconds("UTC").unwrap(), 0);
+/// assert!(time::utc_offset_seconds("Mars/Olympus").is_err());
+/// ```
+#[cfg(feature = "timezone")]
+pub fn utc_offset_seconds(timezone: &str) -> Result<i32> {
+    utc_offset_seconds_at(timezone, chrono::Utc::now().timestamp())
+}
+
+/// 时区在指定时间戳（秒）时相对 UTC 的偏移秒数
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// // 纽约冬令时为 UTC-5，夏令时为 UTC-4
+/// let winter = time::to_timestamp_utc("2025-01-15 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// let summer = time::to_timestamp_utc("2025-07-15 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// assert_eq!(time::utc_offset_seconds_at("America/New_York", winter).unwrap(), -5 * 3600);
+/// assert_eq!(time::utc_offset_seconds_at("America/New_York", summer).unwrap(), -4 * 3600);
+/// assert_eq!(time::utc_offset_seconds_at("Asia/Kolkata", winter).unwrap(), 5 * 3600 + 1800);
+/// ```
+#[cfg(feature = "timezone")]
+pub fn utc_offset_seconds_at(timezone: &str, timestamp: i64) -> Result<i32> {
+    let tz = parse_timezone(timezone)?;
+    let dt = DateTime::from_timestamp(timestamp, 0)
+        .ok_or(Error::TimestampOutOfRange(timestamp))?;
+    Ok(dt.with_timezone(&tz).offset().fix().local_minus_utc())
+}
+
+/// 解析 RFC3339 时间字符串，保留原始时区偏移量
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let dt = time::from_rfc3339("2025-01-15T18:16:13+08:00").unwrap();
+/// assert_eq!(dt.timestamp(), 1736936173);
+/// assert_eq!(dt.offset().local_minus_utc(), 8 * 3600);
+/// assert_eq!(time::to_rfc3339(&dt), "2025-01-15T18:16:13+08:00");
+///
+/// let dt = time::from_rfc3339("2025-01-15T10:16:13Z").unwrap();
+/// assert_eq!(dt.timestamp(), 1736936173);
+/// assert_eq!(time::to_rfc3339(&dt), "2025-01-15T10:16:13Z");
+///
+/// let dt = time::from_rfc3339("2025-01-15T18:16:13.123+08:00").unwrap();
+/// assert_eq!(dt.timestamp_subsec_millis(), 123);
+/// assert_eq!(time::to_rfc3339(&dt), "2025-01-15T18:16:13.123+08:00");
+///
+/// assert!(time::from_rfc3339("2025-01-15 18:16:13").is_err());
+/// ```
+pub fn from_rfc3339(s: &str) -> Result<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(s).map_err(|_| parse_error(s, "RFC3339"))
+}
+
+/// 宽松地解析 RFC3339 时间字符串，允许使用空格代替 `T` 以及省略秒
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let dt = time::from_rfc3339_lenient("2025-01-15 18:16:13+08:00").unwrap();
+/// assert_eq!(dt.timestamp(), 1736936173);
+///
+/// let dt = time::from_rfc3339_lenient("2025-01-15 10:16Z").unwrap();
+/// assert_eq!(time::to_rfc3339(&dt), "2025-01-15T10:16:00Z");
+///
+/// assert!(time::from_rfc3339_lenient("2025-01-15 10:16").is_err());
+/// ```
+pub fn from_rfc3339_lenient(s: &str) -> Result<DateTime<FixedOffset>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt);
+    }
+
+    // 将 `Z` 统一为 `+00:00` 以便使用 `%:z` 匹配
+    let normalized = match s.strip_suffix(['Z', 'z']) {
+        Some(rest) => format!("{rest}+00:00"),
+        None => s.to_string(),
+    };
+    const FORMATS: [&str; 4] = [
+        "%Y-%m-%dT%H:%M:%S%.f%:z",
+        "%Y-%m-%d %H:%M:%S%.f%:z",
+        "%Y-%m-%dT%H:%M%:z",
+        "%Y-%m-%d %H:%M%:z",
+    ];
+    FORMATS.iter()
+        .find_map(|fmt| DateTime::parse_from_str(&normalized, fmt).ok())
+        .ok_or_else(|| parse_error(s, "RFC3339"))
+}
+
+/// 格式化为 RFC3339 时间字符串，UTC 时间使用 `Z` 结尾，仅在有亚秒时输出小数部分
+pub fn to_rfc3339<T: TimeZone>(dt: &DateTime<T>) -> String
+    where T::Offset: std::fmt::Display,
+{
+    dt.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// 获取某年某月中所有星期几的日期
+///
+/// Examples
+///
+/// ```
+/// use chrono::{NaiveDate, Weekday};
+/// use lazytool::time;
+///
+/// let days = time::weekdays_in_month(2025, 1, Weekday::Mon).unwrap();
+/// assert_eq!(days.len(), 4);
+/// assert_eq!(days[0], NaiveDate::from_ymd_opt(2025, 1, 6).unwrap());
+/// assert_eq!(days[3], NaiveDate::from_ymd_opt(2025, 1, 27).unwrap());
+///
+/// let days = time::weekdays_in_month(2025, 3, Weekday::Mon).unwrap();
+/// assert_eq!(days.len(), 5);
+/// assert_eq!(days[4], NaiveDate::from_ymd_opt(2025, 3, 31).unwrap());
+///
+/// assert!(time::weekdays_in_month(2025, 13, Weekday::Mon).is_err());
+/// ```
+pub fn weekdays_in_month(year: i32, month: u32, weekday: Weekday) -> Result<Vec<NaiveDate>> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| Error::InvalidTime(format!("invalid month {year}-{month}")))?;
+    // 当月第一个符合的日期
+    let offset = (7 + weekday.num_days_from_monday() - first.weekday().num_days_from_monday()) % 7;
+    let dates = first.iter_days()
+        .skip(offset as usize)
+        .step_by(7)
+        .take_while(|d| d.month() == month)
+        .collect();
+    Ok(dates)
+}
+
+/// 是否为周末（周六或周日），时间戳可使用 `is_weekend_timestamp`
+///
+/// Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use lazytool::time;
+///
+/// assert!(time::is_weekend(&NaiveDate::from_ymd_opt(2025, 1, 18).unwrap()));
+/// assert!(!time::is_weekend(&NaiveDate::from_ymd_opt(2025, 1, 17).unwrap()));
+/// ```
+pub fn is_weekend<D: Datelike>(dt: &D) -> bool {
+    matches!(dt.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// 时间戳（秒）在本地时间中是星期几
+///
+/// Examples
+///
+/// ```
+/// use chrono::Weekday;
+/// use lazytool::time;
+///
+/// let ts = time::to_timestamp("2025-01-18 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// assert_eq!(time::weekday(ts).unwrap(), Weekday::Sat);
+/// assert!(time::weekday(i64::MAX).is_err());
+/// ```
+pub fn weekday(ts: i64) -> Result<Weekday> {
+    Ok(Timestamp::with_unit(ts, TimestampUnit::Seconds).to_datetime()?.weekday())
+}
+
+/// 时间戳（秒）在本地时间中是否为周末
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let ts = time::to_timestamp("2025-01-19 23:59:59", "%Y-%m-%d %H:%M:%S").unwrap();
+/// assert!(time::is_weekend_timestamp(ts).unwrap());
+/// assert!(!time::is_weekend_timestamp(ts + 1).unwrap());
+/// ```
+pub fn is_weekend_timestamp(ts: i64) -> Result<bool> {
+    Ok(matches!(weekday(ts)?, Weekday::Sat | Weekday::Sun))
+}
+
+/// 时间戳（秒）在指定时区中是星期几
+///
+/// Examples
+///
+/// ```
+/// use chrono::Weekday;
+/// use lazytool::time;
+///
+/// // 上海已是周六，纽约还是周五
+/// let ts = time::to_timestamp_utc("2025-01-17 20:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// assert_eq!(time::weekday_with_timezone(ts, "Asia/Shanghai").unwrap(), Weekday::Sat);
+/// assert_eq!(time::weekday_with_timezone(ts, "America/New_York").unwrap(), Weekday::Fri);
+/// assert!(time::weekday_with_timezone(ts, "Mars/Olympus").is_err());
+/// ```
+#[cfg(feature = "timezone")]
+pub fn weekday_with_timezone(ts: i64, timezone: &str) -> Result<Weekday> {
+    let tz = parse_timezone(timezone)?;
+    let dt = Timestamp::with_unit(ts, TimestampUnit::Seconds).to_datetime()?;
+    Ok(dt.with_timezone(&tz).weekday())
+}
+
+/// 时间戳（秒）在指定时区中是否为周末
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let ts = time::to_timestamp_utc("2025-01-17 20:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+/// assert!(time::is_weekend_timestamp_with_timezone(ts, "Asia/Shanghai").unwrap());
+/// assert!(!time::is_weekend_timestamp_with_timezone(ts, "America/New_York").unwrap());
+/// ```
+#[cfg(feature = "timezone")]
+pub fn is_weekend_timestamp_with_timezone(ts: i64, timezone: &str) -> Result<bool> {
+    Ok(matches!(weekday_with_timezone(ts, timezone)?, Weekday::Sat | Weekday::Sun))
+}
+
+/// 是否为工作日，即不是周末也不在节假日列表中
+pub fn is_business_day(date: NaiveDate, holidays: &[NaiveDate]) -> bool {
+    !is_weekend(&date) && !holidays.contains(&date)
+}
+
+/// 增加 `n` 个工作日，`n` 可以为负数，保留原时间的时分秒
+///
+/// `n` 为 0 时，如果当天不是工作日则顺延到下一个工作日，例如周六加 0 个工作日得到下周一；
+/// `n` 不为 0 时从次日（或前一日）开始计数，跳过周末和节假日，日期超出 chrono 支持的范围时返回错误
+///
+/// Examples
+///
+/// ```
+/// use chrono::{Local, NaiveDate, TimeZone};
+/// use lazytool::{time, Error};
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let friday = Local.with_ymd_and_hms(2025, 1, 17, 10, 0, 0).unwrap();
+/// let saturday = Local.with_ymd_and_hms(2025, 1, 18, 10, 0, 0).unwrap();
+/// let tuesday = NaiveDate::from_ymd_opt(2025, 1, 21).unwrap();
+///
+/// let dt = time::add_business_days(friday, 1, &[]).unwrap();
+/// assert_eq!(dt.format(fmt).to_string(), "2025-01-20 10:00:00");
+///
+/// // 跨过周末和周二的节假日
+/// let dt = time::add_business_days(friday, 2, &[tuesday]).unwrap();
+/// assert_eq!(dt.format(fmt).to_string(), "2025-01-22 10:00:00");
+///
+/// let dt = time::add_business_days(saturday, 0, &[]).unwrap();
+/// assert_eq!(dt.format(fmt).to_string(), "2025-01-20 10:00:00");
+///
+/// let dt = time::add_business_days(saturday, -1, &[]).unwrap();
+/// assert_eq!(dt.format(fmt).to_string(), "2025-01-17 10:00:00");
+///
+/// // 超出日期范围时返回错误
+/// let last = NaiveDate::MAX - chrono::Duration::days(3);
+/// let dt = Local.from_utc_datetime(&last.and_hms_opt(12, 0, 0).unwrap());
+/// assert!(matches!(time::add_business_days(dt, 10, &[]), Err(Error::InvalidTime(_))));
+/// let holidays: Vec<_> = (0..5).map(|i| NaiveDate::MAX - chrono::Duration::days(i)).collect();
+/// assert!(matches!(time::add_business_days(dt, 0, &holidays), Err(Error::InvalidTime(_))));
+/// ```
+pub fn add_business_days(dt: DateTime<Local>, n: i64, holidays: &[NaiveDate]) -> Result<DateTime<Local>> {
+    let out_of_range = || Error::InvalidTime(format!("date out of range when adding {n} business days to {dt}"));
+    let mut date = dt.date_naive();
+    if n == 0 {
+        while !is_business_day(date, holidays) {
+            date = date.succ_opt().ok_or_else(out_of_range)?;
+        }
+    }
+
+    let step = if n < 0 { -1 } else { 1 };
+    let mut remaining = n.unsigned_abs();
+    while remaining > 0 {
+        date = date.checked_add_signed(Duration::days(step)).ok_or_else(out_of_range)?;
+        if is_business_day(date, holidays) {
+            remaining -= 1;
+        }
+    }
+
+    Ok(at_local(date, dt.time()))
+}
+
+/// 计算 `[start, end)` 之间的工作日数量，`end` 早于 `start` 时为负数，落在周末的节假日不重复计算
+///
+/// Examples
+///
+/// ```
+/// use chrono::{Local, NaiveDate, TimeZone};
+/// use lazytool::time;
+///
+/// let friday = Local.with_ymd_and_hms(2025, 1, 17, 10, 0, 0).unwrap();
+/// let next_friday = Local.with_ymd_and_hms(2025, 1, 24, 10, 0, 0).unwrap();
+/// let holidays = [
+///     NaiveDate::from_ymd_opt(2025, 1, 19).unwrap(), // 周日
+///     NaiveDate::from_ymd_opt(2025, 1, 21).unwrap(), // 周二
+/// ];
+///
+/// assert_eq!(time::business_days_between(friday, next_friday, &[]), 5);
+/// assert_eq!(time::business_days_between(friday, next_friday, &holidays), 4);
+/// assert_eq!(time::business_days_between(next_friday, friday, &holidays), -4);
+/// ```
+pub fn business_days_between(start: DateTime<Local>, end: DateTime<Local>, holidays: &[NaiveDate]) -> i64 {
+    let (from, to, sign) = if start <= end {
+        (start.date_naive(), end.date_naive(), 1)
+    } else {
+        (end.date_naive(), start.date_naive(), -1)
+    };
+    let count = from.iter_days()
+        .take_while(|d| *d < to)
+        .filter(|d| is_business_day(*d, holidays))
+        .count() as i64;
+    count * sign
+}
+
+/// 通过时间字符串增加工作日，结果使用相同格式输出
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let s = time::add_business_days_str("2025-01-17 10:00:00", fmt, 1, &[]).unwrap();
+/// assert_eq!(s, "2025-01-20 10:00:00");
+/// ```
+pub fn add_business_days_str(s: &str, fmt: &str, n: i64, holidays: &[NaiveDate]) -> Result<String> {
+    let dt = from_str(s, fmt)?;
+    Ok(add_business_days(dt, n, holidays)?.format(fmt).to_string())
+}
+
+/// 通过时间字符串计算工作日数量
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let n = time::business_days_between_str("2025-01-17 00:00:00", "2025-01-24 00:00:00", fmt, &[]).unwrap();
+/// assert_eq!(n, 5);
+/// ```
+pub fn business_days_between_str(start: &str, end: &str, fmt: &str, holidays: &[NaiveDate]) -> Result<i64> {
+    Ok(business_days_between(from_str(start, fmt)?, from_str(end, fmt)?, holidays))
+}
+
+/// 简单的周期计划，用于计算下一次执行时间
+///
+/// Examples
+///
+/// ```
+/// use chrono::{Duration, Local, TimeZone, Weekday};
+/// use lazytool::time::Schedule;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let now = Local.with_ymd_and_hms(2025, 1, 15, 18, 16, 13).unwrap();
+///
+/// // 今天的 03:30 已过，顺延到明天
+/// let schedule = Schedule::daily_at("03:30").unwrap();
+/// assert_eq!(schedule.next_after(now).format(fmt).to_string(), "2025-01-16 03:30:00");
+///
+/// let schedule = Schedule::weekly_on(Weekday::Mon, "09:00").unwrap();
+/// let times: Vec<_> = schedule.upcoming(now, 2).iter().map(|dt| dt.format(fmt).to_string()).collect();
+/// assert_eq!(times, ["2025-01-20 09:00:00", "2025-01-27 09:00:00"]);
+///
+/// // 按锚点对齐，不会因为计算时间而漂移
+/// let anchor = Local.with_ymd_and_hms(2025, 1, 15, 18, 0, 0).unwrap();
+/// let schedule = Schedule::every_from(anchor, Duration::minutes(15)).unwrap();
+/// assert_eq!(schedule.next_after(now).format(fmt).to_string(), "2025-01-15 18:30:00");
+///
+/// assert!(Schedule::daily_at("25:00").is_err());
+/// assert!(Schedule::every(Duration::zero()).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    /// 每天的固定时间
+    Daily(NaiveTime),
+    /// 每周某天的固定时间
+    Weekly(Weekday, NaiveTime),
+    /// 从锚点开始的固定间隔
+    Every { anchor: DateTime<Local>, interval: Duration },
+}
+
+impl Schedule {
+    /// 每天在 `HH:MM` 或 `HH:MM:SS` 执行
+    pub fn daily_at(time: &str) -> Result<Self> {
+        Ok(Self::Daily(parse_time_of_day(time)?))
+    }
+
+    /// 每周在指定的星期几的 `HH:MM` 或 `HH:MM:SS` 执行
+    pub fn weekly_on(weekday: Weekday, time: &str) -> Result<Self> {
+        Ok(Self::Weekly(weekday, parse_time_of_day(time)?))
+    }
+
+    /// 以 UNIX 纪元为锚点，每隔 `interval` 执行
+    pub fn every(interval: Duration) -> Result<Self> {
+        Self::every_from(DateTime::UNIX_EPOCH.with_timezone(&Local), interval)
+    }
+
+    /// 以 `anchor` 为锚点，每隔 `interval` 执行
+    pub fn every_from(anchor: DateTime<Local>, interval: Duration) -> Result<Self> {
+        if interval <= Duration::zero() {
+            return Err(Error::InvalidTime(format!("invalid schedule interval {interval}")));
+        }
+        Ok(Self::Every { anchor, interval })
+    }
+
+    /// `dt` 之后（不含 `dt`）的下一次执行时间
+    pub fn next_after(&self, dt: DateTime<Local>) -> DateTime<Local> {
+        match self {
+            Self::Daily(time) => {
+                let mut date = dt.date_naive();
+                loop {
+                    let next = at_local(date, *time);
+                    if next > dt {
+                        return next;
+                    }
+                    date += Duration::days(1);
+                }
+            }
+            Self::Weekly(weekday, time) => {
+                let days = (7 + weekday.num_days_from_monday() - dt.weekday().num_days_from_monday()) % 7;
+                let mut date = dt.date_naive() + Duration::days(days as i64);
+                loop {
+                    let next = at_local(date, *time);
+                    if next > dt {
+                        return next;
+                    }
+                    date += Duration::weeks(1);
+                }
+            }
+            Self::Every { anchor, interval } => {
+                if dt < *anchor {
+                    return *anchor;
+                }
+                let elapsed = (dt - *anchor).num_milliseconds();
+                let step = interval.num_milliseconds();
+                *anchor + Duration::milliseconds((elapsed / step + 1) * step)
+            }
+        }
+    }
+
+    /// `dt` 之后的 `n` 次执行时间
+    pub fn upcoming(&self, dt: DateTime<Local>, n: usize) -> Vec<DateTime<Local>> {
+        let mut times = Vec::with_capacity(n);
+        let mut current = dt;
+        for _ in 0..n {
+            current = self.next_after(current);
+            times.push(current);
+        }
+        times
+    }
+}
+
+/// 解析 `HH:MM` 或 `HH:MM:SS` 格式的时间
+fn parse_time_of_day(s: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .map_err(|_| parse_error(s, "%H:%M"))
+}
+
+/// 将日期和时间组合为本地时间，夏令时歧义时取较早的时间
+fn at_local(date: NaiveDate, time: NaiveTime) -> DateTime<Local> {
+    let naive = date.and_time(time);
+    resolve_local(&Local, &naive, AmbiguityPolicy::Earliest)
+        .unwrap_or_else(|_| Local.from_utc_datetime(&naive))
+}
+
+/// 将秒级时间戳转为 UTC 的 `NaiveDateTime`，超出范围时返回错误
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let dt = time::timestamp_to_naive(1736936173).unwrap();
+/// assert_eq!(dt.format("%Y-%m-%d %H:%M:%S").to_string(), "2025-01-15 10:16:13");
+///
+/// let dt = time::timestamp_to_naive(-86400).unwrap();
+/// assert_eq!(dt.format("%Y-%m-%d").to_string(), "1969-12-31");
+///
+/// assert!(time::timestamp_to_naive(i64::MAX).is_err());
+/// ```
+pub fn timestamp_to_naive(ts: i64) -> Result<NaiveDateTime> {
+    DateTime::from_timestamp(ts, 0)
+        .map(|dt| dt.naive_utc())
+        .ok_or(Error::TimestampOutOfRange(ts))
+}
+
+/// 时间戳（秒）按本地时间增加 `months` 个月，可以为负数，保留时分秒
+///
+/// 目标月份没有对应的日期时取该月最后一天，如 1 月 31 日加一个月为 2 月 28 日（闰年为 29 日），
+/// 结果落在夏令时跳过的时间内时顺延，超出范围时返回错误
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let ts = time::to_timestamp("2025-01-31 08:00:00", fmt).unwrap();
+/// assert_eq!(time::add_months(ts, 1).unwrap(), time::to_timestamp("2025-02-28 08:00:00", fmt).unwrap());
+/// assert_eq!(time::add_months(ts, -2).unwrap(), time::to_timestamp("2024-11-30 08:00:00", fmt).unwrap());
+///
+/// // 闰日加一年为 2 月 28 日
+/// let ts = time::to_timestamp("2024-02-29 08:00:00", fmt).unwrap();
+/// assert_eq!(time::add_months(ts, 12).unwrap(), time::to_timestamp("2025-02-28 08:00:00", fmt).unwrap());
+/// assert_eq!(time::add_months(ts, 48).unwrap(), time::to_timestamp("2028-02-29 08:00:00", fmt).unwrap());
+///
+/// assert!(time::add_months(0, i32::MAX).is_err());
+/// ```
+pub fn add_months(ts: i64, months: i32) -> Result<i64> {
+    shift_local(ts, |dt| {
+        let n = Months::new(months.unsigned_abs());
+        if months >= 0 { dt.checked_add_months(n) } else { dt.checked_sub_months(n) }
+    })
+}
+
+/// 时间戳（秒）按本地时间增加 `days` 天，可以为负数，跨越夏令时切换时保留时分秒，
+/// 超出本地时间的范围时按每天 86400 秒计算并在溢出时饱和
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let ts = time::to_timestamp("2024-02-28 08:00:00", fmt).unwrap();
+/// assert_eq!(time::add_days(ts, 1), time::to_timestamp("2024-02-29 08:00:00", fmt).unwrap());
+/// assert_eq!(time::add_days(ts, 366), time::to_timestamp("2025-02-28 08:00:00", fmt).unwrap());
+/// assert_eq!(time::add_days(ts, -59), time::to_timestamp("2023-12-31 08:00:00", fmt).unwrap());
+///
+/// assert_eq!(time::add_days(i64::MAX, 1), i64::MAX);
+/// ```
+pub fn add_days(ts: i64, days: i64) -> i64 {
+    shift_local(ts, |dt| dt.checked_add_signed(Duration::try_days(days)?))
+        .unwrap_or_else(|_| ts.saturating_add(days.saturating_mul(86400)))
+}
+
+/// 在本地时间上调整时间戳，夏令时导致的歧义取较早的时间，不存在的时间顺延
+fn shift_local<F>(ts: i64, f: F) -> Result<i64>
+    where F: FnOnce(NaiveDateTime) -> Option<NaiveDateTime>,
+{
+    let dt = Timestamp::with_unit(ts, TimestampUnit::Seconds).to_datetime()?;
+    let shifted = f(dt.naive_local())
+        .ok_or_else(|| Error::InvalidTime(format!("date out of range when shifting timestamp {ts}")))?;
+    Ok(resolve_local(&Local, &shifted, AmbiguityPolicy::Earliest)?.timestamp())
+}
+
+/// 将时间戳格式化为相对当前时间的描述，如 `just now`、`3 days ago`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let now = time::current_timestamp() as i64;
+/// assert_eq!(time::time_ago(now), "just now");
+/// assert_eq!(time::time_ago(now - 3 * 86400), "3 days ago");
+/// ```
+pub fn time_ago(ts: i64) -> String {
+    time_ago_from(ts, current_timestamp() as i64)
+}
+
+/// 以指定的 `now` 作为当前时间将时间戳格式化为相对时间描述
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let now = 1736936173;
+/// assert_eq!(time::time_ago_from(now - 30, now), "just now");
+/// assert_eq!(time::time_ago_from(now - 60, now), "1 minute ago");
+/// assert_eq!(time::time_ago_from(now - 5 * 3600, now), "5 hours ago");
+/// assert_eq!(time::time_ago_from(now - 45 * 86400, now), "1 month ago");
+/// assert_eq!(time::time_ago_from(now - 800 * 86400, now), "2 years ago");
+/// assert_eq!(time::time_ago_from(now + 2 * 86400, now), "in 2 days");
+/// ```
+pub fn time_ago_from(ts: i64, now: i64) -> String {
+    humanize_with(ts, now, Locale::En)
+}
+
+/// 相对时间描述使用的语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    /// 中文，如 `刚刚`、`3分钟前`
+    #[default]
+    Zh,
+    /// 英文，如 `just now`、`3 minutes ago`
+    En,
+}
+
+/// 将时间戳格式化为相对当前时间的中文描述，如 `刚刚`、`3分钟前`、`2天前`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let now = time::current_timestamp() as i64;
+/// assert_eq!(time::humanize(now), "刚刚");
+/// assert_eq!(time::humanize(now - 3 * 86400), "3天前");
+/// ```
+pub fn humanize(ts: i64) -> String {
+    humanize_with(ts, current_timestamp() as i64, Locale::Zh)
+}
+
+/// 将本地时间格式化为相对当前时间的中文描述，见 `humanize`
+///
+/// Examples
+///
+/// ```
+/// use chrono::{Duration, Local};
+/// use lazytool::time;
+///
+/// assert_eq!(time::humanize_dt(Local::now() - Duration::minutes(3)), "3分钟前");
+/// assert_eq!(time::humanize_dt(Local::now() + Duration::hours(2) + Duration::seconds(5)), "2小时后");
+/// ```
+pub fn humanize_dt(dt: DateTime<Local>) -> String {
+    humanize_with(dt.timestamp(), Local::now().timestamp(), Locale::Zh)
+}
+
+/// 以指定的 `now` 作为当前时间，按指定语言将时间戳格式化为相对时间描述
+///
+/// 不足一分钟为 `刚刚`，之后依次按分钟、小时、天、月（30 天）、年（365 天）向下取整，晚于 `now` 时为 `后`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time::{self, Locale};
+///
+/// let now = 1736936173;
+/// assert_eq!(time::humanize_with(now - 30, now, Locale::Zh), "刚刚");
+/// assert_eq!(time::humanize_with(now - 3 * 60, now, Locale::Zh), "3分钟前");
+/// assert_eq!(time::humanize_with(now - 5 * 3600, now, Locale::Zh), "5小时前");
+/// assert_eq!(time::humanize_with(now - 45 * 86400, now, Locale::Zh), "1个月前");
+/// assert_eq!(time::humanize_with(now - 800 * 86400, now, Locale::Zh), "2年前");
+/// assert_eq!(time::humanize_with(now + 2 * 86400, now, Locale::Zh), "2天后");
+///
+/// assert_eq!(time::humanize_with(now - 3 * 60, now, Locale::En), "3 minutes ago");
+/// assert_eq!(time::humanize_with(now + 86400, now, Locale::En), "in 1 day");
+/// ```
+pub fn humanize_with(ts: i64, now: i64, locale: Locale) -> String {
+    let secs = now.saturating_sub(ts);
+    let abs = secs.unsigned_abs();
+    let (value, en, zh) = match abs {
+        0..=59 => {
+            return match locale {
+                Locale::Zh => "刚刚".to_string(),
+                Locale::En => "just now".to_string(),
+            };
+        }
+        60..=3599 => (abs / 60, "minute", "分钟"),
+        3600..=86399 => (abs / 3600, "hour", "小时"),
+        86400..=2591999 => (abs / 86400, "day", "天"),
+        2592000..=31535999 => (abs / 2592000, "month", "个月"),
+        _ => (abs / 31536000, "year", "年"),
+    };
+    match (locale, secs >= 0) {
+        (Locale::Zh, true) => format!("{value}{zh}前"),
+        (Locale::Zh, false) => format!("{value}{zh}后"),
+        (Locale::En, past) => {
+            let plural = if value == 1 { "" } else { "s" };
+            if past {
+                format!("{value} {en}{plural} ago")
+            } else {
+                format!("in {value} {en}{plural}")
+            }
+        }
+    }
+}
+
+/// 时间戳单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampUnit {
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl TimestampUnit {
+    /// 每秒包含的单位数
+    fn per_second(self) -> i64 {
+        match self {
+            Self::Seconds => 1,
+            Self::Millis => 1_000,
+            Self::Micros => 1_000_000,
+            Self::Nanos => 1_000_000_000,
+        }
+    }
+}
+
+/// 自动识别单位的时间戳
+///
+/// 按绝对值大小判断单位：
+///
+/// | 绝对值范围 | 单位 | 对应的最大年份 |
+/// | --- | --- | --- |
+/// | `< 10^11` | 秒 | 约 5138 年 |
+/// | `< 10^14` | 毫秒 | 约 5138 年 |
+/// | `< 10^17` | 微秒 | 约 5138 年 |
+/// | 其他 | 纳秒 | |
+///
+/// 因此 2001 年的毫秒时间戳 `978307200000` 会识别为毫秒，而不是 33000 年前后的秒，负数表示纪元之前
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time::{Timestamp, TimestampUnit};
+///
+/// let ts = Timestamp::detect(1736936173);
+/// assert_eq!(ts.unit(), TimestampUnit::Seconds);
+///
+/// let ts = Timestamp::detect(1736936173123);
+/// assert_eq!(ts.unit(), TimestampUnit::Millis);
+/// assert_eq!((ts.secs(), ts.nanos()), (1736936173, 123_000_000));
+/// assert_eq!(ts.to_millis(), 1736936173123);
+///
+/// let ts = Timestamp::detect(1736936173123456);
+/// assert_eq!(ts.unit(), TimestampUnit::Micros);
+/// assert_eq!(ts.to_datetime().unwrap().timestamp_subsec_micros(), 123456);
+///
+/// let ts = Timestamp::detect(1736936173123456789);
+/// assert_eq!(ts.unit(), TimestampUnit::Nanos);
+/// assert_eq!(ts.nanos(), 123456789);
+///
+/// // 边界：2001 年的毫秒与 5138 年之前的秒
+/// assert_eq!(Timestamp::detect(978307200000).unit(), TimestampUnit::Millis);
+/// assert_eq!(Timestamp::detect(99_999_999_999).unit(), TimestampUnit::Seconds);
+///
+/// // 毫秒超出 i64 范围时取边界值
+/// assert_eq!(Timestamp::with_unit(i64::MAX, TimestampUnit::Seconds).to_millis(), i64::MAX);
+/// assert_eq!(Timestamp::with_unit(i64::MIN, TimestampUnit::Seconds).to_millis(), i64::MIN);
+/// assert_eq!(Timestamp::with_unit(i64::MAX, TimestampUnit::Micros).to_millis(), i64::MAX / 1000);
+///
+/// // 纪元之前
+/// let ts = Timestamp::detect(-1500);
+/// assert_eq!((ts.secs(), ts.nanos()), (-1500, 0));
+/// let ts = Timestamp::detect(-1_000_000_000_500);
+/// assert_eq!(ts.unit(), TimestampUnit::Millis);
+/// assert_eq!((ts.secs(), ts.nanos()), (-1_000_000_001, 500_000_000));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp {
+    secs: i64,
+    nanos: u32,
+    unit: TimestampUnit,
+}
+
+impl Timestamp {
+    /// 根据数值大小识别单位并转换为秒和纳秒
+    pub fn detect(ts: i64) -> Self {
+        let abs = ts.unsigned_abs();
+        let unit = if abs < 100_000_000_000 {
+            TimestampUnit::Seconds
+        } else if abs < 100_000_000_000_000 {
+            TimestampUnit::Millis
+        } else if abs < 100_000_000_000_000_000 {
+            TimestampUnit::Micros
+        } else {
+            TimestampUnit::Nanos
+        };
+        Self::with_unit(ts, unit)
+    }
+
+    /// 按指定单位转换
+    pub fn with_unit(ts: i64, unit: TimestampUnit) -> Self {
+        let per_second = unit.per_second();
+        let secs = ts.div_euclid(per_second);
+        let nanos = ts.rem_euclid(per_second) * (1_000_000_000 / per_second);
+        Self { secs, nanos: nanos as u32, unit }
+    }
+
+    /// 识别出的单位
+    pub fn unit(&self) -> TimestampUnit {
+        self.unit
+    }
+
+    /// 秒
+    pub fn secs(&self) -> i64 {
+        self.secs
+    }
+
+    /// 秒以下的纳秒部分
+    pub fn nanos(&self) -> u32 {
+        self.nanos
+    }
+
+    /// 毫秒时间戳，超出 i64 范围时取 `i64::MAX` 或 `i64::MIN`
+    pub fn to_millis(&self) -> i64 {
+        self.secs.saturating_mul(1000).saturating_add((self.nanos / 1_000_000) as i64)
+    }
+
+    /// 转为本地时间
+    pub fn to_datetime(&self) -> Result<DateTime<Local>> {
+        DateTime::from_timestamp(self.secs, self.nanos)
+            .map(|dt| dt.with_timezone(&Local))
+            .ok_or(Error::TimestampOutOfRange(self.secs))
+    }
+}
+
+/// 将秒或毫秒（以及微秒、纳秒）时间戳统一转为秒，不足一秒的部分向下取整
+///
+/// 按 `Timestamp::detect` 的规则识别单位：绝对值小于 `10^11` 视为秒，即 5138-11-16 之前的秒级时间戳；
+/// 小于 `10^14` 视为毫秒，即 1973-03-03 之后的毫秒级时间戳，更大的数值依次视为微秒和纳秒
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// assert_eq!(time::normalize_timestamp(1736936173), 1736936173);
+/// assert_eq!(time::normalize_timestamp(1736936173000), 1736936173);
+/// assert_eq!(time::normalize_timestamp(1736936173999), 1736936173);
+/// assert_eq!(time::normalize_timestamp(1736936173123456), 1736936173);
+/// assert_eq!(time::normalize_timestamp(-1500), -1500);
+/// ```
+pub fn normalize_timestamp(value: i64) -> i64 {
+    Timestamp::detect(value).secs()
+}
+
+/// 将秒或毫秒（以及微秒、纳秒）时间戳统一转为毫秒，识别规则与 `normalize_timestamp` 相同
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// assert_eq!(time::normalize_timestamp_millis(1736936173), 1736936173000);
+/// assert_eq!(time::normalize_timestamp_millis(1736936173123), 1736936173123);
+/// assert_eq!(time::normalize_timestamp_millis(1736936173123456), 1736936173123);
+/// ```
+pub fn normalize_timestamp_millis(value: i64) -> i64 {
+    Timestamp::detect(value).to_millis()
+}
+
+/// 解析中文日期时间，如 `2025年1月15日 18时16分13秒`
+///
+/// 支持省略年份（默认今年）与省略时间（默认 `00:00:00`），小时可以用 `时` 或 `点`，
+/// 日可以用 `日` 或 `号`，数字可以是一位或两位，也支持全角数字
+///
+/// Examples
+///
+/// ```
+/// use chrono::{Datelike, Local};
+/// use lazytool::{time, Error};
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+///
+/// let dt = time::from_cn_str("2025年1月15日 18时16分13秒").unwrap();
+/// assert_eq!(dt.format(fmt).to_string(), "2025-01-15 18:16:13");
+///
+/// let dt = time::from_cn_str("2025年01月15日18点16分").unwrap();
+/// assert_eq!(dt.format(fmt).to_string(), "2025-01-15 18:16:00");
+///
+/// let dt = time::from_cn_str("2025年1月15日 8点").unwrap();
+/// assert_eq!(dt.format(fmt).to_string(), "2025-01-15 08:00:00");
+///
+/// let dt = time::from_cn_str("2025年01月15日").unwrap();
+/// assert_eq!(dt.format(fmt).to_string(), "2025-01-15 00:00:00");
+///
+/// let dt = time::from_cn_str("1月15号").unwrap();
+/// assert_eq!(dt.year(), Local::now().year());
+/// assert_eq!(dt.format("%m-%d %H:%M:%S").to_string(), "01-15 00:00:00");
+///
+/// let dt = time::from_cn_str("２０２５年１月１５日").unwrap();
+/// assert_eq!(dt.format(fmt).to_string(), "2025-01-15 00:00:00");
+///
+/// // 没有中文日期标记或日期不合法时返回 `Error::ParseTime`
+/// assert!(matches!(time::from_cn_str("2025-01-15"), Err(Error::ParseTime { .. })));
+/// assert!(time::from_cn_str("2025年13月15日").is_err());
+/// ```
+pub fn from_cn_str(s: &str) -> Result<DateTime<Local>> {
+    static CN_DATETIME: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^\s*(?:(\d{4})\s*年\s*)?(\d{1,2})\s*月\s*(\d{1,2})\s*[日号]?\s*(?:(\d{1,2})\s*[时点]\s*(?:(\d{1,2})\s*分\s*(?:(\d{1,2})\s*秒)?)?)?\s*$").unwrap()
+    });
+
+    if !s.contains(['年', '月', '日', '号', '时', '点', '分', '秒']) {
+        return Err(parse_error(s, CN_FORMAT));
+    }
+
+    // 全角数字转为半角
+    let normalized: String = s.chars()
+        .map(|c| match c {
+            '０'..='９' => char::from_u32(c as u32 - '０' as u32 + '0' as u32).unwrap_or(c),
+            _ => c,
+        })
+        .collect();
+    let invalid = || parse_error(s, CN_FORMAT);
+    let caps = CN_DATETIME.captures(&normalized).ok_or_else(invalid)?;
+    let num = |i: usize| caps.get(i).map(|m| m.as_str().parse::<u32>()).transpose().map_err(|_| invalid());
+
+    let year = match caps.get(1) {
+        Some(m) => m.as_str().parse().map_err(|_| invalid())?,
+        None => Local::now().year(),
+    };
+    let date = NaiveDate::from_ymd_opt(year, num(2)?.unwrap_or(1), num(3)?.unwrap_or(1))
+        .ok_or_else(invalid)?;
+    let time = NaiveTime::from_hms_opt(num(4)?.unwrap_or(0), num(5)?.unwrap_or(0), num(6)?.unwrap_or(0))
+        .ok_or_else(invalid)?;
+    resolve_local(&Local, &date.and_time(time), AmbiguityPolicy::Reject)
+}
+
+/// 自动识别常见格式解析时间字符串，不需要指定格式
+///
+/// 依次尝试：
+/// - 纯数字：8 位和 14 位按 `%Y%m%d`、`%Y%m%d%H%M%S` 解析，其他按秒、毫秒、微秒或纳秒时间戳解析，见 `Timestamp::detect`
+/// - 带时区的 ISO8601 / RFC3339（见 `from_rfc3339_lenient`）和 RFC2822，转为本地时间
+/// - `%Y-%m-%d %H:%M:%S`、`%Y/%m/%d %H:%M`、`%Y-%m-%dT%H:%M:%S%.f` 等不带时区的时间，以及 `%Y-%m-%d`、`%Y/%m/%d` 等日期，按本地时区解析
+/// - 中文日期，见 `from_cn_str`
+///
+/// 都不匹配时返回 `Error::ParseTime`，匹配但本地时间不存在或有歧义时返回 `Error::InvalidTime`
+///
+/// Examples
+///
+/// ```
+/// use chrono::{Local, TimeZone};
+/// use lazytool::time;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// for s in ["2025-01-15 18:16:13", "2025/01/15 18:16:13", "2025-01-15T18:16:13", "20250115181613"] {
+///     assert_eq!(time::parse(s).unwrap().format(fmt).to_string(), "2025-01-15 18:16:13", "{s}");
+/// }
+///
+/// // 秒或毫秒时间戳
+/// let expected = Local.timestamp_opt(1736936173, 0).unwrap();
+/// assert_eq!(time::parse("1736936173").unwrap(), expected);
+/// assert_eq!(time::parse("1736936173000").unwrap(), expected);
+/// for s in ["2025-01-15", "2025/1/15", "20250115", "2025年1月15日"] {
+///     assert_eq!(time::parse(s).unwrap().format(fmt).to_string(), "2025-01-15 00:00:00", "{s}");
+/// }
+///
+/// // 带时区的时间
+/// assert_eq!(time::parse("2025-01-15T10:16:13Z").unwrap().timestamp(), 1736936173);
+/// assert_eq!(time::parse("Wed, 15 Jan 2025 10:16:13 +0000").unwrap().timestamp(), 1736936173);
+///
+/// assert!(time::parse("next tuesday").is_err());
+/// assert!(time::parse("2025-13-01").is_err());
+/// ```
+pub fn parse(s: &str) -> Result<DateTime<Local>> {
+    const DATETIME_FORMATS: [&str; 8] = [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y/%m/%d %H:%M:%S%.f",
+        "%Y.%m.%d %H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+        "%Y/%m/%d %H:%M",
+        "%Y%m%d%H%M%S",
+    ];
+    const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d", "%Y%m%d"];
+
+    let s = s.trim();
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    let is_number = !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit());
+    if is_number && !matches!(s.len(), 8 | 14) {
+        let ts: i64 = s.parse().map_err(|_| parse_error(s, AUTO_FORMAT))?;
+        return Timestamp::detect(ts).to_datetime();
+    }
+
+    if let Ok(dt) = from_rfc3339_lenient(s).or_else(|_| DateTime::parse_from_rfc2822(s)) {
+        return Ok(dt.with_timezone(&Local));
+    }
+    let naive = DATETIME_FORMATS.iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .or_else(|| {
+            DATE_FORMATS.iter()
+                .find_map(|fmt| NaiveDate::parse_from_str(s, fmt).ok())
+                .map(|date| date.and_time(NaiveTime::MIN))
+        });
+    match naive {
+        Some(naive) => resolve_local(&Local, &naive, AmbiguityPolicy::Reject),
+        None => from_cn_str(s).map_err(|_| parse_error(s, AUTO_FORMAT)),
+    }
+}
+
+/// 两个时间的差值，`total_seconds` 带符号，分解后的各字段为绝对值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeDiff {
+    pub total_seconds: i64,
+    pub days: u64,
+    pub hours: u64,
+    pub minutes: u64,
+    pub seconds: u64,
+}
+
+impl TimeDiff {
+    /// 通过带符号的秒数构造
+    pub fn from_seconds(total_seconds: i64) -> Self {
+        let abs = total_seconds.unsigned_abs();
+        Self {
+            total_seconds,
+            days: abs / 86400,
+            hours: abs % 86400 / 3600,
+            minutes: abs % 3600 / 60,
+            seconds: abs % 60,
+        }
+    }
+
+    /// 是否为负数，即结束时间早于开始时间
+    pub fn is_negative(&self) -> bool {
+        self.total_seconds < 0
+    }
+
+    /// 英文紧凑格式，如 `3d 2h 15m`
+    pub fn to_string_en(&self) -> String {
+        let sign = if self.is_negative() { "-" } else { "" };
+        format!("{sign}{}", format_compact(self.total_seconds.unsigned_abs(), false))
+    }
+}
+
+/// 中文紧凑格式，如 `3天2小时15分`
+impl std::fmt::Display for TimeDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.is_negative() { "-" } else { "" };
+        write!(f, "{sign}{}", format_compact(self.total_seconds.unsigned_abs(), true))
+    }
+}
+
+/// 计算两个时间字符串的差值 `b - a`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// let d = time::diff("2025-01-12 16:01:00", "2025-01-15 18:16:00", fmt).unwrap();
+/// assert_eq!((d.days, d.hours, d.minutes, d.seconds), (3, 2, 15, 0));
+/// assert_eq!(d.to_string(), "3天2小时15分");
+/// assert_eq!(d.to_string_en(), "3d 2h 15m");
+///
+/// let d = time::diff("2025-01-15 18:16:13", "2025-01-15 18:15:58", fmt).unwrap();
+/// assert!(d.is_negative());
+/// assert_eq!(d.total_seconds, -15);
+/// assert_eq!(d.seconds, 15);
+/// assert_eq!(d.to_string(), "-15秒");
+///
+/// let d = time::diff("2025-01-15 18:16:13", "2025-01-15 18:16:13", fmt).unwrap();
+/// assert_eq!((d.total_seconds, d.days, d.hours, d.minutes, d.seconds), (0, 0, 0, 0, 0));
+/// assert_eq!(d.to_string(), "0秒");
+/// ```
+pub fn diff(a: &str, b: &str, fmt: &str) -> Result<TimeDiff> {
+    Ok(TimeDiff::from_seconds(duration_between(a, b, fmt)?))
+}
+
+/// 计算两个时间的差值 `b - a`，按实际时刻计算，不受夏令时影响
+///
+/// Examples
+///
+/// ```
+/// use chrono::{FixedOffset, TimeZone};
+/// use lazytool::time;
+///
+/// // 纽约 2024-03-10 凌晨跳过了一小时，UTC 偏移由 -05:00 变为 -04:00
+/// let est = FixedOffset::west_opt(5 * 3600).unwrap();
+/// let edt = FixedOffset::west_opt(4 * 3600).unwrap();
+/// let a = est.with_ymd_and_hms(2024, 3, 10, 0, 0, 0).unwrap();
+/// let b = edt.with_ymd_and_hms(2024, 3, 10, 4, 0, 0).unwrap();
+/// assert_eq!(time::diff_datetime(&a, &b).hours, 3);
+/// ```
+pub fn diff_datetime<A: TimeZone, B: TimeZone>(a: &DateTime<A>, b: &DateTime<B>) -> TimeDiff {
+    TimeDiff::from_seconds(b.timestamp() - a.timestamp())
+}
+
+/// 计算两个时间字符串相差的秒数 `b - a`，按本地时区解析，`b` 早于 `a` 时为负数
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// assert_eq!(time::duration_between("2025-01-15 18:16:13", "2025-01-15 18:17:00", fmt).unwrap(), 47);
+///
+/// // 跨越午夜
+/// assert_eq!(time::duration_between("2025-01-15 23:30:00", "2025-01-16 00:15:00", fmt).unwrap(), 45 * 60);
+/// assert_eq!(time::duration_between("2025-01-16 00:15:00", "2025-01-15 23:30:00", fmt).unwrap(), -45 * 60);
+///
+/// assert!(time::duration_between("2025-01-15", "2025-01-16 00:15:00", fmt).is_err());
+/// ```
+pub fn duration_between(a: &str, b: &str, fmt: &str) -> Result<i64> {
+    Ok(from_str(b, fmt)?.timestamp() - from_str(a, fmt)?.timestamp())
+}
+
+/// 按指定时区计算两个时间字符串相差的秒数 `b - a`，按实际时刻计算，不受夏令时影响
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+///
+/// let fmt = "%Y-%m-%d %H:%M:%S";
+/// // 纽约 2024-03-10 凌晨跳过了一小时
+/// let secs = time::duration_between_with_timezone("2024-03-09 23:00:00", "2024-03-10 04:00:00", fmt, "America/New_York").unwrap();
+/// assert_eq!(secs, 4 * 3600);
+///
+/// let secs = time::duration_between_with_timezone("2025-01-15 23:30:00", "2025-01-16 00:15:00", fmt, "Asia/Shanghai").unwrap();
+/// assert_eq!(secs, 45 * 60);
+///
+/// assert!(time::duration_between_with_timezone("2025-01-15 23:30:00", "2025-01-16 00:15:00", fmt, "Asia/Nowhere").is_err());
+/// ```
+#[cfg(feature = "timezone")]
+pub fn duration_between_with_timezone(a: &str, b: &str, fmt: &str, timezone: &str) -> Result<i64> {
+    Ok(to_timestamp_with_timezone(b, fmt, timezone)? - to_timestamp_with_timezone(a, fmt, timezone)?)
+}
+
+/// 解析 `1h30m`、`2d`、`45s`、`1.5h`、`1小时30分` 形式的时长，多个单位可以用空白分隔，不区分大小写
+///
+/// 支持的单位：`ms`/`毫秒`、`s`/`sec`/`秒`、`m`/`min`/`分`/`分钟`、`h`/`hr`/`时`/`小时`、`d`/`day`/`天`、`w`/`week`/`周`，
+/// 英文单位可以带复数 `s` 后缀。为空、缺少单位、单位未知或数值溢出时返回 `Error::InvalidTime`
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+/// use std::time::Duration;
+///
+/// assert_eq!(time::parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+/// assert_eq!(time::parse_duration("2d").unwrap(), Duration::from_secs(2 * 86400));
+/// assert_eq!(time::parse_duration("45s").unwrap(), Duration::from_secs(45));
+/// assert_eq!(time::parse_duration("1.5h").unwrap(), Duration::from_secs(5400));
+/// assert_eq!(time::parse_duration("1 hour 500ms").unwrap(), Duration::from_millis(3_600_500));
+/// assert_eq!(time::parse_duration("1小时30分").unwrap(), Duration::from_secs(5400));
+///
+/// assert!(time::parse_duration("").is_err());
+/// assert!(time::parse_duration("90").is_err());
+/// assert!(time::parse_duration("1y").is_err());
+/// ```
+pub fn parse_duration(s: &str) -> Result<std::time::Duration> {
+    static PART: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^\s*(\d+(?:\.\d+)?)\s*([a-zA-Z]+|[\p{Han}]+)").unwrap()
+    });
+    let invalid = || Error::InvalidTime(format!("invalid duration {s:?}"));
+
+    let mut rest = s.trim_end();
+    if rest.trim_start().is_empty() {
+        return Err(invalid());
+    }
+    let mut total = std::time::Duration::ZERO;
+    while !rest.is_empty() {
+        let caps = PART.captures(rest).ok_or_else(invalid)?;
+        let unit_secs: f64 = match caps[2].to_ascii_lowercase().as_str() {
+            "ms" | "毫秒" => 0.001,
+            "s" | "sec" | "secs" | "second" | "seconds" | "秒" | "秒钟" => 1.0,
+            "m" | "min" | "mins" | "minute" | "minutes" | "分" | "分钟" => 60.0,
+            "h" | "hr" | "hrs" | "hour" | "hours" | "时" | "小时" => 3600.0,
+            "d" | "day" | "days" | "天" => 86400.0,
+            "w" | "week" | "weeks" | "周" => 604800.0,
+            _ => return Err(invalid()),
+        };
+        let value: f64 = caps[1].parse().map_err(|_| invalid())?;
+        let part = std::time::Duration::try_from_secs_f64(value * unit_secs).map_err(|_| invalid())?;
+        total = total.checked_add(part).ok_or_else(invalid)?;
+        rest = &rest[caps[0].len()..];
+    }
+    Ok(total)
+}
+
+/// 将时长格式化为中文紧凑格式，如 `1小时30分`，省略为 0 的单位，不足一秒的部分舍去
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+/// use std::time::Duration;
+///
+/// assert_eq!(time::humanize_duration(Duration::from_secs(5400)), "1小时30分");
+/// assert_eq!(time::humanize_duration(Duration::from_secs(90061)), "1天1小时1分1秒");
+/// assert_eq!(time::humanize_duration(Duration::from_millis(500)), "0秒");
+///
+/// // 与 parse_duration 互逆
+/// let d = time::parse_duration("3d 2h 15m").unwrap();
+/// assert_eq!(time::parse_duration(&time::humanize_duration(d)).unwrap(), d);
+/// ```
+pub fn humanize_duration(d: std::time::Duration) -> String {
+    format_compact(d.as_secs(), true)
+}
+
+/// 将时长格式化为英文紧凑格式，如 `1h 30m`，省略为 0 的单位，不足一秒的部分舍去
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+/// use std::time::Duration;
+///
+/// assert_eq!(time::humanize_duration_en(Duration::from_secs(5400)), "1h 30m");
+/// assert_eq!(time::humanize_duration_en(Duration::from_secs(45)), "45s");
+/// assert_eq!(time::humanize_duration_en(Duration::ZERO), "0s");
+/// assert_eq!(time::parse_duration(&time::humanize_duration_en(Duration::from_secs(93784))).unwrap().as_secs(), 93784);
+/// ```
+pub fn humanize_duration_en(d: std::time::Duration) -> String {
+    format_compact(d.as_secs(), false)
+}
+
+/// 将秒数格式化为紧凑的 `1天2小时3分4秒` 或 `1d 2h 3m 4s`，省略为 0 的单位
+fn format_compact(secs: u64, zh: bool) -> String {
+    let units: [(u64, &str, &str); 4] = [
+        (secs / 86400, "天", "d"),
+        (secs % 86400 / 3600, "小时", "h"),
+        (secs % 3600 / 60, "分", "m"),
+        (secs % 60, "秒", "s"),
+    ];
+    let parts: Vec<String> = units.iter()
+        .filter(|(value, _, _)| *value > 0)
+        .map(|(value, zh_unit, en_unit)| format!("{value}{}", if zh { zh_unit } else { en_unit }))
+        .collect();
+    match (parts.is_empty(), zh) {
+        (true, true) => "0秒".to_string(),
+        (true, false) => "0s".to_string(),
+        (false, true) => parts.concat(),
+        (false, false) => parts.join(" "),
+    }
+}
+
+/// 计时结果，包含返回值与耗时
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timed<T> {
+    pub value: T,
+    pub elapsed: std::time::Duration,
+}
+
+impl<T> Timed<T> {
+    /// 拆分为返回值与耗时
+    pub fn into_parts(self) -> (T, std::time::Duration) {
+        (self.value, self.elapsed)
+    }
+}
+
+/// 计算闭包的执行耗时
+///
+/// Examples
+///
+/// ```no_run
+/// use lazytool::{time, Episode};
+///
+/// let paths: Vec<String> = (0..10000)
+///     .map(|i| format!("/还珠格格S01.国语中字.无台标.1080P/还珠格格S01E{:02}.mp4", i % 100))
+///     .collect();
+/// let (parsed, elapsed) = time::measure(|| {
+///     paths.iter().filter(|p| Episode::from_path(p).unwrap().is_some()).count()
+/// });
+/// assert_eq!(parsed, 10000);
+/// println!("parsed {parsed} paths in {elapsed:?}");
+/// ```
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, std::time::Duration) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed())
+}
+
+/// 计算闭包的执行耗时，并通过 `log` 以 info 级别输出 `{name} took 1.23s`，
+/// 是否显示由使用方的日志实现决定
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+/// use std::time::Duration;
+///
+/// let (value, elapsed) = time::measure_named("sleep", || {
+///     std::thread::sleep(Duration::from_millis(20));
+///     1
+/// });
+/// assert_eq!(value, 1);
+/// assert!(elapsed >= Duration::from_millis(20));
+/// assert!(elapsed < Duration::from_secs(2));
+/// ```
+pub fn measure_named<T>(name: &str, f: impl FnOnce() -> T) -> (T, std::time::Duration) {
+    let (value, elapsed) = measure(f);
+    log::info!("{name} took {}", format_elapsed(elapsed));
+    (value, elapsed)
+}
+
+/// 计算返回 `Result` 的闭包的执行耗时，无论成功或失败都带有耗时
+///
+/// Examples
+///
+/// ```
+/// use lazytool::time;
+/// use std::time::Duration;
+///
+/// let result = time::measure_result(|| {
+///     std::thread::sleep(Duration::from_millis(10));
+///     "x".parse::<i32>()
+/// });
+/// let err = result.unwrap_err();
+/// assert!(err.elapsed >= Duration::from_millis(10));
+///
+/// let ok = time::measure_result(|| "1".parse::<i32>()).unwrap();
+/// assert_eq!(ok.value, 1);
+/// ```
+pub fn measure_result<T, E>(f: impl FnOnce() -> std::result::Result<T, E>) -> std::result::Result<Timed<T>, Timed<E>> {
+    let (result, elapsed) = measure(f);
+    match result {
+        Ok(value) => Ok(Timed { value, elapsed }),
+        Err(value) => Err(Timed { value, elapsed }),
+    }
+}
+
+/// 格式化耗时，一分钟以内精确到小数点后两位，如 `1.23s`、`15.20ms`
+fn format_elapsed(elapsed: std::time::Duration) -> String {
+    if elapsed.as_secs() >= 60 {
+        format_compact(elapsed.as_secs(), false)
+    } else if elapsed.as_secs() >= 1 {
+        format!("{:.2}s", elapsed.as_secs_f64())
+    } else {
+        format!("{:.2}ms", elapsed.as_secs_f64() * 1000.0)
+    }
+}
+
+/// 本地时间在夏令时切换时出现歧义（回拨时重复）或不存在（跳过）时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AmbiguityPolicy {
+    /// 返回错误
+    #[default]
+    Reject,
+    /// 歧义时取较早的时间
+    Earliest,
+    /// 歧义时取较晚的时间
+    Latest,
+}
+
+/// 按策略将本地时间转换为带时区的时间
+///
+/// 不存在的时间在 `Earliest` 与 `Latest` 策略下都按跳变前的偏移量换算，即顺延跳过的时长，
+/// 例如纽约的 `02:30` 会得到 `03:30`
+fn resolve_local<T: TimeZone>(tz: &T, datetime: &NaiveDateTime, policy: AmbiguityPolicy) -> Result<DateTime<T>> {
+    match (tz.from_local_datetime(datetime), policy) {
+        (LocalResult::Single(dt), _) => Ok(dt),
+        (LocalResult::Ambiguous(_, _), AmbiguityPolicy::Reject) => {
+            Err(Error::InvalidTime(format!("ambiguous local time {datetime}")))
+        }
+        (LocalResult::Ambiguous(a, b), AmbiguityPolicy::Earliest) => Ok(a.min(b)),
+        (LocalResult::Ambiguous(a, b), AmbiguityPolicy::Latest) => Ok(a.max(b)),
+        (LocalResult::None, AmbiguityPolicy::Reject) => {
+            Err(Error::InvalidTime(format!("nonexistent local time {datetime}")))
+        }
+        (LocalResult::None, _) => {
+            // 取一天前的偏移量作为跳变前的偏移量
+            let before = tz.offset_from_utc_datetime(&(*datetime - Duration::days(1))).fix();
+            let utc = *datetime - Duration::seconds(before.local_minus_utc() as i64);
+            Ok(tz.from_utc_datetime(&utc))
+        }
+    }
+}
+
+/// 按格式解析不带时区的时间，失败时返回带有输入和格式的错误
+fn parse_naive(s: &str, fmt: &str) -> Result<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, fmt).map_err(|_| parse_error(s, fmt))
+}
+
+/// `from_cn_str` 解析失败时错误中的格式名称
+const CN_FORMAT: &str = "中文日期";
+
+/// `parse` 解析失败时错误中的格式名称
+const AUTO_FORMAT: &str = "自动识别";
+
+/// 时间字符串与格式不匹配的错误
+fn parse_error(s: &str, fmt: &str) -> Error {
+    Error::ParseTime { input: s.to_string(), fmt: fmt.to_string() }
+}
+
+/// 解析时区名称，失败时返回带有时区名称的错误
+#[cfg(feature = "timezone")]
+fn parse_timezone(timezone: &str) -> Result<Tz> {
+    timezone.parse().map_err(|_| Error::UnknownTimezone(timezone.to_string()))
+}